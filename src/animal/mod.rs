@@ -0,0 +1,370 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{bevy_animations::*, ldtk::*, GameState, item::{Item, Quality}, save::Savable, load_atlases::Atlases, level::LevelEntity, animations::set_animal_animations, mechanics::{perspective::PrimaryPerspectiveBody, interaction::*}};
+
+use self::systems::*;
+
+pub mod systems;
+
+pub const MAX_HAPPINESS: u32 = 100;
+/// animals start out content but not happy enough for better products
+pub const STARTING_HAPPINESS: u32 = 50;
+pub const FEED_HAPPINESS: u32 = 8;
+pub const PET_HAPPINESS: u32 = 4;
+/// lost overnight by animals that weren't fed
+pub const HUNGRY_HAPPINESS_LOSS: u32 = 20;
+pub const SILVER_PRODUCT_HAPPINESS: u32 = 60;
+pub const GOLD_PRODUCT_HAPPINESS: u32 = 90;
+/// how far in cells an animal wanders from where it is standing
+pub const WANDER_RADIUS: i32 = 5;
+/// how many random cells are tried before an animal gives up and rests again
+pub const WANDER_ATTEMPTS: usize = 8;
+/// animals rest for a random time in this range between wanders, in seconds
+pub const MIN_REST_SECONDS: f32 = 2.;
+pub const MAX_REST_SECONDS: f32 = 8.;
+pub const FEED_PROMPT: &str = "Feed";
+pub const PET_PROMPT: &str = "Pet";
+pub const COLLECT_PROMPT: &str = "Collect";
+
+/// **INFO** plugin for the farm animals
+///
+/// A new game starts with an animal on every LDtk `Animal` entity, after that animals are saved like crops and belong to the level they
+/// were placed in. Animals wander between random cells close by that they can reach on the `NavGrid` without going through a gate, so
+/// together with the fence colliders they stay inside their pens
+///
+/// Interacting with an animal collects its product if it has one, otherwise it eats a `Hay` from the inventory or, once it has been fed,
+/// gets petted. Every animal that was fed has a product the next morning, the happier it is the better the quality
+///
+/// **NOTE** the chicken and cow sheets are loaded by `load_altases` with 4 frames for each of the 4 directions
+pub struct AnimalPlugin;
+
+impl Plugin for AnimalPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkAnimalSpawn>("Animal")
+            .add_system_set(SystemSet::on_enter(GameState::LoadingNewGame)
+                .with_system(spawn_animals)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(set_animal_animations)
+                .with_system(plan_animal_wandering.before("walk_animals"))
+                .with_system(walk_animals.label("walk_animals"))
+                .with_system(interact_with_animals)
+                .with_system(tend_animals_overnight)
+                .with_system(update_animal_prompts)
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Game)
+                .with_system(stop_animals)
+            )
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AnimalKind {
+    #[default]
+    Chicken,
+    Cow
+}
+
+impl AnimalKind {
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Chicken" => Some(AnimalKind::Chicken),
+            "Cow" => Some(AnimalKind::Cow),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnimalKind::Chicken => "Chicken",
+            AnimalKind::Cow => "Cow"
+        }
+    }
+
+    pub fn atlas_name(&self) -> &'static str {
+        match self {
+            AnimalKind::Chicken => "chicken",
+            AnimalKind::Cow => "cow"
+        }
+    }
+
+    pub fn animation_name(&self) -> &'static str {
+        match self {
+            AnimalKind::Chicken => "chicken_walking",
+            AnimalKind::Cow => "cow_walking"
+        }
+    }
+
+    /// the size of a single frame in the sprite sheet
+    pub fn sprite_size(&self) -> Vec2 {
+        match self {
+            AnimalKind::Chicken | AnimalKind::Cow => Vec2::new(48., 32.)
+        }
+    }
+
+    pub fn collider_half_extents(&self) -> Vec2 {
+        match self {
+            AnimalKind::Chicken => Vec2::new(5., 4.),
+            AnimalKind::Cow => Vec2::new(14., 8.)
+        }
+    }
+
+    /// in pixels per second
+    pub fn walking_speed(&self) -> f32 {
+        match self {
+            AnimalKind::Chicken => 30.,
+            AnimalKind::Cow => 20.
+        }
+    }
+
+    pub fn product(&self, quality: Quality) -> Item {
+        match self {
+            AnimalKind::Chicken => Item::Egg(quality),
+            AnimalKind::Cow => Item::Milk(quality)
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Animal {
+    pub kind: AnimalKind,
+    pub name: String,
+    pub happiness: u32,
+    pub fed_today: bool,
+    pub petted_today: bool,
+    /// the quality of the product waiting to be collected
+    pub product: Option<Quality>
+}
+
+impl Animal {
+    pub fn new(kind: AnimalKind, name: String) -> Self {
+        Self {
+            kind,
+            name,
+            happiness: STARTING_HAPPINESS,
+            fed_today: false,
+            petted_today: false,
+            product: None
+        }
+    }
+
+    pub fn add_happiness(&mut self, happiness: u32) {
+        self.happiness = (self.happiness + happiness).min(MAX_HAPPINESS);
+    }
+
+    pub fn product_quality(&self) -> Quality {
+        if self.happiness >= GOLD_PRODUCT_HAPPINESS {
+            Quality::Gold
+        }
+        else if self.happiness >= SILVER_PRODUCT_HAPPINESS {
+            Quality::Silver
+        }
+        else {
+            Quality::Normal
+        }
+    }
+
+    /// fed animals make a product overnight, an uncollected product is kept until it is collected
+    pub fn new_day(&mut self) {
+        if self.fed_today {
+            if self.product.is_none() {
+                self.product = Some(self.product_quality());
+            }
+        }
+        else {
+            self.happiness = self.happiness.saturating_sub(HUNGRY_HAPPINESS_LOSS);
+        }
+        self.fed_today = false;
+        self.petted_today = false;
+    }
+
+    /// what interacting with the animal will do
+    pub fn prompt(&self) -> &'static str {
+        if self.product.is_some() {
+            COLLECT_PROMPT
+        }
+        else if !self.fed_today {
+            FEED_PROMPT
+        }
+        else {
+            PET_PROMPT
+        }
+    }
+}
+
+/// the path an animal is wandering along in world grid cells, once it is empty the animal rests before wandering again
+#[derive(Component, Debug, Clone, Default)]
+pub struct AnimalRoute {
+    pub path: VecDeque<IVec2>,
+    /// seconds left to rest
+    pub rest: f32
+}
+
+#[derive(Bundle)]
+pub struct AnimalBundle {
+    #[bundle]
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    pub animal: Animal,
+    pub route: AnimalRoute,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    pub direction: AnimationDirection,
+    pub interactable: Interactable,
+    pub perspective_body: PrimaryPerspectiveBody,
+    pub level_entity: LevelEntity,
+    pub savable: Savable
+}
+
+impl AnimalBundle {
+    pub fn new(animal: Animal, translation: Vec3, level: String, atlases: &Atlases) -> Self {
+        let half_extents = animal.kind.collider_half_extents();
+        Self {
+            sprite_sheet_bundle: SpriteSheetBundle {
+                texture_atlas: atlases.handles.get(animal.kind.atlas_name()).unwrap().clone(),
+                transform: Transform::from_translation(translation),
+                ..Default::default()
+            },
+            route: AnimalRoute::default(),
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(half_extents.x, half_extents.y),
+                // dynamic so the fence colliders keep animals in even when the player pushes them around
+                rigid_body: RigidBody::Dynamic,
+                velocity: Velocity::zero(),
+                rotation_constraints: LockedAxes::ROTATION_LOCKED,
+                ..Default::default()
+            },
+            direction: AnimationDirection::default(),
+            interactable: Interactable::new(animal.prompt(), 1, InteractionHandler::Animal),
+            perspective_body: PrimaryPerspectiveBody,
+            level_entity: LevelEntity(level),
+            savable: Savable,
+            animal
+        }
+    }
+}
+
+/// Placed in LDtk by level designers for the animals a new game starts with
+#[derive(Clone, Debug, Default, Component)]
+pub struct AnimalSpawn {
+    pub kind: AnimalKind,
+    pub name: String
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct LdtkAnimalSpawn {
+    pub animal_spawn: AnimalSpawn,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkAnimalSpawn {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            _: &LayerInstance,
+            _: Option<&Handle<Image>>,
+            _: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            _: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        let mut animal_spawn = AnimalSpawn::default();
+        let mut name = None;
+
+        for field in entity_instance.field_instances.iter() {
+            match (field.identifier.as_str(), &field.value) {
+                ("kind", FieldValue::Enum(Some(kind))) => {
+                    match AnimalKind::from_identifier(kind) {
+                        Some(kind) => animal_spawn.kind = kind,
+                        None => log::warn!("unknown animal kind {} in LDtk", kind)
+                    }
+                },
+                ("name", FieldValue::String(Some(value))) => name = Some(value.clone()),
+                _ => {}
+            }
+        }
+        animal_spawn.name = name.unwrap_or_else(|| animal_spawn.kind.name().to_string());
+
+        Self {
+            animal_spawn,
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animal(happiness: u32) -> Animal {
+        Animal {
+            happiness,
+            ..Animal::new(AnimalKind::Chicken, "Clucky".to_string())
+        }
+    }
+
+    #[test]
+    fn product_quality_follows_happiness() {
+        assert_eq!(animal(0).product_quality(), Quality::Normal);
+        assert_eq!(animal(SILVER_PRODUCT_HAPPINESS - 1).product_quality(), Quality::Normal);
+        assert_eq!(animal(SILVER_PRODUCT_HAPPINESS).product_quality(), Quality::Silver);
+        assert_eq!(animal(GOLD_PRODUCT_HAPPINESS - 1).product_quality(), Quality::Silver);
+        assert_eq!(animal(GOLD_PRODUCT_HAPPINESS).product_quality(), Quality::Gold);
+        assert_eq!(animal(MAX_HAPPINESS).product_quality(), Quality::Gold);
+    }
+
+    #[test]
+    fn fed_animals_make_a_product_overnight() {
+        let mut animal = animal(GOLD_PRODUCT_HAPPINESS);
+        animal.fed_today = true;
+        animal.petted_today = true;
+        animal.new_day();
+
+        assert_eq!(animal.product, Some(Quality::Gold));
+        assert_eq!(animal.happiness, GOLD_PRODUCT_HAPPINESS);
+        assert!(!animal.fed_today);
+        assert!(!animal.petted_today);
+    }
+
+    #[test]
+    fn uncollected_products_are_kept() {
+        let mut animal = animal(GOLD_PRODUCT_HAPPINESS);
+        animal.product = Some(Quality::Normal);
+        animal.fed_today = true;
+        animal.new_day();
+        assert_eq!(animal.product, Some(Quality::Normal));
+    }
+
+    #[test]
+    fn hungry_animals_lose_happiness_and_make_nothing() {
+        let mut animal = animal(STARTING_HAPPINESS);
+        animal.new_day();
+        assert_eq!(animal.product, None);
+        assert_eq!(animal.happiness, STARTING_HAPPINESS - HUNGRY_HAPPINESS_LOSS);
+
+        // happiness never goes below zero
+        for _ in 0..10 {
+            animal.new_day();
+        }
+        assert_eq!(animal.happiness, 0);
+    }
+
+    #[test]
+    fn prompt_collects_then_feeds_then_pets() {
+        let mut animal = animal(STARTING_HAPPINESS);
+        assert_eq!(animal.prompt(), FEED_PROMPT);
+
+        animal.fed_today = true;
+        assert_eq!(animal.prompt(), PET_PROMPT);
+
+        animal.product = Some(Quality::Normal);
+        assert_eq!(animal.prompt(), COLLECT_PROMPT);
+        animal.fed_today = false;
+        assert_eq!(animal.prompt(), COLLECT_PROMPT);
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::{prelude::*, log};
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+use crate::{player::{Player, systems::nearest_direction}, item::{Inventory, Item}, calendar::NewDayEvent, level::CurrentLevel, navigation::*};
+
+use super::*;
+
+pub fn spawn_animals(
+    mut commands: Commands,
+    animal_spawn_query: Query<(&AnimalSpawn, &GlobalTransform)>,
+    atlases: Res<Atlases>,
+    current_level: Res<CurrentLevel>
+) {
+    for (animal_spawn, transform) in animal_spawn_query.iter() {
+        commands.spawn(AnimalBundle::new(
+            Animal::new(animal_spawn.kind, animal_spawn.name.clone()),
+            transform.translation(),
+            current_level.identifier().to_string(),
+            &atlases
+        ));
+    }
+}
+
+/// picks a random cell close by for resting animals to wander to, only animals in the level the `NavGrid` was built for move
+pub fn plan_animal_wandering(
+    mut animal_query: Query<(&mut AnimalRoute, &LevelEntity, &GlobalTransform), With<Animal>>,
+    nav_grid: Res<NavGrid>,
+    time: Res<Time>
+) {
+    // the first level hasn't spawned yet
+    if nav_grid.grid_size == 0 {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+
+    for (mut route, level_entity, transform) in animal_query.iter_mut() {
+        if level_entity.0 != nav_grid.level || !route.path.is_empty() {
+            continue;
+        }
+        if route.rest > 0. {
+            route.rest -= time.delta_seconds();
+            continue;
+        }
+
+        let start = nav_grid.cell(transform.translation());
+        for _ in 0..WANDER_ATTEMPTS {
+            let goal = start + IVec2::new(rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS), rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS));
+            if goal == start {
+                continue;
+            }
+            if let Some(path) = nav_grid.find_path(start, goal, NavAgent::ANIMAL) {
+                route.path = path.into();
+                break;
+            }
+        }
+        route.rest = rng.gen_range(MIN_REST_SECONDS..MAX_REST_SECONDS);
+    }
+}
+
+/// walks animals from cell center to cell center along their route, animals in other levels stay where they are
+pub fn walk_animals(
+    mut animal_query: Query<(
+        Entity,
+        &Animal,
+        &mut AnimalRoute,
+        &mut Velocity,
+        &mut AnimationDirection,
+        &LevelEntity,
+        &GlobalTransform
+    )>,
+    nav_grid: Res<NavGrid>,
+    animations: Res<Animations>,
+    mut animation_event: EventWriter<AnimationEvent>,
+    time: Res<Time>
+) {
+    for (entity, animal, mut route, mut velocity, mut direction, level_entity, transform) in animal_query.iter_mut() {
+        velocity.linvel = Vec2::ZERO;
+        *direction = AnimationDirection::Still;
+        // the animations are set up the frame after the animal spawns
+        if !animations.is_inserted(&entity) || level_entity.0 != nav_grid.level {
+            continue;
+        }
+
+        let position = transform.translation().truncate();
+        let speed = animal.kind.walking_speed();
+        let step = speed * time.delta_seconds();
+        let mut target = None;
+        while let Some(cell) = route.path.front() {
+            let cell_center = nav_grid.cell_center(*cell);
+            if cell_center.distance(position) > step {
+                target = Some(cell_center);
+                break;
+            }
+            route.path.pop_front();
+        }
+
+        if let Some(target) = target {
+            let move_vec = (target - position).normalize_or_zero();
+            *direction = nearest_direction(move_vec);
+            velocity.linvel = move_vec * speed;
+            animation_event.send(AnimationEvent(animal.kind.animation_name(), entity));
+        }
+    }
+}
+
+/// collects the product of an animal, or feeds it hay from the inventory, or pets it
+pub fn interact_with_animals(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut animal_query: Query<&mut Animal>,
+    mut player_query: Query<&mut Inventory, With<Player>>
+) {
+    for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Animal) {
+        let mut animal = match animal_query.get_mut(event.entity) {
+            Ok(animal) => animal,
+            Err(_) => continue
+        };
+        let mut inventory = player_query.single_mut();
+
+        if let Some(quality) = animal.product {
+            let product = animal.kind.product(quality);
+            if inventory.add(product.clone(), 1) > 0 {
+                log::info!("no room in the inventory for the {}", product.name());
+                continue;
+            }
+            animal.product = None;
+            log::info!("collected {} from {}", product.name(), animal.name);
+        }
+        else if !animal.fed_today {
+            if !inventory.remove(&Item::Hay, 1) {
+                log::info!("{} is hungry but there is no hay in the inventory", animal.name);
+                continue;
+            }
+            animal.fed_today = true;
+            animal.add_happiness(FEED_HAPPINESS);
+            log::info!("fed {}", animal.name);
+        }
+        else if !animal.petted_today {
+            animal.petted_today = true;
+            animal.add_happiness(PET_HAPPINESS);
+            log::info!("petted {}, happiness {}", animal.name, animal.happiness);
+        }
+    }
+}
+
+pub fn tend_animals_overnight(
+    mut new_day_events: EventReader<NewDayEvent>,
+    mut animal_query: Query<&mut Animal>
+) {
+    for _ in new_day_events.iter() {
+        for mut animal in animal_query.iter_mut() {
+            animal.new_day();
+        }
+    }
+}
+
+pub fn update_animal_prompts(
+    mut animal_query: Query<(&Animal, &mut Interactable), Changed<Animal>>
+) {
+    for (animal, mut interactable) in animal_query.iter_mut() {
+        if interactable.prompt != animal.prompt() {
+            interactable.prompt = animal.prompt().to_string();
+        }
+    }
+}
+
+/// nothing sets the velocity of animals outside the game so they would keep walking through menus
+pub fn stop_animals(
+    mut animal_query: Query<&mut Velocity, With<Animal>>
+) {
+    for mut velocity in animal_query.iter_mut() {
+        velocity.linvel = Vec2::ZERO;
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use crate::{bevy_animations::*, player::*, gate::*, OtherAssets, GameState, load_atlases::Atlases};
+use crate::{bevy_animations::*, player::*, gate::*, npc::Villager, animal::{Animal, AnimalKind}, OtherAssets, GameState, load_atlases::Atlases, tool::ToolKind};
 
 // gate configs
 pub const GATE_OPENING_FRAMES: [usize; 3] = [0, 1, 2];
@@ -12,6 +12,21 @@ pub const RUNNUNG_ANIMATION_FRAMES: [usize; 6] = [0, 1, 6, 3, 4, 7];
 pub const HARVESTING_ANIMATION_FRAMES: [usize; 4] = [0, 1, 2, 3];
 pub const PLAYER_HARVESTING_TIMINGS: [f32; 4] = [0.300, 0.300, 0.350, 0.375];
 
+// tool configs, the player uses the farming sheet for every tool and the tool itself is drawn on top from `hoe_and_axe.png`
+pub const TOOL_ANIMATION_FRAMES: [usize; 4] = [0, 1, 2, 3];
+pub const HOE_TIMINGS: [f32; 4] = [0.150, 0.150, 0.200, 0.250];
+pub const WATERING_CAN_TIMINGS: [f32; 4] = [0.200, 0.300, 0.300, 0.200];
+pub const AXE_TIMINGS: [f32; 4] = [0.200, 0.150, 0.150, 0.250];
+pub const PICKAXE_TIMINGS: [f32; 4] = [0.250, 0.150, 0.150, 0.250];
+pub const SCYTHE_TIMINGS: [f32; 4] = [0.100, 0.100, 0.150, 0.200];
+pub const TOOL_SPRITE_FRAMES: [usize; 7] = [0, 1, 2, 3, 4, 5, 6];
+pub const TOOL_SPRITE_TIMINGS: [f32; 7] = [0.001, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1];
+
+// animal configs, every animal sheet has a row of 4 walking frames for each direction
+pub const ANIMAL_WALKING_FRAMES: [usize; 4] = [0, 1, 2, 3];
+pub const CHICKEN_METERS_PER_FRAME: f32 = 0.3;
+pub const COW_METERS_PER_FRAME: f32 = 0.6;
+
 pub fn set_animations(
     mut player_query: Query<(
         Entity,
@@ -77,35 +92,145 @@ pub fn set_animations(
             )
         )
     ;
+    for kind in ToolKind::ALL {
+        let timings = match kind {
+            ToolKind::Hoe => HOE_TIMINGS,
+            ToolKind::WateringCan => WATERING_CAN_TIMINGS,
+            ToolKind::Axe => AXE_TIMINGS,
+            ToolKind::Pickaxe => PICKAXE_TIMINGS,
+            ToolKind::Scythe => SCYTHE_TIMINGS
+        };
+        animations.insert_animation(player_entity, AnimationType::Timed(
+            TimedAnimation::new(
+                Vec::from(TOOL_ANIMATION_FRAMES),
+                Vec::from(timings),
+                farming_handle.clone(),
+                Vec2::new(4., 4.),
+                AnimationDirectionIndexes::new(4, 3, 2, 1),
+                false,
+                true,
+                1
+            ),
+            kind.animation_name()
+            )
+        );
+    }
     for query in gate_query.iter_mut() {
         let (gate_entity, mut texture, _) = query;
         *texture = gate_handle.clone();
-        if !animations.is_inserted(&gate_entity) {
-            animations.insert_animation(
-            gate_entity, 
-            AnimationType::LinearTimed(
-                LinearTimedAnimation::new(
-                    Vec::from(GATE_OPENING_FRAMES), 
-                        Vec::from(GATE_FRAME_TIMINGS), 
-                        texture_atlases.get_handle(gate_handle.clone()), 
-                        false
-                    ), 
-                    "gate_opening"
-                )
-            );
-            animations.insert_animation(
-                gate_entity, 
-                AnimationType::LinearTimed(
-                    LinearTimedAnimation::new(
-                        Vec::from(GATE_CLOSING_FRAMES), 
-                        Vec::from(GATE_FRAME_TIMINGS), 
-                        texture_atlases.get_handle(gate_handle.clone()), 
-                        false
-                    ), 
-                    "gate_closing"
-                )
-            );
-        }
+        insert_gate_animations(gate_entity, &mut animations, &texture_atlases, gate_handle);
     }
     state.overwrite_replace(GameState::LoadingGameMenu).unwrap();
-}
\ No newline at end of file
+}
+
+/// villagers share the players sprite sheet and walking animation, they spawn with their level so they are set up as they appear
+pub fn set_villager_animations(
+    mut villager_query: Query<(Entity, &mut Handle<TextureAtlas>), With<Villager>>,
+    mut animations: ResMut<Animations>,
+    atlases: Res<Atlases>
+) {
+    let farmer_handle = atlases.handles.get("player").unwrap();
+
+    for (villager_entity, mut texture) in villager_query.iter_mut() {
+        if animations.is_inserted(&villager_entity) {
+            continue;
+        }
+        *texture = farmer_handle.clone();
+        animations.insert_animation(villager_entity, AnimationType::Transform(
+            TransformAnimation::new(
+                Vec::from(WALKING_ANIMATION_FRAMES), 
+                0.65, 
+                farmer_handle.clone(), 
+                Vec2::new(8., 8.), 
+                AnimationDirectionIndexes::new(8, 7, 6, 5), 
+                true
+            ), 
+            "villager_walking"
+            )
+        );
+    }
+}
+
+/// every kind of animal has its own sheet and walking animation, animals can be spawned at any time so they are set up as they appear
+pub fn set_animal_animations(
+    mut animal_query: Query<(Entity, &Animal, &mut Handle<TextureAtlas>)>,
+    mut animations: ResMut<Animations>,
+    atlases: Res<Atlases>
+) {
+    for (animal_entity, animal, mut texture) in animal_query.iter_mut() {
+        if animations.is_inserted(&animal_entity) {
+            continue;
+        }
+        let animal_handle = atlases.handles.get(animal.kind.atlas_name()).unwrap();
+        let meters_per_frame = match animal.kind {
+            AnimalKind::Chicken => CHICKEN_METERS_PER_FRAME,
+            AnimalKind::Cow => COW_METERS_PER_FRAME
+        };
+        *texture = animal_handle.clone();
+        animations.insert_animation(animal_entity, AnimationType::Transform(
+            TransformAnimation::new(
+                Vec::from(ANIMAL_WALKING_FRAMES),
+                meters_per_frame,
+                animal_handle.clone(),
+                Vec2::new(4., 4.),
+                AnimationDirectionIndexes::new(4, 3, 2, 1),
+                true
+            ),
+            animal.kind.animation_name()
+            )
+        );
+    }
+}
+
+/// gates in levels that are spawned after the game has loaded still need their animations
+pub fn set_gate_animations(
+    mut gate_query: Query<(Entity, &mut Handle<TextureAtlas>), With<Gate>>,
+    mut animations: ResMut<Animations>,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    atlases: Res<Atlases>,
+) {
+    let gate_handle = atlases.handles.get("gate").unwrap();
+
+    for (gate_entity, mut texture) in gate_query.iter_mut() {
+        if animations.is_inserted(&gate_entity) {
+            continue;
+        }
+        *texture = gate_handle.clone();
+        insert_gate_animations(gate_entity, &mut animations, &texture_atlases, gate_handle);
+    }
+}
+
+fn insert_gate_animations(
+    gate_entity: Entity,
+    animations: &mut Animations,
+    texture_atlases: &Assets<TextureAtlas>,
+    gate_handle: &Handle<TextureAtlas>
+) {
+    if animations.is_inserted(&gate_entity) {
+        return;
+    }
+    animations.insert_animation(
+        gate_entity, 
+        AnimationType::LinearTimed(
+            LinearTimedAnimation::new(
+                Vec::from(GATE_OPENING_FRAMES), 
+                Vec::from(GATE_FRAME_TIMINGS), 
+                texture_atlases.get_handle(gate_handle.clone()), 
+                false
+            ), 
+            "gate_opening"
+        )
+    );
+    animations.insert_animation(
+        gate_entity, 
+        AnimationType::LinearTimed(
+            LinearTimedAnimation::new(
+                Vec::from(GATE_CLOSING_FRAMES), 
+                Vec::from(GATE_FRAME_TIMINGS), 
+                texture_atlases.get_handle(gate_handle.clone()), 
+                false
+            ), 
+            "gate_closing"
+        )
+    );
+}
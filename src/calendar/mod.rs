@@ -0,0 +1,166 @@
+use bevy::{prelude::*, log};
+use serde::{Serialize, Deserialize};
+use crate::{GameState, energy::SleepEvent};
+
+pub const DAYS_PER_SEASON: u32 = 28;
+/// the player wakes up at 6am
+pub const DAY_START_MINUTES: f32 = 6. * 60.;
+/// the player passes out at 2am if they haven't gone to sleep yet, which starts the next day
+pub const DAY_END_MINUTES: f32 = 26. * 60.;
+/// how many in-game minutes pass every real second
+pub const MINUTES_PER_SECOND: f32 = 1.;
+
+/// **INFO** plugin for the in-game date and time of day
+///
+/// A new day starts every time the player sleeps or passes out, everything that happens overnight listens for the `NewDayEvent`. The `Clock` only runs
+/// while the game isn't paused or in a menu and goes back to the morning every new day
+pub struct CalendarPlugin;
+
+impl Plugin for CalendarPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Calendar::default())
+            .insert_resource(Clock::default())
+            .add_event::<NewDayEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(advance_day.label("advance_day"))
+                .with_system(advance_clock)
+            )
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Fall,
+    Winter
+}
+
+impl Season {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Fall => "Fall",
+            Season::Winter => "Winter"
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Season::Spring => Season::Summer,
+            Season::Summer => Season::Fall,
+            Season::Fall => Season::Winter,
+            Season::Winter => Season::Spring
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Serialize, Deserialize)]
+pub struct Calendar {
+    /// the day of the season starting at 1
+    pub day: u32,
+    pub season: Season,
+    pub year: u32
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            day: 1,
+            season: Season::Spring,
+            year: 1
+        }
+    }
+}
+
+impl Calendar {
+    pub fn advance(&mut self) {
+        self.day += 1;
+        if self.day > DAYS_PER_SEASON {
+            self.day = 1;
+            self.season = self.season.next();
+            if self.season == Season::Spring {
+                self.year += 1;
+            }
+        }
+    }
+
+    /// days since the start of the game, the first day is 0
+    pub fn total_days(&self) -> u32 {
+        let season = match self.season {
+            Season::Spring => 0,
+            Season::Summer => 1,
+            Season::Fall => 2,
+            Season::Winter => 3
+        };
+        ((self.year - 1) * 4 + season) * DAYS_PER_SEASON + self.day - 1
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} {}, Year {}", self.season.name(), self.day, self.year)
+    }
+}
+
+/// The time of day in minutes since midnight of the current day, times after midnight go past 24 hours
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+pub struct Clock {
+    pub minutes: f32
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            minutes: DAY_START_MINUTES
+        }
+    }
+}
+
+impl Clock {
+    /// the hour of the day, 24 and 25 are the hours after midnight
+    pub fn hour(&self) -> u32 {
+        (self.minutes / 60.) as u32
+    }
+
+    pub fn describe(&self) -> String {
+        let hour = self.hour() % 24;
+        let minute = (self.minutes as u32 % 60) / 10 * 10;
+        let suffix = if hour < 12 { "am" } else { "pm" };
+        let hour = match hour % 12 {
+            0 => 12,
+            hour => hour
+        };
+        format!("{}:{:02}{}", hour, minute, suffix)
+    }
+}
+
+/// sent when a new day starts, the calendar has already moved on to it
+#[derive(Debug, Clone, Copy)]
+pub struct NewDayEvent(pub Calendar);
+
+pub fn advance_day(
+    mut sleep_events: EventReader<SleepEvent>,
+    mut calendar: ResMut<Calendar>,
+    mut clock: ResMut<Clock>,
+    mut new_day_events: EventWriter<NewDayEvent>
+) {
+    for _ in sleep_events.iter() {
+        calendar.advance();
+        *clock = Clock::default();
+        log::info!("a new day, {}", calendar.describe());
+        new_day_events.send(NewDayEvent(*calendar));
+    }
+}
+
+pub fn advance_clock(
+    mut clock: ResMut<Clock>,
+    time: Res<Time>
+) {
+    if clock.minutes >= DAY_END_MINUTES {
+        return;
+    }
+    clock.minutes = (clock.minutes + time.delta_seconds() * MINUTES_PER_SECOND).min(DAY_END_MINUTES);
+}
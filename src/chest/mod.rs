@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use crate::GameState;
+
+use self::systems::*;
+
+pub mod systems;
+
+/// **INFO** plugin for the chests the player places to store items in
+///
+/// Interacting with a chest opens the inventory in `InventoryMode::Chest` with the chests own slots shown above the players. Clicking a slot
+/// moves one item to the other side and holding the quick transfer action while clicking moves the whole stack. The contents are an
+/// `Inventory` next to the chests `PlacedObject` and are saved with it
+///
+/// **NOTE** chests are picked up by hitting them with the axe or pickaxe and only once they are empty so nothing in them gets lost
+pub struct ChestPlugin;
+
+impl Plugin for ChestPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<TransferEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(open_chests)
+                .with_system(pick_up_chests)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Inventory)
+                .with_system(transfer_items)
+            )
+        ;
+    }
+}
+
+/// which side of an open chest an inventory slot is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotOwner {
+    Player,
+    Chest
+}
+
+/// moves the stack in the slot to the other side of the open chest
+#[derive(Debug, Clone, Copy)]
+pub struct TransferEvent {
+    pub owner: SlotOwner,
+    pub slot: usize,
+    /// moves the whole stack instead of a single item
+    pub all: bool
+}
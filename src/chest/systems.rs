@@ -0,0 +1,71 @@
+use bevy::{prelude::*, log};
+use crate::{player::Player, item::{Inventory, InventoryMode, Item}, tool::{ToolUsedEvent, ToolKind}, crop::grid_coords, level::{CurrentLevel, LevelEntity}, placement::{PlacedObject, ObjectKind}, mechanics::interaction::*, ui::notification::NotificationEvent};
+
+use super::*;
+
+pub fn open_chests(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut inventory_mode: ResMut<InventoryMode>,
+    mut state: ResMut<State<GameState>>
+) {
+    if let Some(event) = interaction_events.iter().filter(|event| event.handler == InteractionHandler::Chest).last() {
+        *inventory_mode = InventoryMode::Chest(event.entity);
+        state.overwrite_set(GameState::Inventory).unwrap();
+    }
+}
+
+pub fn transfer_items(
+    mut transfer_events: EventReader<TransferEvent>,
+    inventory_mode: Res<InventoryMode>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut chest_query: Query<&mut Inventory, (With<PlacedObject>, Without<Player>)>
+) {
+    let chest = match *inventory_mode {
+        InventoryMode::Chest(entity) => entity,
+        _ => return
+    };
+    for event in transfer_events.iter() {
+        let (mut player_inventory, mut chest_inventory) = match (player_query.get_single_mut(), chest_query.get_mut(chest)) {
+            (Ok(player_inventory), Ok(chest_inventory)) => (player_inventory, chest_inventory),
+            _ => return
+        };
+        let (from, to) = match event.owner {
+            SlotOwner::Player => (&mut *player_inventory, &mut *chest_inventory),
+            SlotOwner::Chest => (&mut *chest_inventory, &mut *player_inventory)
+        };
+        let count = if event.all { u32::MAX } else { 1 };
+        if from.get(event.slot).is_some() && from.move_to(event.slot, count, to) == 0 {
+            log::info!("no room on the other side of the chest");
+        }
+    }
+}
+
+/// hitting an empty chest with the axe or pickaxe puts it back in the inventory
+pub fn pick_up_chests(
+    mut commands: Commands,
+    mut tool_used_events: EventReader<ToolUsedEvent>,
+    chest_query: Query<(Entity, &PlacedObject, &Inventory, &Transform, &LevelEntity), Without<Player>>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    current_level: Res<CurrentLevel>,
+    mut notification_events: EventWriter<NotificationEvent>
+) {
+    for event in tool_used_events.iter().filter(|event| matches!(event.tool.kind, ToolKind::Axe | ToolKind::Pickaxe)) {
+        for (entity, object, contents, transform, level_entity) in chest_query.iter() {
+            if object.kind != ObjectKind::Chest
+                || level_entity.0 != current_level.identifier()
+                || !event.cells.contains(&grid_coords(transform.translation, event.grid_size)) {
+                continue;
+            }
+            if !contents.is_empty() {
+                notification_events.send(NotificationEvent("Empty the chest before picking it up".to_string()));
+                continue;
+            }
+            let mut inventory = player_query.single_mut();
+            if inventory.add(Item::Chest, 1) > 0 {
+                log::info!("no room in the inventory for the chest");
+                continue;
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
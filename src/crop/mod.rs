@@ -5,10 +5,22 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use rand::Rng;
 use serde::{Serialize, Deserialize};
-use crate::{bevy_animations::*, ldtk::*, crop::systems::*, GameState, save::Savable, mechanics::perspective::SecondaryPerspectiveBody};
+use crate::{bevy_animations::*, ldtk::*, crop::systems::*, GameState, save::Savable, load_atlases::Atlases, mechanics::perspective::SecondaryPerspectiveBody, level::LevelEntity};
 
 // the crops chance to die from the player stepping on it
 pub const CROP_KILL_CHANCE: i32 = 30;
+// the number of growth stages in every crop sprite sheet
+pub const CROP_MAX_STAGE: usize = 5;
+// how far in front of the crop field the crop sprite is drawn
+pub const CROP_Z_OFFSET: f32 = 5.;
+pub const HARVEST_PROMPT: &str = "Harvest";
+// how much faster a watered crop grows
+pub const WATERED_GROWTH_MULTIPLIER: u32 = 2;
+// how much faster a fertilized crop grows, on top of watering
+pub const FERTILIZED_GROWTH_MULTIPLIER: u32 = 2;
+// tilled soil sits above the ground layers and below the crops planted on it
+pub const TILLED_SOIL_Z: f32 = 2.;
+pub const TILLED_SOIL_COLOR: Color = Color::rgb(0.36, 0.25, 0.17);
 
 pub mod systems;
 pub struct CropPlugin;
@@ -16,10 +28,11 @@ pub struct CropPlugin;
 impl Plugin for CropPlugin {
     fn build(&self, app: &mut App) {
         app
+            .register_ldtk_entity::<LdtkCrop>("Crop")
             .add_system_set(SystemSet::on_update(GameState::Game)
                 .with_system(check_crop_foot_collisions.label("foot"))
-                .with_system(check_crop_collisions_to_highlight.after("foot").label("highlight"))
-                .with_system(crop_liftime.after("highlight"))
+                .with_system(crop_liftime.after("foot"))
+                .with_system(apply_tools_to_crops)
             )
             .add_system_set(SystemSet::on_enter(GameState::LoadingNewGame)
                 .with_system(spawn_crops)
@@ -29,7 +42,51 @@ impl Plugin for CropPlugin {
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
-pub struct CropField;
+pub struct CropField {
+    pub grid_size: i32
+}
+
+/// A crop field the player made with the hoe instead of one from LDtk
+///
+/// the pickaxe turns it back into grass as long as nothing is planted on it
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct TilledSoil;
+
+#[derive(Bundle, Default)]
+pub struct TilledSoilBundle {
+    #[bundle]
+    pub sprite_bundle: SpriteBundle,
+    pub crop_field: CropField,
+    pub tilled_soil: TilledSoil,
+    pub level_entity: LevelEntity,
+    pub savable: Savable
+}
+
+impl TilledSoilBundle {
+    pub fn new(cell: IVec2, grid_size: i32, level: String) -> Self {
+        Self {
+            sprite_bundle: SpriteBundle {
+                sprite: Sprite {
+                    color: TILLED_SOIL_COLOR,
+                    custom_size: Some(Vec2::splat(grid_size as f32)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(((cell.as_vec2() + 0.5) * grid_size as f32).extend(TILLED_SOIL_Z)),
+                ..Default::default()
+            },
+            crop_field: CropField {
+                grid_size
+            },
+            tilled_soil: TilledSoil,
+            level_entity: LevelEntity(level),
+            savable: Savable
+        }
+    }
+}
+
+/// watered crops grow faster until they reach their next stage
+#[derive(Component, Debug, Default)]
+pub struct Watered;
 
 #[derive(Clone, Default, Bundle)]
 pub struct CropFieldBundle {
@@ -39,16 +96,89 @@ pub struct CropFieldBundle {
 }
 
 impl LdtkIntCell for CropFieldBundle {
-    fn bundle_int_cell(_: IntGridCell, _: &LayerInstance) -> Self {
+    fn bundle_int_cell(_: IntGridCell, layer_instance: &LayerInstance) -> Self {
         Self {
-            ..Default::default()
+            crop_field: CropField {
+                grid_size: layer_instance.grid_size
+            },
+            ldtk: Ldtk
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Component, Default)]
+/// Placed in LDtk by level designers to lay out the crops a new game starts with
+/// 
+/// A `Crop` entity on a crop field overrides the default crop for that field and with `planted` set to false it leaves the field empty
+#[derive(Clone, Debug, Default, Component)]
+pub struct CropSpawn {
+    pub crop_type: CropType,
+    pub stage: usize,
+    pub planted: bool,
+    pub grid_size: i32
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct LdtkCrop {
+    pub crop_spawn: CropSpawn,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkCrop {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            layer_instance: &LayerInstance,
+            _: Option<&Handle<Image>>,
+            _: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            _: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        let mut crop_spawn = CropSpawn {
+            crop_type: CropType::Corn,
+            stage: 1,
+            planted: true,
+            grid_size: layer_instance.grid_size
+        };
+
+        for field in entity_instance.field_instances.iter() {
+            match (field.identifier.as_str(), &field.value) {
+                ("crop_type", FieldValue::Enum(Some(crop_type))) => {
+                    match CropType::from_identifier(crop_type) {
+                        Some(crop_type) => crop_spawn.crop_type = crop_type,
+                        None => warn!("unknown crop type {} in LDtk", crop_type)
+                    }
+                },
+                ("stage", FieldValue::Int(Some(stage))) => crop_spawn.stage = (*stage as usize).clamp(1, CROP_MAX_STAGE),
+                ("planted", FieldValue::Bool(planted)) => crop_spawn.planted = *planted,
+                _ => {}
+            }
+        }
+
+        Self {
+            crop_spawn,
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Component, Default)]
+#[serde(from = "CropTypeRepr", into = "CropTypeRepr")]
 pub enum CropType {
     #[default]
+    Potato,
+    Carrot,
+    Corn,
+    Cabbage,
+    Dead
+}
+
+/// How `CropType` is written to saves
+///
+/// crops used to have a highlighted variant each. They are kept here so saves written while a crop was highlighted still load, and so the
+/// variant indices the binary codec writes stay the same
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum CropTypeRepr {
     Potato,
     PotatoHighlighted,
     CarrotHighlighted,
@@ -61,77 +191,103 @@ pub enum CropType {
     DeadHighlighted
 }
 
+impl From<CropTypeRepr> for CropType {
+    fn from(repr: CropTypeRepr) -> Self {
+        match repr {
+            CropTypeRepr::Potato | CropTypeRepr::PotatoHighlighted => CropType::Potato,
+            CropTypeRepr::Carrot | CropTypeRepr::CarrotHighlighted => CropType::Carrot,
+            CropTypeRepr::Corn | CropTypeRepr::CornHighlighted => CropType::Corn,
+            CropTypeRepr::Cabbage | CropTypeRepr::CabbageHighlighted => CropType::Cabbage,
+            CropTypeRepr::Dead | CropTypeRepr::DeadHighlighted => CropType::Dead
+        }
+    }
+}
+
+impl From<CropType> for CropTypeRepr {
+    fn from(crop_type: CropType) -> Self {
+        match crop_type {
+            CropType::Potato => CropTypeRepr::Potato,
+            CropType::Carrot => CropTypeRepr::Carrot,
+            CropType::Corn => CropTypeRepr::Corn,
+            CropType::Cabbage => CropTypeRepr::Cabbage,
+            CropType::Dead => CropTypeRepr::Dead
+        }
+    }
+}
+
 impl CropType {
+    /// maps the value of the `crop_type` enum field on LDtk `Crop` entities
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "Potato" => Some(CropType::Potato),
+            "Carrot" => Some(CropType::Carrot),
+            "Corn" => Some(CropType::Corn),
+            "Cabbage" => Some(CropType::Cabbage),
+            "Dead" => Some(CropType::Dead),
+            _ => None
+        }
+    }
+    /// the size of a single frame in the crops sprite sheet
+    pub fn sprite_size(&self) -> Vec2 {
+        match self {
+            CropType::Potato | CropType::Cabbage => Vec2::new(16., 28.),
+            CropType::Carrot | CropType::Dead => Vec2::new(16., 26.),
+            CropType::Corn => Vec2::new(16., 29.)
+        }
+    }
+    /// the inverse of `CropType::translation`, the center of the grid cell a crop at `translation` was planted on
+    pub fn cell_center(&self, translation: Vec3, grid_size: i32) -> Vec3 {
+        Vec3::new(
+            translation.x,
+            translation.y + grid_size as f32 / 2. - self.sprite_size().y / 2.,
+            translation.z - CROP_Z_OFFSET
+        )
+    }
+    /// where to place the crop so the bottom of its sprite sits on the bottom of the grid cell centered on `cell_center`
+    pub fn translation(&self, cell_center: Vec3, grid_size: i32) -> Vec3 {
+        Vec3::new(
+            cell_center.x,
+            cell_center.y - grid_size as f32 / 2. + self.sprite_size().y / 2.,
+            cell_center.z + CROP_Z_OFFSET
+        )
+    }
     pub fn atlas_name(&self) -> &'static str {
         match self {
             CropType::Potato => "potato_growth",
-            CropType::PotatoHighlighted => "potato_growth_highlighted",
             CropType::Carrot => "carrot_growth",
-            CropType::CarrotHighlighted => "carrot_growth_highlighted",
             CropType::Corn => "corn_growth",
-            CropType::CornHighlighted => "corn_growth_highlighted",
             CropType::Cabbage => "cabbage_growth",
-            CropType::CabbageHighlighted => "cabbage_growth_highlighted",
-            CropType::Dead => "dead_crop",
-            CropType::DeadHighlighted => "dead_crop_highlighted"
+            CropType::Dead => "dead_crop"
         }
     }
-    pub fn to_highlighted(&self) -> Self {
+    /// what the shipping bin pays for a normal quality crop
+    pub fn sell_price(&self) -> u32 {
         match self {
-            CropType::Potato => CropType::PotatoHighlighted,
-            CropType::PotatoHighlighted => CropType::PotatoHighlighted,
-            CropType::Carrot => CropType::CarrotHighlighted,
-            CropType::CarrotHighlighted => CropType::CarrotHighlighted,
-            CropType::Corn => CropType::CornHighlighted,
-            CropType::CornHighlighted => CropType::CornHighlighted,
-            CropType::Cabbage => CropType::CabbageHighlighted,
-            CropType::CabbageHighlighted => CropType::CabbageHighlighted,
-            CropType::Dead => CropType::DeadHighlighted,
-            CropType::DeadHighlighted => CropType::DeadHighlighted
-        }
-    }
-    pub fn to_normal(&self) -> Self {
-        match self {
-            CropType::Potato => CropType::Potato,
-            CropType::PotatoHighlighted => CropType::Potato,
-            CropType::Carrot => CropType::Carrot,
-            CropType::CarrotHighlighted => CropType::Carrot,
-            CropType::Corn => CropType::Corn,
-            CropType::CornHighlighted => CropType::Corn,
-            CropType::Cabbage => CropType::Cabbage,
-            CropType::CabbageHighlighted => CropType::Cabbage,
-            CropType::Dead => CropType::Dead,
-            CropType::DeadHighlighted => CropType::Dead
+            CropType::Potato => 35,
+            CropType::Carrot => 30,
+            CropType::Corn => 50,
+            CropType::Cabbage => 60,
+            CropType::Dead => 0
         }
     }
     pub fn duration(&self) -> f32 {
         let mut rng = rand::thread_rng();
         match self {
             CropType::Potato => rng.gen_range(50..75) as f32,
-            CropType::PotatoHighlighted => rng.gen_range(50..75) as f32,
             CropType::Carrot => rng.gen_range(80..100) as f32,
-            CropType::CarrotHighlighted => rng.gen_range(80..100) as f32,
             CropType::Corn => rng.gen_range(45..65) as f32,
-            CropType::CornHighlighted => rng.gen_range(45..65) as f32,
             CropType::Cabbage => rng.gen_range(100..125) as f32,
-            CropType::CabbageHighlighted => rng.gen_range(100..125) as f32,
             CropType::Dead => 1.,
-            CropType::DeadHighlighted => 1.,
         }
     }
     pub fn duration_from(value: &CropType) -> f32 {
         let mut rng = rand::thread_rng();
         match value {
             CropType::Potato => rng.gen_range(50..75) as f32,
-            CropType::PotatoHighlighted => rng.gen_range(50..75) as f32,
             CropType::Carrot => rng.gen_range(80..100) as f32,
-            CropType::CarrotHighlighted => rng.gen_range(80..100) as f32,
             CropType::Corn => rng.gen_range(45..65) as f32,
-            CropType::CornHighlighted => rng.gen_range(45..65) as f32,
             CropType::Cabbage => rng.gen_range(100..125) as f32,
-            CropType::CabbageHighlighted => rng.gen_range(100..125) as f32,
             CropType::Dead => 1.,
-            CropType::DeadHighlighted => 1.,
         }
     }
 }
@@ -141,16 +297,42 @@ pub struct Crop {
     pub stage: usize,
     pub crop_type: CropType,
     pub in_collision: bool,
+    /// fertilized crops grow faster for the rest of their life
+    #[serde(default)]
+    pub fertilized: bool
 }
 
 impl Crop {
-    fn new(crop_type: CropType) -> Self {
+    pub fn new(crop_type: CropType) -> Self {
         Self { 
             stage: 1, 
             crop_type,
-            in_collision: false
+            in_collision: false,
+            fertilized: false
         }
     }
+
+    /// fertilizer only goes on crops that are still growing
+    pub fn can_fertilize(&self) -> bool {
+        !self.fertilized && !self.is_ripe() && self.crop_type != CropType::Dead
+    }
+
+    /// fully grown crops give produce when they are harvested
+    pub fn is_ripe(&self) -> bool {
+        self.stage >= CROP_MAX_STAGE && self.crop_type != CropType::Dead
+    }
+
+    /// the grid cell the crop is planted on
+    pub fn cell(&self, translation: Vec3, grid_size: i32) -> IVec2 {
+        grid_coords(self.crop_type.cell_center(translation, grid_size), grid_size)
+    }
+
+    /// turns the crop into a dead one, whatever killed it
+    pub fn kill(&mut self, sprite: &mut TextureAtlasSprite, texture: &mut Handle<TextureAtlas>, atlases: &Atlases) {
+        sprite.index = 0;
+        self.crop_type = CropType::Dead;
+        *texture = atlases.handles.get(CropType::Dead.atlas_name()).unwrap().clone();
+    }
 }
 
 #[derive(Bundle, Default)]
@@ -163,9 +345,34 @@ pub struct CropBundle {
     pub secondary_perpective_body: SecondaryPerspectiveBody,
     pub rigid_body: RigidBody,
     pub animation_timer: AnimationTimer,
+    pub level_entity: LevelEntity,
     pub crop: Crop
 }
 
+impl CropBundle {
+    /// a crop planted on the grid cell centered on `cell_center`
+    pub fn new(crop: Crop, cell_center: Vec3, grid_size: i32, level: String, atlases: &Atlases) -> Self {
+        Self {
+            sprite_sheet_bundle: SpriteSheetBundle {
+                texture_atlas: atlases.handles.get(crop.crop_type.atlas_name()).unwrap().clone(),
+                sprite: TextureAtlasSprite::new(crop.stage - 1),
+                transform: Transform::from_translation(crop.crop_type.translation(cell_center, grid_size)),
+                ..Default::default()
+            },
+            sensor_bundle: SensorBundle {
+                collider: Collider::cuboid(8., 3.),
+                sensor: Sensor,
+                ..Default::default()
+            },
+            animation_timer: AnimationTimer(Timer::from_seconds(crop.crop_type.duration(), TimerMode::Repeating)),
+            rigid_body: RigidBody::KinematicPositionBased,
+            level_entity: LevelEntity(level),
+            crop,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct SmallCropColliderBundle {
     sensor_bundle: SensorBundle,
@@ -190,6 +397,10 @@ impl Default for SmallCropColliderBundle {
 #[derive(Component)]
 pub struct CropCollider;
 
+/// the grid cell containing `translation`
+pub fn grid_coords(translation: Vec3, grid_size: i32) -> IVec2 {
+    (translation.truncate() / grid_size as f32).floor().as_ivec2()
+}
+
 #[derive(Component)]
 pub struct CropTexture;
-
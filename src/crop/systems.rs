@@ -1,46 +1,82 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
-use crate::{bevy_animations::*, ldtk::*, OtherAssets, player::{Player, PlayerFootCollider, PlayerLargeCollider}, load_atlases::Atlases, mechanics::perspective::SecondaryPerspectiveBody};
+use crate::{bevy_animations::*, ldtk::*, OtherAssets, player::{Player, PlayerFootCollider}, load_atlases::Atlases, mechanics::{perspective::SecondaryPerspectiveBody, interaction::*}, level::{CurrentLevel, LevelEntity}, tool::{ToolUsedEvent, ToolKind}, item::{Inventory, Item}, fence::Fence, path::Path, placement::PlacedObject, skill::*};
 use rand::Rng;
 
 use super::*;
 
 pub fn spawn_crops(
     mut commands: Commands,
-    crop_field_query: Query<(&CropField, &Transform)>,
+    crop_field_query: Query<(&CropField, &GlobalTransform)>,
+    crop_spawn_query: Query<(&CropSpawn, &GlobalTransform)>,
     atlases: Res<Atlases>,
+    current_level: Res<CurrentLevel>,
     mut game_state: ResMut<State<GameState>>
 ) {
+    // crops placed in LDtk keyed by the grid cell they sit on
+    let mut crop_spawns: HashMap<IVec2, &CropSpawn> = HashMap::new();
 
-    let handle = atlases.handles.get("corn_growth").unwrap().clone();
+    for (crop_spawn, transform) in crop_spawn_query.iter() {
+        crop_spawns.insert(grid_coords(transform.translation(), crop_spawn.grid_size), crop_spawn);
+    }
 
-    for (_, transform) in crop_field_query.iter() {
-        let new_transform = Transform::from_translation(Vec3::new(transform.translation.x + 10., transform.translation.y + 20., transform.translation.z + 5.));
-        commands.spawn(SpriteSheetBundle {
-            texture_atlas: handle.clone(),
-            transform: new_transform,
-            ..Default::default()
-        })
-            .insert(SensorBundle {
+    let mut crops: Vec<(Crop, Vec3, i32)> = Vec::new();
+
+    for (crop_field, transform) in crop_field_query.iter() {
+        let crop = match crop_spawns.remove(&grid_coords(transform.translation(), crop_field.grid_size)) {
+            Some(crop_spawn) if !crop_spawn.planted => continue,
+            Some(crop_spawn) => Crop {
+                stage: crop_spawn.stage,
+                crop_type: crop_spawn.crop_type.clone(),
+                in_collision: false,
+                fertilized: false
+            },
+            // crop fields without a crop placed on them start with corn
+            None => Crop::new(CropType::Corn)
+        };
+        crops.push((crop, transform.translation(), crop_field.grid_size));
+    }
+
+    // crops that were placed outside of a crop field
+    for (coords, crop_spawn) in crop_spawns {
+        if !crop_spawn.planted {
+            continue;
+        }
+        let cell_center = ((coords.as_vec2() + 0.5) * crop_spawn.grid_size as f32).extend(0.);
+        crops.push((Crop {
+            stage: crop_spawn.stage,
+            crop_type: crop_spawn.crop_type.clone(),
+            in_collision: false,
+            fertilized: false
+        }, cell_center, crop_spawn.grid_size));
+    }
+
+    for (crop, cell_center, grid_size) in crops {
+        let handle = atlases.handles.get(crop.crop_type.atlas_name()).unwrap().clone();
+        commands.spawn(CropBundle {
+            sprite_sheet_bundle: SpriteSheetBundle {
+                texture_atlas: handle,
+                sprite: TextureAtlasSprite::new(crop.stage - 1),
+                transform: Transform::from_translation(crop.crop_type.translation(cell_center, grid_size)),
+                ..Default::default()
+            },
+            sensor_bundle: SensorBundle {
                 collider: Collider::cuboid(8., 3.),
                 sensor: Sensor,
                 ..Default::default()
-            })
-            .insert(Savable)
-            .insert(SecondaryPerspectiveBody)
-            .insert(Crop::new(CropType::Corn))
-            .insert(RigidBody::KinematicPositionBased)
-            .insert(AnimationTimer(Timer::from_seconds(CropType::duration_from(&CropType::Corn), TimerMode::Repeating)))
-            .with_children(|children| {
-                children.spawn(SensorBundle {
-                    collider: Collider::cuboid(1.5, 2.5),
-                    sensor: Sensor,
-                    ..Default::default()
-                })
-                    .insert(TransformBundle::from(Transform::from_xyz(0., -12., 0.)))
-                    .insert(CropCollider)
-                ;
+            },
+            animation_timer: AnimationTimer(Timer::from_seconds(crop.crop_type.duration(), TimerMode::Repeating)),
+            rigid_body: RigidBody::KinematicPositionBased,
+            level_entity: LevelEntity(current_level.identifier().to_string()),
+            crop,
+            ..Default::default()
+        })
+            .insert(Interactable::new(HARVEST_PROMPT, 0, InteractionHandler::Harvest))
+            .with_children(|parent| {
+                parent.spawn(SmallCropColliderBundle::default());
             })
         ;
     }
@@ -51,12 +87,14 @@ pub fn spawn_crops(
 pub fn check_crop_foot_collisions(
     mut commands: Commands,
     player_query: Query<Entity, With<PlayerFootCollider>>,
+    skills_query: Query<&Skills, With<Player>>,
     crop_collider_query: Query<(Entity, &Parent), With<CropCollider>>,
     mut crop_query: Query<(&mut TextureAtlasSprite, &mut Crop, &mut Handle<TextureAtlas>)>,
     context: Res<RapierContext>,
     atlases: Res<Atlases>
 ) {
     let player_foot_entity = player_query.single();
+    let trample_chance = skills_query.single().trample_chance();
 
     let mut rng = rand::thread_rng();
 
@@ -67,10 +105,8 @@ pub fn check_crop_foot_collisions(
         };
         if let Some(_) = context.intersection_pair(player_foot_entity, crop_collider_entity) {
             let random_number = rng.gen_range(0..100);
-            if random_number <= CROP_KILL_CHANCE && !crop.in_collision {
-                sprite.index = 0;
-                crop.crop_type = CropType::Dead;
-                *texture = atlases.handles.get("dead_crop").unwrap().clone();
+            if random_number <= trample_chance && !crop.in_collision {
+                crop.kill(&mut sprite, &mut texture, &atlases);
             } 
             crop.in_collision = true;
             continue;
@@ -79,100 +115,114 @@ pub fn check_crop_foot_collisions(
     }
 }
 
-/// check collisions for highlighing the crop
-pub fn check_crop_collisions_to_highlight(
-    mut player_query: Query<&mut Player>,
-    player_collider: Query<Entity, With<PlayerLargeCollider>>,
-    crop_collider_query: Query<(Entity, &Parent), With<CropCollider>>,
-    mut crop_query: Query<(&mut Handle<TextureAtlas>, &Crop, Entity)>,
-    context: Res<RapierContext>,
-    atlases: Res<Atlases>,
-) {
-
-    let mut player = player_query.single_mut();
-    // this entity is the large collider for the whole player
-    let player_entity = player_collider.single();
-
-    if player.harvesting {
-        return;
-    }
-
-
-    let mut collision = false;
-
-    // first loop is for checking collisions and setting textures to highlighted
-    for (crop_collider_entity, crop_parent) in crop_collider_query.iter() {
-        let (mut texture, crop, texture_entity) = match crop_query.get_mut(crop_parent.get()) {
-            Ok(q) => q,
-            Err(_) => continue
-        };
-
-        if let Some(_) = context.intersection_pair(player_entity, crop_collider_entity) {
-            if let Some(colliding_entity) = player.crop_colliding {
-                if colliding_entity.index() != texture_entity.index() {
-                    player.crop_colliding = Some(texture_entity);
-                    player.previous_crop_colliding = Some(colliding_entity);
-                    *texture = atlases.handles.get(crop.crop_type.to_highlighted().atlas_name()).unwrap().clone();
-                }
-            }
-            else {
-                player.crop_colliding = Some(texture_entity);
-                *texture = atlases.handles.get(crop.crop_type.to_highlighted().atlas_name()).unwrap().clone();
-            }
-            collision = true;
-            break;  
-        }
-    }
-
-    // set the texture of the previous colliding entity back to normal
-    if collision {
-        if let Some(previous_colliding_entity) = player.previous_crop_colliding {
-            let (mut texture, crop, _) = match crop_query.get_mut(previous_colliding_entity) {
-                Ok(q) => q,
-                Err(_) => {
-                    return;
-                }
-            };
-            player.previous_crop_colliding = None;
-            *texture = atlases.handles.get(crop.crop_type.to_normal().atlas_name()).unwrap().clone();
-        }
-    }
-    else {
-        let colliding_entity = match player.crop_colliding {
-            Some(entity) => entity,
-            None => return
-        };
-        let (mut texture, crop, _) = match crop_query.get_mut(colliding_entity) {
-            Ok(q) => q,
-            Err(_) => {
-                return;
-            }
-        };
-        player.previous_crop_colliding = None;
-        player.crop_colliding = None;
-        *texture = atlases.handles.get(crop.crop_type.to_normal().atlas_name()).unwrap().clone();    
-    }
-}
-
 /// system for cycling the lifetime of the crop
 pub fn crop_liftime (
-    mut crop_query: Query<(&mut AnimationTimer, &mut Crop, &mut TextureAtlasSprite,)>,
+    mut commands: Commands,
+    mut crop_query: Query<(Entity, &mut AnimationTimer, &mut Crop, &mut TextureAtlasSprite, Option<&Watered>)>,
     time: Res<Time>
 ) {
-    for (mut timer, mut crop, mut sprite) in crop_query.iter_mut() {
-        timer.tick(time.delta());
+    for (entity, mut timer, mut crop, mut sprite, watered) in crop_query.iter_mut() {
+        let mut delta = time.delta();
+        if watered.is_some() {
+            delta *= WATERED_GROWTH_MULTIPLIER;
+        }
+        if crop.fertilized {
+            delta *= FERTILIZED_GROWTH_MULTIPLIER;
+        }
+        timer.tick(delta);
         // if we have changed the crop stage anywhere else we make sure to change the sprite as well
-        if sprite.index != crop.stage - 1 && crop.crop_type != CropType::Dead && crop.crop_type != CropType::DeadHighlighted {
+        if sprite.index != crop.stage - 1 && crop.crop_type != CropType::Dead {
             println!("setting sprite index");
             sprite.index = crop.stage - 1;
         }
-        if timer.finished() && crop.crop_type != CropType::Dead && crop.crop_type != CropType::DeadHighlighted {
-            if crop.stage + 1 > 5 {
+        if timer.finished() && crop.crop_type != CropType::Dead {
+            if crop.stage + 1 > CROP_MAX_STAGE {
                 return;
             }
             sprite.index = crop.stage - 1;
             crop.stage += 1;
             timer.reset();
+            // the crop needs watering again for its next stage
+            if watered.is_some() {
+                commands.entity(entity).remove::<Watered>();
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// what the hoe, watering can, scythe and pickaxe do to the cells they are used on
+pub fn apply_tools_to_crops(
+    mut commands: Commands,
+    mut tool_used_events: EventReader<ToolUsedEvent>,
+    crop_query: Query<(Entity, &Transform, &Crop, &LevelEntity)>,
+    crop_field_query: Query<(Entity, &CropField, &GlobalTransform, Option<&TilledSoil>, Option<&LevelEntity>)>,
+    blocked_query: Query<&GlobalTransform, Or<(With<Fence>, With<Path>, With<PlacedObject>)>>,
+    mut player_query: Query<(&mut Inventory, &Skills), With<Player>>,
+    current_level: Res<CurrentLevel>,
+    mut experience_events: EventWriter<ExperienceEvent>
+) {
+    for event in tool_used_events.iter() {
+        let grid_size = event.grid_size;
+        let in_level = |level_entity: Option<&LevelEntity>| level_entity.map_or(true, |level_entity| level_entity.0 == current_level.identifier());
+
+        let crops: Vec<(Entity, &Crop, IVec2)> = crop_query.iter()
+            .filter(|(_, _, _, level_entity)| in_level(Some(level_entity)))
+            .map(|(entity, transform, crop, _)| (entity, crop, crop.cell(transform.translation, grid_size)))
+            .filter(|(_, _, cell)| event.cells.contains(cell))
+            .collect();
+
+        match event.tool.kind {
+            ToolKind::Hoe => {
+                for cell in event.cells.iter() {
+                    let taken = crop_field_query.iter()
+                        .filter(|(_, _, _, _, level_entity)| in_level(*level_entity))
+                        .any(|(_, crop_field, transform, _, _)| grid_coords(transform.translation(), crop_field.grid_size) == *cell);
+                    let blocked = blocked_query.iter().any(|transform| grid_coords(transform.translation(), grid_size) == *cell);
+                    if taken || blocked {
+                        continue;
+                    }
+                    commands.spawn(TilledSoilBundle::new(*cell, grid_size, current_level.identifier().to_string()));
+                }
+            },
+            ToolKind::WateringCan => {
+                for (entity, _, _) in crops {
+                    commands.entity(entity).insert(Watered);
+                }
+            },
+            ToolKind::Scythe => {
+                let (mut inventory, skills) = player_query.single_mut();
+                let mut rng = rand::thread_rng();
+                for (entity, crop, _) in crops {
+                    if crop.is_ripe() {
+                        let quality = skills.roll_crop_quality(&mut rng);
+                        if inventory.add(Item::Crop(crop.crop_type.clone(), quality), 1) > 0 {
+                            warn!("no room in the inventory for {:?}", crop.crop_type);
+                            continue;
+                        }
+                        experience_events.send(ExperienceEvent {
+                            skill: Skill::Farming,
+                            amount: HARVEST_EXPERIENCE
+                        });
+                    }
+                    else if crop.crop_type != CropType::Dead {
+                        continue;
+                    }
+                    commands.entity(entity).despawn_recursive();
+                }
+            },
+            ToolKind::Pickaxe => {
+                for (entity, _, transform, tilled_soil, level_entity) in crop_field_query.iter() {
+                    let cell = grid_coords(transform.translation(), grid_size);
+                    if tilled_soil.is_none() || !in_level(level_entity) || !event.cells.contains(&cell) {
+                        continue;
+                    }
+                    if crops.iter().any(|(_, _, crop_cell)| *crop_cell == cell) {
+                        continue;
+                    }
+                    commands.entity(entity).despawn_recursive();
+                }
+            },
+            ToolKind::Axe => {}
+        }
+    }
+}
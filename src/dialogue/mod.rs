@@ -0,0 +1,303 @@
+use std::{collections::{HashMap, HashSet}, fs::read_to_string};
+
+use bevy::{prelude::*, log};
+use serde::{Serialize, Deserialize};
+use crate::{GameState, item::{Item, Inventory}, economy::Wallet, calendar::{Calendar, Clock, Season}, npc::friendship::{Relationship, FriendshipConfig}};
+
+use self::systems::*;
+
+pub mod systems;
+
+pub const DIALOGUE_PATH: &str = "assets/data/dialogue.rson";
+pub const TALK_PROMPT: &str = "Talk";
+pub const TYPEWRITER_CHARS_PER_SECOND: f32 = 45.;
+/// nodes without any lines are passed straight through, this stops two of them pointing at each other from looping forever
+pub const MAX_EMPTY_NODES: usize = 16;
+
+/// **INFO** plugin for talking to villagers
+///
+/// Everything villagers say is read from `assets/data/dialogue.rson`. A dialogue is a set of named nodes, each with some lines, the choices
+/// shown after the last line and the node to go to next. Dialogues start at the first of their `start` entries whose conditions are met and
+/// nodes and choices can have effects like giving items, setting flags or changing how much the villager likes the player
+///
+/// Items are never given if they don't fit in the inventory. Choices that give more than fits aren't shown and nodes that do go to their
+/// `no_room` node instead without applying any of their effects
+///
+/// **NOTE** the dialogue box is drawn by the ui, the systems here only move the `ActiveDialogue` along with the interact and movement actions
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(DialogueLibrary::read())
+            .insert_resource(DialogueFlags::default())
+            .insert_resource(ActiveDialogue::default())
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(open_dialogue)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Dialogue)
+                .with_system(advance_dialogue)
+            )
+        ;
+    }
+}
+
+/// has to be true for a dialogue to start on a node or for a choice to be shown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// the player has at least this many of the item
+    HasItem(Item, u32),
+    /// the hour is at least the first and before the second
+    Hours(u32, u32),
+    Season(Season),
+    Flag(String),
+    NotFlag(String),
+    /// the villager has at least this many relationship points with the player
+    Friendship(i32),
+    /// the villager hasn't had a gift from the player today
+    CanGift
+}
+
+/// what happens when a node is reached or a choice is picked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    GiveItem(Item, u32),
+    TakeItem(Item, u32),
+    GiveCoins(u32),
+    SetFlag(String),
+    ClearFlag(String),
+    Friendship(i32),
+    /// opens the inventory to give the villager a gift once the dialogue closes
+    OfferGift
+}
+
+/// everything conditions look at and effects change
+pub struct DialogueContext<'a> {
+    pub inventory: &'a mut Inventory,
+    pub wallet: &'a mut Wallet,
+    pub flags: &'a mut DialogueFlags,
+    /// the relationship with the villager being talked to
+    pub relationship: &'a mut Relationship,
+    pub friendship_config: &'a FriendshipConfig,
+    pub calendar: &'a Calendar,
+    pub clock: &'a Clock,
+    /// set by the `OfferGift` effect
+    pub offer_gift: bool
+}
+
+impl Condition {
+    pub fn met(&self, context: &DialogueContext) -> bool {
+        match self {
+            Condition::HasItem(item, count) => context.inventory.count(item) >= *count,
+            Condition::Hours(from, to) => (*from..*to).contains(&context.clock.hour()),
+            Condition::Season(season) => context.calendar.season == *season,
+            Condition::Flag(flag) => context.flags.0.contains(flag),
+            Condition::NotFlag(flag) => !context.flags.0.contains(flag),
+            Condition::Friendship(points) => context.relationship.points >= *points,
+            Condition::CanGift => !context.relationship.gifted_today
+        }
+    }
+}
+
+impl Effect {
+    pub fn apply(&self, context: &mut DialogueContext) {
+        match self {
+            Effect::GiveItem(item, count) => {
+                // `effects_fit` is checked before any effects are applied so this shouldn't happen
+                let left_over = context.inventory.add(item.clone(), *count);
+                if left_over > 0 {
+                    log::warn!("no room in the inventory for {} {}", left_over, item.name());
+                }
+            },
+            Effect::TakeItem(item, count) => {
+                if !context.inventory.remove(item, *count) {
+                    log::warn!("the player doesn't have {} {} to give", count, item.name());
+                }
+            },
+            Effect::GiveCoins(coins) => context.wallet.earn(*coins),
+            Effect::SetFlag(flag) => {
+                context.flags.0.insert(flag.clone());
+            },
+            Effect::ClearFlag(flag) => {
+                context.flags.0.remove(flag);
+            },
+            Effect::Friendship(points) => context.relationship.add_points(*points, context.friendship_config),
+            Effect::OfferGift => context.offer_gift = true
+        }
+    }
+}
+
+fn all_met(conditions: &[Condition], context: &DialogueContext) -> bool {
+    conditions.iter().all(|condition| condition.met(context))
+}
+
+/// whether everything `effects` give fits in the inventory once the items they take are gone
+pub fn effects_fit(effects: &[Effect], inventory: &Inventory) -> bool {
+    let mut inventory = inventory.clone();
+    effects.iter().all(|effect| match effect {
+        Effect::GiveItem(item, count) => inventory.add(item.clone(), *count) == 0,
+        Effect::TakeItem(item, count) => {
+            inventory.remove(item, *count);
+            true
+        },
+        _ => true
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Choice {
+    pub text: String,
+    /// the node to go to, the dialogue ends if it is `None`
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub effects: Vec<Effect>
+}
+
+impl Choice {
+    pub fn available(&self, context: &DialogueContext) -> bool {
+        all_met(&self.conditions, context) && effects_fit(&self.effects, context.inventory)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DialogueNode {
+    /// shown one after the other
+    #[serde(default)]
+    pub lines: Vec<String>,
+    /// shown with the last line, choosing one replaces `next`
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+    /// applied as soon as the node is reached
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// the node to go to after the last line when there are no choices, the dialogue ends if it is `None`
+    #[serde(default)]
+    pub next: Option<String>,
+    /// the node to go to instead when the items the effects give don't fit in the inventory, the dialogue ends if it is `None`
+    #[serde(default)]
+    pub no_room: Option<String>
+}
+
+/// a node a dialogue can start on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DialogueStart {
+    pub node: String,
+    #[serde(default)]
+    pub conditions: Vec<Condition>
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dialogue {
+    /// the first one whose conditions are met is used
+    pub start: Vec<DialogueStart>,
+    pub nodes: HashMap<String, DialogueNode>
+}
+
+impl Dialogue {
+    pub fn start_node(&self, context: &DialogueContext) -> Option<&str> {
+        self.start.iter()
+            .find(|start| all_met(&start.conditions, context))
+            .map(|start| start.node.as_str())
+    }
+}
+
+/// every dialogue by the name villagers refer to it with, read from `assets/data/dialogue.rson`
+#[derive(Debug, Resource, Default, Serialize, Deserialize)]
+pub struct DialogueLibrary {
+    pub dialogues: HashMap<String, Dialogue>
+}
+
+impl DialogueLibrary {
+    /// nobody has anything to say if the file is missing or invalid
+    pub fn read() -> Self {
+        match read_to_string(DIALOGUE_PATH) {
+            Ok(library) => ron::from_str::<DialogueLibrary>(&library.as_str()).unwrap_or_else(|err| {
+                log::warn!("invalid dialogue, villagers won't talk: {}", err);
+                DialogueLibrary::default()
+            }),
+            Err(_) => {
+                log::warn!("no dialogue at {}, villagers won't talk", DIALOGUE_PATH);
+                DialogueLibrary::default()
+            }
+        }
+    }
+}
+
+/// flags set by dialogue effects, they stay set across days and are saved
+#[derive(Debug, Resource, Default, Clone)]
+pub struct DialogueFlags(pub HashSet<String>);
+
+/// the conversation being had, only meaningful in `GameState::Dialogue`
+#[derive(Debug, Resource, Default)]
+pub struct ActiveDialogue {
+    pub villager: Option<Entity>,
+    /// the name of the villager shown above the text
+    pub speaker: String,
+    pub dialogue: String,
+    pub node: String,
+    pub line: usize,
+    /// how many characters of the line the typewriter has shown
+    pub revealed: f32,
+    /// the indices of the choices of the node whose conditions were met
+    pub choices: Vec<usize>,
+    pub selected: usize,
+    /// the inventory opens in gift mode instead of going back to the game once the dialogue closes
+    pub offer_gift: bool
+}
+
+impl ActiveDialogue {
+    pub fn current_node<'a>(&self, library: &'a DialogueLibrary) -> Option<&'a DialogueNode> {
+        library.dialogues.get(&self.dialogue)?.nodes.get(&self.node)
+    }
+
+    pub fn current_line<'a>(&self, library: &'a DialogueLibrary) -> Option<&'a str> {
+        self.current_node(library)?.lines.get(self.line).map(|line| line.as_str())
+    }
+
+    /// the part of the current line the typewriter has got to
+    pub fn revealed_text(&self, library: &DialogueLibrary) -> String {
+        self.current_line(library)
+            .map(|line| line.chars().take(self.revealed as usize).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn line_finished(&self, library: &DialogueLibrary) -> bool {
+        self.current_line(library).map_or(true, |line| self.revealed as usize >= line.chars().count())
+    }
+
+    /// choices are only shown once the last line has been typed out
+    pub fn showing_choices(&self, library: &DialogueLibrary) -> bool {
+        let last_line = self.current_node(library).map_or(true, |node| self.line + 1 >= node.lines.len());
+        last_line && !self.choices.is_empty() && self.line_finished(library)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{crop::CropType, item::{CHEST_INVENTORY_SIZE, MAX_STACK_SIZE}};
+    use super::*;
+
+    #[test]
+    fn given_items_have_to_fit() {
+        let seeds = Effect::GiveItem(Item::Seeds(CropType::Carrot), 5);
+        let mut inventory = Inventory::new(1);
+        assert!(effects_fit(&[seeds.clone(), Effect::SetFlag("gift".to_string())], &inventory));
+
+        inventory.add(Item::Stone, 1);
+        assert!(!effects_fit(&[seeds.clone()], &inventory));
+        // taking the stone first makes room
+        assert!(effects_fit(&[Effect::TakeItem(Item::Stone, 1), seeds.clone()], &inventory));
+        assert!(!effects_fit(&[seeds.clone(), Effect::TakeItem(Item::Stone, 1)], &inventory));
+    }
+
+    #[test]
+    fn given_items_fit_together() {
+        let inventory = Inventory::new(CHEST_INVENTORY_SIZE);
+        let stacks = CHEST_INVENTORY_SIZE as u32;
+        assert!(effects_fit(&[Effect::GiveItem(Item::Wood, MAX_STACK_SIZE * stacks)], &inventory));
+        assert!(!effects_fit(&[Effect::GiveItem(Item::Wood, MAX_STACK_SIZE * stacks), Effect::GiveItem(Item::Stone, 1)], &inventory));
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::{prelude::*, log};
+use crate::{GameState, player::Player, input::*, item::InventoryMode, npc::{Villager, Relationships, friendship::FriendshipConfig}, mechanics::interaction::*};
+
+use super::*;
+
+/// moves the dialogue on to `node`, passing through nodes without lines. Returns false once there is nothing left to say
+fn enter_node(active: &mut ActiveDialogue, node: Option<String>, dialogue: &Dialogue, context: &mut DialogueContext) -> bool {
+    let mut next = node;
+    for _ in 0..MAX_EMPTY_NODES {
+        let (id, node) = match next.and_then(|id| dialogue.nodes.get(&id).map(|node| (id, node))) {
+            Some(node) => node,
+            None => return false
+        };
+        if !effects_fit(&node.effects, context.inventory) {
+            next = node.no_room.clone();
+            continue;
+        }
+        for effect in node.effects.iter() {
+            effect.apply(context);
+        }
+        if node.lines.is_empty() {
+            next = node.next.clone();
+            continue;
+        }
+        active.node = id;
+        active.line = 0;
+        active.revealed = 0.;
+        active.choices = node.choices.iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.available(context))
+            .map(|(index, _)| index)
+            .collect();
+        active.selected = 0;
+        return true;
+    }
+    log::warn!("dialogue {} passes through too many empty nodes", active.dialogue);
+    false
+}
+
+pub fn open_dialogue(
+    mut interaction_events: EventReader<InteractionEvent>,
+    villager_query: Query<(Entity, &Villager)>,
+    mut player_query: Query<(&mut Inventory, &mut Wallet), With<Player>>,
+    library: Res<DialogueLibrary>,
+    mut flags: ResMut<DialogueFlags>,
+    mut relationships: ResMut<Relationships>,
+    friendship_config: Res<FriendshipConfig>,
+    calendar: Res<Calendar>,
+    clock: Res<Clock>,
+    mut active: ResMut<ActiveDialogue>,
+    mut state: ResMut<State<GameState>>
+) {
+    for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Talk) {
+        let (entity, villager) = match villager_query.get(event.entity) {
+            Ok(villager) => villager,
+            Err(_) => continue
+        };
+        let dialogue = match library.dialogues.get(&villager.dialogue) {
+            Some(dialogue) => dialogue,
+            None => {
+                log::warn!("{} has no dialogue called {}", villager.name, villager.dialogue);
+                continue;
+            }
+        };
+        let (mut inventory, mut wallet) = player_query.single_mut();
+        let mut context = DialogueContext {
+            inventory: &mut inventory,
+            wallet: &mut wallet,
+            flags: &mut flags,
+            relationship: relationships.0.entry(villager.name.clone()).or_default(),
+            friendship_config: &friendship_config,
+            calendar: &calendar,
+            clock: &clock,
+            offer_gift: false
+        };
+
+        *active = ActiveDialogue {
+            villager: Some(entity),
+            speaker: villager.name.clone(),
+            dialogue: villager.dialogue.clone(),
+            ..Default::default()
+        };
+        let start = dialogue.start_node(&context).map(|node| node.to_string());
+        let started = enter_node(&mut active, start, dialogue, &mut context);
+        active.offer_gift = context.offer_gift;
+        if started {
+            state.overwrite_set(GameState::LoadingDialogue).unwrap();
+            return;
+        }
+    }
+}
+
+/// types out the current line and moves on with the interact action, the movement actions pick between choices
+pub fn advance_dialogue(
+    mut player_query: Query<(&mut Inventory, &mut Wallet), With<Player>>,
+    library: Res<DialogueLibrary>,
+    mut flags: ResMut<DialogueFlags>,
+    mut relationships: ResMut<Relationships>,
+    friendship_config: Res<FriendshipConfig>,
+    calendar: Res<Calendar>,
+    clock: Res<Clock>,
+    mut active: ResMut<ActiveDialogue>,
+    mut action_input: ResMut<ActionInput>,
+    mut inventory_mode: ResMut<InventoryMode>,
+    mut state: ResMut<State<GameState>>,
+    time: Res<Time>
+) {
+    if !active.line_finished(&library) {
+        active.revealed += TYPEWRITER_CHARS_PER_SECOND * time.delta_seconds();
+    }
+
+    let cancelled = action_input.just_pressed(Action::Pause);
+    let mut finished = cancelled;
+
+    if active.showing_choices(&library) {
+        let choice_count = active.choices.len();
+        if action_input.just_pressed(Action::MoveUp) {
+            active.selected = (active.selected + choice_count - 1) % choice_count;
+        }
+        if action_input.just_pressed(Action::MoveDown) {
+            active.selected = (active.selected + 1) % choice_count;
+        }
+    }
+
+    if action_input.just_pressed(Action::Interact) && !finished {
+        if !active.line_finished(&library) {
+            // skips the typewriter to the end of the line
+            active.revealed = f32::MAX;
+        }
+        else {
+            let dialogue = library.dialogues.get(&active.dialogue);
+            let node = active.current_node(&library);
+            match (dialogue, node) {
+                (Some(_), Some(node)) if active.line + 1 < node.lines.len() => {
+                    active.line += 1;
+                    active.revealed = 0.;
+                },
+                (Some(dialogue), Some(node)) => {
+                    let (mut inventory, mut wallet) = player_query.single_mut();
+                    let mut context = DialogueContext {
+                        inventory: &mut inventory,
+                        wallet: &mut wallet,
+                        flags: &mut flags,
+                        relationship: relationships.0.entry(active.speaker.clone()).or_default(),
+                        friendship_config: &friendship_config,
+                        calendar: &calendar,
+                        clock: &clock,
+                        offer_gift: false
+                    };
+                    let next = match active.choices.get(active.selected).and_then(|index| node.choices.get(*index)) {
+                        Some(choice) => {
+                            for effect in choice.effects.iter() {
+                                effect.apply(&mut context);
+                            }
+                            choice.next.clone()
+                        },
+                        None => node.next.clone()
+                    };
+                    finished = !enter_node(&mut active, next, dialogue, &mut context);
+                    active.offer_gift |= context.offer_gift;
+                },
+                _ => finished = true
+            }
+        }
+    }
+
+    if finished {
+        // so closing the dialogue doesn't start it again or open the pause menu straight away
+        action_input.consume(Action::Interact);
+        action_input.consume(Action::Pause);
+        match active.villager {
+            Some(villager) if active.offer_gift && !cancelled => {
+                *inventory_mode = InventoryMode::Gift(villager);
+                state.overwrite_set(GameState::Inventory).unwrap();
+            },
+            _ => state.overwrite_set(GameState::Game).unwrap()
+        }
+        *active = ActiveDialogue::default();
+    }
+}
@@ -0,0 +1,58 @@
+use bevy_ecs_ldtk::prelude::*;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::ldtk::*;
+
+/// A transition point between two levels
+///
+/// Walking into a door fades the screen out, loads `target_level` and places the player on the door in that level with the id `target_door`
+#[derive(Debug, Component, Clone, Default)]
+pub struct Door {
+    pub id: String,
+    pub target_level: String,
+    pub target_door: String
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct LdtkDoor {
+    pub door: Door,
+    #[bundle]
+    pub sensor_bundle: SensorBundle,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkDoor {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            _: &LayerInstance,
+            _: Option<&Handle<Image>>,
+            _: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            _: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        let mut door = Door::default();
+
+        for field in entity_instance.field_instances.iter() {
+            if let FieldValue::String(Some(value)) = &field.value {
+                match field.identifier.as_str() {
+                    "id" => door.id = value.clone(),
+                    "target_level" => door.target_level = value.clone(),
+                    "target_door" => door.target_door = value.clone(),
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            door,
+            sensor_bundle: SensorBundle {
+                collider: Collider::cuboid(entity_instance.width as f32 / 2., entity_instance.height as f32 / 2.),
+                sensor: Sensor,
+                ..Default::default()
+            },
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
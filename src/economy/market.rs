@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Serialize, Deserialize};
+use crate::crop::CropType;
+
+/// every crop the market trades
+pub const MARKET_CROPS: [CropType; 4] = [
+    CropType::Potato,
+    CropType::Carrot,
+    CropType::Corn,
+    CropType::Cabbage,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketConfig {
+    /// how much every unit shipped lowers the multiplier
+    pub saturation_per_unit: f32,
+    /// the multiplier never drops below this no matter how much is shipped
+    pub min_multiplier: f32,
+    /// how many days a shipment keeps affecting the price, its effect shrinks every day until then
+    pub window_days: u32,
+    /// the chance every morning that a crop is in high demand
+    pub spike_chance: f64,
+    pub spike_multiplier: f32,
+    /// how many days of prices are kept for the price board
+    pub history_days: usize
+}
+
+impl Default for MarketConfig {
+    fn default() -> Self {
+        Self {
+            saturation_per_unit: 0.01,
+            min_multiplier: 0.4,
+            window_days: 7,
+            spike_chance: 0.1,
+            spike_multiplier: 1.5,
+            history_days: 14
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shipment {
+    pub day: u32,
+    pub count: u32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub day: u32,
+    /// the price of a normal quality crop that day
+    pub price: u32
+}
+
+/// The crop market, kept free of any bevy types so it can be driven without an `App`
+///
+/// Every crop sells for its base price times a multiplier. Shipping a crop saturates its market which lowers the multiplier, the effect of a
+/// shipment fades out over `MarketConfig::window_days` so prices recover on their own. On top of that every morning each crop has a chance
+/// of a demand spike that raises its price for the day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Market {
+    /// makes the daily demand spikes the same every time a save is played
+    pub seed: u64,
+    pub shipments: HashMap<CropType, Vec<Shipment>>,
+    /// the crops in high demand today
+    pub spikes: Vec<CropType>,
+    pub history: HashMap<CropType, Vec<PricePoint>>
+}
+
+impl Default for Market {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Market {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            shipments: HashMap::new(),
+            spikes: Vec::new(),
+            history: HashMap::new()
+        }
+    }
+
+    /// how saturated a crops market is on `day`, a shipment counts fully on the day it is shipped and fades out over the window
+    pub fn supply(&self, crop_type: &CropType, day: u32, config: &MarketConfig) -> f32 {
+        let window = config.window_days.max(1) as f32;
+        self.shipments.get(crop_type)
+            .map(|shipments| shipments.iter()
+                .filter(|shipment| shipment.day <= day)
+                .map(|shipment| shipment.count as f32 * (1. - (day - shipment.day) as f32 / window).max(0.))
+                .sum()
+            )
+            .unwrap_or(0.)
+    }
+
+    pub fn multiplier(&self, crop_type: &CropType, day: u32, config: &MarketConfig) -> f32 {
+        let saturated = (1. - self.supply(crop_type, day, config) * config.saturation_per_unit).max(config.min_multiplier);
+        if self.spikes.contains(crop_type) {
+            return saturated * config.spike_multiplier;
+        }
+        saturated
+    }
+
+    /// the price of one crop before its quality is taken into account
+    pub fn price(&self, crop_type: &CropType, day: u32, config: &MarketConfig) -> u32 {
+        (crop_type.sell_price() as f32 * self.multiplier(crop_type, day, config)).round() as u32
+    }
+
+    pub fn record_shipment(&mut self, crop_type: &CropType, count: u32, day: u32) {
+        let shipments = self.shipments.entry(crop_type.clone()).or_default();
+        match shipments.iter_mut().find(|shipment| shipment.day == day) {
+            Some(shipment) => shipment.count += count,
+            None => shipments.push(Shipment { day, count })
+        }
+    }
+
+    /// rolls todays demand spikes, forgets shipments that no longer affect the price and adds todays prices to the history
+    pub fn start_day(&mut self, day: u32, config: &MarketConfig) {
+        let mut rng = StdRng::seed_from_u64(self.seed ^ day as u64);
+        self.spikes = MARKET_CROPS.iter()
+            .filter(|_| rng.gen_bool(config.spike_chance))
+            .cloned()
+            .collect();
+
+        for shipments in self.shipments.values_mut() {
+            shipments.retain(|shipment| shipment.day + config.window_days > day);
+        }
+
+        for crop_type in MARKET_CROPS.iter() {
+            let price = self.price(crop_type, day, config);
+            let history = self.history.entry(crop_type.clone()).or_default();
+            history.push(PricePoint { day, price });
+            if history.len() > config.history_days {
+                let excess = history.len() - config.history_days;
+                history.drain(..excess);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "expected {} but got {}", expected, actual);
+    }
+
+    #[test]
+    fn supply_fades_over_window() {
+        let config = MarketConfig::default();
+        let mut market = Market::new(0);
+        market.record_shipment(&CropType::Potato, 40, 3);
+        market.record_shipment(&CropType::Potato, 30, 3);
+
+        assert_close(market.supply(&CropType::Potato, 2, &config), 0.);
+        assert_close(market.supply(&CropType::Potato, 3, &config), 70.);
+        assert_close(market.supply(&CropType::Potato, 4, &config), 60.);
+        assert_close(market.supply(&CropType::Potato, 9, &config), 10.);
+        assert_close(market.supply(&CropType::Potato, 3 + config.window_days, &config), 0.);
+        assert_close(market.supply(&CropType::Carrot, 3, &config), 0.);
+
+        market.start_day(3 + config.window_days, &config);
+        assert!(market.shipments[&CropType::Potato].is_empty());
+    }
+
+    #[test]
+    fn multiplier_never_drops_below_min() {
+        let config = MarketConfig { spike_chance: 0., ..Default::default() };
+        let mut market = Market::new(0);
+        market.record_shipment(&CropType::Corn, 10_000, 0);
+
+        assert_close(market.multiplier(&CropType::Corn, 0, &config), config.min_multiplier);
+        assert_close(market.multiplier(&CropType::Cabbage, 0, &config), 1.);
+
+        market.spikes = vec![CropType::Corn];
+        assert_close(market.multiplier(&CropType::Corn, 0, &config), config.min_multiplier * config.spike_multiplier);
+    }
+
+    #[test]
+    fn spikes_are_the_same_for_a_seed() {
+        let config = MarketConfig::default();
+        let mut first = Market::new(1234);
+        let mut second = Market::new(1234);
+        for day in 0..60 {
+            first.start_day(day, &config);
+            second.start_day(day, &config);
+            assert_eq!(first.spikes, second.spikes, "day {}", day);
+        }
+
+        let mut market = Market::new(1234);
+        market.start_day(0, &MarketConfig { spike_chance: 1., ..Default::default() });
+        assert_eq!(market.spikes, MARKET_CROPS.to_vec());
+        market.start_day(1, &MarketConfig { spike_chance: 0., ..Default::default() });
+        assert!(market.spikes.is_empty());
+    }
+
+    #[test]
+    fn history_is_trimmed() {
+        let config = MarketConfig::default();
+        let mut market = Market::new(0);
+        for day in 0..30 {
+            market.start_day(day, &config);
+        }
+
+        for crop_type in MARKET_CROPS.iter() {
+            let history = &market.history[crop_type];
+            assert_eq!(history.len(), config.history_days);
+            assert_eq!(history.first().unwrap().day, 30 - config.history_days as u32);
+            assert_eq!(history.last().unwrap().day, 29);
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::sprite_sheet_bundle_from_entity_info};
+use bevy_rapier2d::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{GameState, ldtk::*, item::{Item, ItemStack}, mechanics::{interaction::*, perspective::SecondaryPerspectiveBody}};
+
+use self::{systems::*, market::*};
+
+pub mod systems;
+pub mod market;
+
+pub const SHIPPING_BIN_PROMPT: &str = "Ship";
+
+/// **INFO** plugin for the players coins and the shipping bin
+///
+/// The player starts with the `coins` field of the LDtk `Player` entity. Interacting with a `ShippingBin` opens the inventory in shipping
+/// mode where clicking a slot ships its stack. Everything shipped is paid for at the start of the next day, crops at the price the
+/// `CropMarket` gives them and everything else with `Item::sell_price`
+///
+/// **NOTE** there is a single `ShippingBinContents` no matter how many bins there are so shipping from any of them works the same
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkShippingBin>("ShippingBin")
+            .insert_resource(ShippingBinContents::default())
+            .insert_resource(CropMarket::default())
+            .add_event::<ShipEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(open_shipping_bin)
+                .with_system(pay_for_shipped_items.before("update_market"))
+                .with_system(update_market.label("update_market"))
+            )
+            .add_system_set(SystemSet::on_update(GameState::Inventory)
+                .with_system(ship_items)
+            )
+        ;
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wallet {
+    pub coins: u32
+}
+
+impl Wallet {
+    /// reads the `coins` field of the LDtk `Player` entity
+    pub fn from_entity_instance(entity_instance: &EntityInstance) -> Self {
+        let coins = entity_instance.field_instances.iter()
+            .find(|field| field.identifier == "coins")
+            .and_then(|field| match field.value {
+                FieldValue::Int(Some(coins)) => Some(coins.max(0) as u32),
+                _ => None
+            })
+            .unwrap_or(0);
+        Self { coins }
+    }
+
+    /// only spends the coins if there are enough of them
+    pub fn spend(&mut self, amount: u32) -> bool {
+        if self.coins < amount {
+            return false;
+        }
+        self.coins -= amount;
+        true
+    }
+
+    pub fn earn(&mut self, amount: u32) {
+        self.coins = self.coins.saturating_add(amount);
+    }
+}
+
+/// everything shipped today, paid for when the next day starts
+#[derive(Debug, Resource, Default, Clone)]
+pub struct ShippingBinContents(pub Vec<ItemStack>);
+
+/// the `Market` crops are sold to, by the shipping bin and the shop
+#[derive(Debug, Resource, Default)]
+pub struct CropMarket {
+    pub market: Market,
+    pub config: MarketConfig
+}
+
+impl CropMarket {
+    /// what one of the item sells for on `day`, `None` for items that can't be sold
+    pub fn sell_price(&self, item: &Item, day: u32) -> Option<u32> {
+        match item {
+            Item::Crop(crop_type, quality) if item.sell_price().is_some() => {
+                Some((self.market.price(crop_type, day, &self.config) as f32 * quality.price_multiplier()).round() as u32)
+            },
+            _ => item.sell_price()
+        }
+    }
+
+    /// sells `count` of the item on `day` and returns what they made, selling crops lowers their price
+    pub fn sell(&mut self, item: &Item, count: u32, day: u32) -> Option<u32> {
+        let total = self.sell_price(item, day)? * count;
+        if let Item::Crop(crop_type, _) = item {
+            self.market.record_shipment(crop_type, count, day);
+        }
+        Some(total)
+    }
+}
+
+/// sent by the inventory ui to ship the stack in an inventory slot
+#[derive(Debug, Clone, Copy)]
+pub struct ShipEvent(pub usize);
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct ShippingBin;
+
+#[derive(Clone, Bundle)]
+pub struct LdtkShippingBin {
+    #[bundle]
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    pub shipping_bin: ShippingBin,
+    pub interactable: Interactable,
+    pub secondary_perspective_body: SecondaryPerspectiveBody,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkShippingBin {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            _: &LayerInstance,
+            tileset: Option<&Handle<Image>>,
+            tileset_definition: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            texture_atlases: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        // entities without a tile in LDtk are left invisible
+        let sprite_sheet_bundle = if tileset.is_some() && tileset_definition.is_some() {
+            sprite_sheet_bundle_from_entity_info(entity_instance, tileset, tileset_definition, texture_atlases, false)
+        }
+        else {
+            SpriteSheetBundle::default()
+        };
+
+        Self {
+            sprite_sheet_bundle,
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(entity_instance.width as f32 / 2., entity_instance.height as f32 / 2.),
+                rigid_body: RigidBody::Fixed,
+                ..Default::default()
+            },
+            shipping_bin: ShippingBin,
+            interactable: Interactable::new(SHIPPING_BIN_PROMPT, 1, InteractionHandler::ShippingBin),
+            secondary_perspective_body: SecondaryPerspectiveBody,
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
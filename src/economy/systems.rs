@@ -0,0 +1,67 @@
+use bevy::{prelude::*, log};
+use crate::{GameState, player::Player, item::*, calendar::NewDayEvent};
+
+use super::*;
+
+pub fn open_shipping_bin(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut inventory_mode: ResMut<InventoryMode>,
+    mut state: ResMut<State<GameState>>
+) {
+    if interaction_events.iter().any(|event| event.handler == InteractionHandler::ShippingBin) {
+        *inventory_mode = InventoryMode::Ship;
+        state.overwrite_set(GameState::Inventory).unwrap();
+    }
+}
+
+pub fn ship_items(
+    mut ship_events: EventReader<ShipEvent>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut contents: ResMut<ShippingBinContents>
+) {
+    for ShipEvent(slot) in ship_events.iter() {
+        let mut inventory = player_query.single_mut();
+        let count = match inventory.get(*slot) {
+            Some(stack) if stack.item.sell_price().is_some() => stack.count,
+            _ => continue
+        };
+        if let Some(shipped) = inventory.remove_from_slot(*slot, count) {
+            log::info!("shipped {} {}", shipped.count, shipped.item.name());
+            contents.0.push(shipped);
+        }
+    }
+}
+
+/// the shipping bin pays out overnight at the prices of the day the items were shipped on
+pub fn pay_for_shipped_items(
+    mut new_day_events: EventReader<NewDayEvent>,
+    mut player_query: Query<&mut Wallet, With<Player>>,
+    mut contents: ResMut<ShippingBinContents>,
+    mut crop_market: ResMut<CropMarket>
+) {
+    for NewDayEvent(calendar) in new_day_events.iter() {
+        if contents.0.is_empty() {
+            continue;
+        }
+        let shipped_on = calendar.total_days().saturating_sub(1);
+        let earned: u32 = contents.0.iter()
+            .map(|stack| crop_market.sell(&stack.item, stack.count, shipped_on).unwrap_or(0))
+            .sum();
+        player_query.single_mut().earn(earned);
+        log::info!("the shipping bin paid {} coins for {} stacks", earned, contents.0.len());
+        contents.0.clear();
+    }
+}
+
+pub fn update_market(
+    mut new_day_events: EventReader<NewDayEvent>,
+    mut crop_market: ResMut<CropMarket>
+) {
+    for NewDayEvent(calendar) in new_day_events.iter() {
+        let CropMarket { market, config } = &mut *crop_market;
+        market.start_day(calendar.total_days(), config);
+        if !market.spikes.is_empty() {
+            log::info!("{:?} in high demand today", market.spikes);
+        }
+    }
+}
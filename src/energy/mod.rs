@@ -0,0 +1,208 @@
+use std::{collections::HashMap, fs::read_to_string};
+
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::{prelude::*, utils::sprite_sheet_bundle_from_entity_info};
+use bevy_rapier2d::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{GameState, ldtk::*, crop::CropType, tool::ToolKind, mechanics::{interaction::*, perspective::SecondaryPerspectiveBody}};
+
+use self::systems::*;
+
+pub mod systems;
+
+pub const ENERGY_SETTINGS_PATH: &str = "assets/data/energy.rson";
+pub const DEFAULT_MAX_ENERGY: f32 = 100.;
+pub const SLEEP_PROMPT: &str = "Sleep";
+/// how much of the energy bar comes back after passing out instead of sleeping in a bed
+pub const PASS_OUT_ENERGY_FRACTION: f32 = 0.5;
+
+/// **INFO** plugin for the players energy
+///
+/// Using tools, harvesting and running drain energy by the amounts in `assets/data/energy.rson`. Eating crops from the inventory and
+/// sleeping in a bed fill it back up. Staying up until `DAY_END_MINUTES` makes the player pass out, which also starts a new day but only
+/// gives back part of the bar. With no energy left the player walks slower, can't run and can't use tools
+///
+/// **NOTE** a `SleepEvent` is sent every time the player sleeps
+pub struct EnergyPlugin;
+
+impl Plugin for EnergyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkBed>("Bed")
+            .insert_resource(EnergySettings::read())
+            .add_event::<EatEvent>()
+            .add_event::<SleepEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(drain_tool_energy)
+                .with_system(sleep_in_bed)
+                .with_system(pass_out.before("advance_day"))
+            )
+            .add_system_set(SystemSet::on_update(GameState::Inventory)
+                .with_system(eat_crops)
+            )
+        ;
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Energy {
+    pub current: f32,
+    pub max: f32
+}
+
+impl Default for Energy {
+    fn default() -> Self {
+        Self {
+            current: DEFAULT_MAX_ENERGY,
+            max: DEFAULT_MAX_ENERGY
+        }
+    }
+}
+
+impl Energy {
+    pub fn spend(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.);
+    }
+
+    pub fn restore(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.current <= 0.
+    }
+
+    /// how full the energy bar is from 0 to 1
+    pub fn fraction(&self) -> f32 {
+        if self.max <= 0. {
+            return 0.;
+        }
+        self.current / self.max
+    }
+}
+
+/// everything that costs energy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyAction {
+    Tool(ToolKind),
+    Harvest,
+    /// drained per second while running
+    Run
+}
+
+/// how much energy every action costs and every crop gives back, read from `assets/data/energy.rson`
+#[derive(Debug, Resource, Serialize, Deserialize)]
+pub struct EnergySettings {
+    pub tool_drains: HashMap<ToolKind, f32>,
+    pub harvest_drain: f32,
+    pub run_drain_per_second: f32,
+    pub crop_restores: HashMap<CropType, f32>,
+    /// how much of their walking speed the player keeps with no energy left
+    pub exhausted_speed_multiplier: f32
+}
+
+impl Default for EnergySettings {
+    fn default() -> Self {
+        Self {
+            tool_drains: HashMap::from([
+                (ToolKind::Hoe, 4.),
+                (ToolKind::WateringCan, 2.),
+                (ToolKind::Axe, 5.),
+                (ToolKind::Pickaxe, 5.),
+                (ToolKind::Scythe, 2.),
+            ]),
+            harvest_drain: 1.,
+            run_drain_per_second: 0.5,
+            crop_restores: HashMap::from([
+                (CropType::Potato, 15.),
+                (CropType::Carrot, 10.),
+                (CropType::Corn, 20.),
+                (CropType::Cabbage, 25.),
+            ]),
+            exhausted_speed_multiplier: 0.5
+        }
+    }
+}
+
+impl EnergySettings {
+    /// falls back to the default settings if the file is missing or invalid
+    pub fn read() -> Self {
+        match read_to_string(ENERGY_SETTINGS_PATH) {
+            Ok(settings) => ron::from_str::<EnergySettings>(&settings.as_str()).unwrap_or_else(|err| {
+                log::warn!("invalid energy settings using defaults: {}", err);
+                EnergySettings::default()
+            }),
+            Err(_) => EnergySettings::default()
+        }
+    }
+
+    pub fn drain(&self, action: EnergyAction) -> f32 {
+        match action {
+            EnergyAction::Tool(kind) => self.tool_drains.get(&kind).copied().unwrap_or(0.),
+            EnergyAction::Harvest => self.harvest_drain,
+            EnergyAction::Run => self.run_drain_per_second
+        }
+    }
+
+    /// how much energy eating a crop gives back, crops that aren't listed can't be eaten
+    pub fn restore(&self, crop_type: &CropType) -> Option<f32> {
+        self.crop_restores.get(crop_type).copied()
+    }
+}
+
+/// sent by the inventory ui to eat whatever is in an inventory slot
+#[derive(Debug, Clone, Copy)]
+pub struct EatEvent(pub usize);
+
+/// sent after the player slept in a bed or passed out
+#[derive(Debug, Clone, Copy)]
+pub struct SleepEvent;
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct Bed;
+
+#[derive(Clone, Bundle)]
+pub struct LdtkBed {
+    #[bundle]
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    pub bed: Bed,
+    pub interactable: Interactable,
+    pub secondary_perspective_body: SecondaryPerspectiveBody,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkBed {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            _: &LayerInstance,
+            tileset: Option<&Handle<Image>>,
+            tileset_definition: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            texture_atlases: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        // entities without a tile in LDtk are left invisible
+        let sprite_sheet_bundle = if tileset.is_some() && tileset_definition.is_some() {
+            sprite_sheet_bundle_from_entity_info(entity_instance, tileset, tileset_definition, texture_atlases, false)
+        }
+        else {
+            SpriteSheetBundle::default()
+        };
+
+        Self {
+            sprite_sheet_bundle,
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(entity_instance.width as f32 / 2., entity_instance.height as f32 / 2.),
+                rigid_body: RigidBody::Fixed,
+                ..Default::default()
+            },
+            bed: Bed,
+            interactable: Interactable::new(SLEEP_PROMPT, 1, InteractionHandler::Sleep),
+            secondary_perspective_body: SecondaryPerspectiveBody,
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
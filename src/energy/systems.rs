@@ -0,0 +1,75 @@
+use bevy::{prelude::*, log};
+use crate::{player::Player, item::*, tool::ToolUsedEvent, mechanics::interaction::*, calendar::{Clock, DAY_END_MINUTES}, ui::notification::NotificationEvent};
+
+use super::*;
+
+/// tools cost energy once their animation finishes and the tool actually did something
+pub fn drain_tool_energy(
+    mut tool_used_events: EventReader<ToolUsedEvent>,
+    mut player_query: Query<&mut Energy, With<Player>>,
+    energy_settings: Res<EnergySettings>
+) {
+    for event in tool_used_events.iter() {
+        let mut energy = player_query.single_mut();
+        energy.spend(energy_settings.drain(EnergyAction::Tool(event.tool.kind)));
+        if energy.is_exhausted() {
+            log::info!("the player is out of energy");
+        }
+    }
+}
+
+pub fn eat_crops(
+    mut eat_events: EventReader<EatEvent>,
+    mut player_query: Query<(&mut Energy, &mut Inventory), With<Player>>,
+    energy_settings: Res<EnergySettings>
+) {
+    for EatEvent(slot) in eat_events.iter() {
+        let (mut energy, mut inventory) = player_query.single_mut();
+        let restore = match inventory.get(*slot) {
+            Some(ItemStack { item: Item::Crop(crop_type, _), .. }) => energy_settings.restore(crop_type),
+            _ => None
+        };
+        let restore = match restore {
+            Some(restore) => restore,
+            None => continue
+        };
+        // eating with a full bar would just waste the crop
+        if energy.current >= energy.max {
+            continue;
+        }
+        if let Some(eaten) = inventory.remove_from_slot(*slot, 1) {
+            energy.restore(restore);
+            log::info!("ate {} for {} energy", eaten.item.name(), restore);
+        }
+    }
+}
+
+pub fn sleep_in_bed(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut player_query: Query<&mut Energy, With<Player>>,
+    mut sleep_events: EventWriter<SleepEvent>
+) {
+    for _ in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Sleep) {
+        let mut energy = player_query.single_mut();
+        energy.current = energy.max;
+        log::info!("the player slept");
+        sleep_events.send(SleepEvent);
+    }
+}
+
+/// the player passes out when the clock runs out, the day still ends but only part of the energy comes back
+pub fn pass_out(
+    clock: Res<Clock>,
+    mut player_query: Query<&mut Energy, With<Player>>,
+    mut sleep_events: EventWriter<SleepEvent>,
+    mut notification_events: EventWriter<NotificationEvent>
+) {
+    if clock.minutes < DAY_END_MINUTES {
+        return;
+    }
+    let mut energy = player_query.single_mut();
+    energy.current = energy.current.max(energy.max * PASS_OUT_ENERGY_FRACTION);
+    log::info!("the player passed out");
+    notification_events.send(NotificationEvent("You stayed up too late and passed out".to_string()));
+    sleep_events.send(SleepEvent);
+}
@@ -0,0 +1,363 @@
+use std::collections::HashSet;
+
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::{prelude::*, rapier::prelude as rapier};
+use crate::{ldtk::*, crop::grid_coords, level::CurrentLevel};
+
+/// half the height of the collider of a single row of fence cells, fences are thinner than a full cell
+pub const FENCE_HALF_THICKNESS: f32 = 4.;
+/// how many physics steps the fence benchmark averages the step time over for each kind of collider
+pub const STEP_TIME_SAMPLES: usize = 300;
+/// the fence benchmark drops a body next to every this many fence cells
+pub const BENCHMARK_CELLS_PER_BODY: usize = 4;
+/// how fast the fence benchmark bodies walk into the fences in pixels per second, about the players running speed
+pub const BENCHMARK_BODY_SPEED: f32 = 120.;
+
+/// A single fence cell from the `Collisions` int grid layer
+///
+/// **NOTE** fence cells don't have colliders of their own. After a level spawns every contiguous group of cells is merged into as few
+/// `MergedFenceCollider` rectangles as possible, which keeps the rapier body count down and stops the player catching on the seams between cells
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Fence {
+    pub grid_size: i32
+}
+
+#[derive(Clone, Debug, Default, Bundle)]
+pub struct FenceBundle {
+    fence: Fence,
+    pub ldtk: Ldtk
+}
+
+impl LdtkIntCell for FenceBundle {
+    fn bundle_int_cell(_: IntGridCell, layer_instance: &LayerInstance) -> Self {
+        Self {
+            fence: Fence {
+                grid_size: layer_instance.grid_size
+            },
+            ldtk: Ldtk
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct MergedFenceCollider;
+
+/// How the fence colliders are built
+///
+/// **NOTE** the game always uses `Merged`, `PerCell` is the old collider for every cell which is only kept for the fence benchmark to compare against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FenceColliderMode {
+    #[default]
+    Merged,
+    PerCell
+}
+
+/// body counts from the last rebuild, before and after merging
+#[derive(Debug, Default, Resource)]
+pub struct FenceColliderStats {
+    pub cells: usize,
+    pub colliders: usize
+}
+
+/// A rectangle of grid cells. `min` is the bottom left cell and `size` is in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRect {
+    pub min: IVec2,
+    pub size: IVec2
+}
+
+/// greedily merges `cells` into rectangles, first along rows and then stacking rows of the same width on top of each other
+pub fn merge_cells(cells: &HashSet<IVec2>) -> Vec<CellRect> {
+    let mut sorted: Vec<IVec2> = cells.iter().copied().collect();
+    sorted.sort_by_key(|cell| (cell.y, cell.x));
+
+    let mut visited: HashSet<IVec2> = HashSet::new();
+    let mut rects = Vec::new();
+
+    for cell in sorted {
+        if visited.contains(&cell) {
+            continue;
+        }
+
+        let mut width = 1;
+        while cells.contains(&IVec2::new(cell.x + width, cell.y)) && !visited.contains(&IVec2::new(cell.x + width, cell.y)) {
+            width += 1;
+        }
+
+        let mut height = 1;
+        'rows: loop {
+            for x in cell.x..cell.x + width {
+                let next = IVec2::new(x, cell.y + height);
+                if !cells.contains(&next) || visited.contains(&next) {
+                    break 'rows;
+                }
+            }
+            height += 1;
+        }
+
+        for y in cell.y..cell.y + height {
+            for x in cell.x..cell.x + width {
+                visited.insert(IVec2::new(x, y));
+            }
+        }
+        rects.push(CellRect {
+            min: cell,
+            size: IVec2::new(width, height)
+        });
+    }
+    rects
+}
+
+/// the rectangles the fence colliders are built from
+pub fn fence_rects(cells: &HashSet<IVec2>, mode: FenceColliderMode) -> Vec<CellRect> {
+    match mode {
+        FenceColliderMode::Merged => merge_cells(cells),
+        FenceColliderMode::PerCell => cells.iter().map(|cell| CellRect { min: *cell, size: IVec2::ONE }).collect()
+    }
+}
+
+/// the center and half extents of the collider for `rect`, a single row keeps the thin fence shape and anything taller is a solid block
+///
+/// single cells get the same 8 by 4 cuboid the fence cells used to spawn with
+pub fn rect_collider(rect: &CellRect, grid_size: i32) -> (Vec2, Vec2) {
+    let cell_size = grid_size as f32;
+    let center = (rect.min.as_vec2() + rect.size.as_vec2() / 2.) * cell_size;
+    let half_height = if rect.size.y == 1 {
+        FENCE_HALF_THICKNESS
+    }
+    else {
+        rect.size.y as f32 * cell_size / 2.
+    };
+    (center, Vec2::new(rect.size.x as f32 * cell_size / 2., half_height))
+}
+
+/// the fence cells of the spawned level and their grid size and z
+fn fence_cells(fence_query: &Query<(&Fence, &GlobalTransform)>) -> (HashSet<IVec2>, i32, f32) {
+    let mut cells: HashSet<IVec2> = HashSet::new();
+    let mut grid_size = 0;
+    let mut z = 0.;
+
+    for (fence, transform) in fence_query.iter() {
+        cells.insert(grid_coords(transform.translation(), fence.grid_size));
+        grid_size = fence.grid_size;
+        z = transform.translation().z;
+    }
+    (cells, grid_size, z)
+}
+
+/// rebuilds the merged fence colliders every time a level finishes spawning, this includes level transitions and hot reloads
+pub fn rebuild_fence_colliders(
+    mut commands: Commands,
+    mut level_events: EventReader<LevelEvent>,
+    fence_query: Query<(&Fence, &GlobalTransform)>,
+    merged_query: Query<Entity, With<MergedFenceCollider>>,
+    mut stats: ResMut<FenceColliderStats>
+) {
+    // wait for the global transforms of the new cells
+    if !level_events.iter().any(|event| matches!(event, LevelEvent::Transformed(_))) {
+        return;
+    }
+
+    for entity in merged_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (cells, grid_size, z) = fence_cells(&fence_query);
+    let rects = merge_cells(&cells);
+
+    for rect in rects.iter() {
+        let (center, half_extents) = rect_collider(rect, grid_size);
+        commands.spawn(ColliderBundle {
+            collider: Collider::cuboid(half_extents.x, half_extents.y),
+            rigid_body: RigidBody::Fixed,
+            ..Default::default()
+        })
+            .insert(TransformBundle::from(Transform::from_xyz(center.x, center.y, z)))
+            .insert(MergedFenceCollider)
+        ;
+    }
+
+    stats.cells = cells.len();
+    stats.colliders = rects.len();
+    log::info!("merged {} fence cells into {} colliders", stats.cells, stats.colliders);
+}
+
+/// Steps a physics world of its own with the fences of `cells` built as `mode` and returns the average step time in milliseconds
+///
+/// a body is dropped above every `BENCHMARK_CELLS_PER_BODY`th fence cell walking down into it so the fences see contacts like they do
+/// in play. The bodies only depend on the cells so both modes step the same scene
+///
+/// **NOTE** rapier only times its steps with the `profiler` feature which the `fence_benchmark` feature turns on
+pub fn time_fence_physics(cells: &HashSet<IVec2>, grid_size: i32, mode: FenceColliderMode) -> f64 {
+    let mut bodies = rapier::RigidBodySet::new();
+    let mut colliders = rapier::ColliderSet::new();
+
+    for rect in fence_rects(cells, mode).iter() {
+        let (center, half_extents) = rect_collider(rect, grid_size);
+        colliders.insert(rapier::ColliderBuilder::cuboid(half_extents.x, half_extents.y)
+            .translation(rapier::Vector::new(center.x, center.y))
+            .build()
+        );
+    }
+
+    let mut sorted: Vec<IVec2> = cells.iter().copied().collect();
+    sorted.sort_by_key(|cell| (cell.y, cell.x));
+    let cell_size = grid_size as f32;
+    for cell in sorted.iter().step_by(BENCHMARK_CELLS_PER_BODY) {
+        let position = (cell.as_vec2() + Vec2::new(0.5, 1.5)) * cell_size;
+        let body = bodies.insert(rapier::RigidBodyBuilder::dynamic()
+            .translation(rapier::Vector::new(position.x, position.y))
+            .linvel(rapier::Vector::new(0., -BENCHMARK_BODY_SPEED))
+            .lock_rotations()
+            .build()
+        );
+        colliders.insert_with_parent(rapier::ColliderBuilder::cuboid(4., 4.).build(), body, &mut bodies);
+    }
+
+    let mut pipeline = rapier::PhysicsPipeline::new();
+    pipeline.counters.enable();
+    let mut islands = rapier::IslandManager::new();
+    let mut broad_phase = rapier::BroadPhase::new();
+    let mut narrow_phase = rapier::NarrowPhase::new();
+    let mut impulse_joints = rapier::ImpulseJointSet::new();
+    let mut multibody_joints = rapier::MultibodyJointSet::new();
+    let mut ccd_solver = rapier::CCDSolver::new();
+    let integration_parameters = rapier::IntegrationParameters::default();
+    let gravity = rapier::Vector::zeros();
+
+    let mut total = 0.;
+    for _ in 0..STEP_TIME_SAMPLES {
+        pipeline.step(
+            &gravity,
+            &integration_parameters,
+            &mut islands,
+            &mut broad_phase,
+            &mut narrow_phase,
+            &mut bodies,
+            &mut colliders,
+            &mut impulse_joints,
+            &mut multibody_joints,
+            &mut ccd_solver,
+            None,
+            &(),
+            &()
+        );
+        total += pipeline.counters.step_time();
+    }
+    total / STEP_TIME_SAMPLES as f64
+}
+
+/// Benchmark comparing the physics step time of the merged fence colliders against a collider for every cell, only added with the
+/// `fence_benchmark` feature
+///
+/// runs once with the fences of the first level that spawns, which is `Main_Farm` in a new game. Both runs step a world of their own so the
+/// colliders the player walks into are never touched
+pub fn benchmark_fence_colliders(
+    mut level_events: EventReader<LevelEvent>,
+    fence_query: Query<(&Fence, &GlobalTransform)>,
+    current_level: Res<CurrentLevel>,
+    mut benchmarked: Local<bool>
+) {
+    if *benchmarked || !level_events.iter().any(|event| matches!(event, LevelEvent::Transformed(_))) {
+        return;
+    }
+    *benchmarked = true;
+
+    let (cells, grid_size, _) = fence_cells(&fence_query);
+    let merged = time_fence_physics(&cells, grid_size, FenceColliderMode::Merged);
+    let per_cell = time_fence_physics(&cells, grid_size, FenceColliderMode::PerCell);
+    log::info!(
+        "physics step in {} with {} fence cells averaged over {} steps: {:.3}ms with {} merged colliders, {:.3}ms with {} per cell colliders",
+        current_level.identifier(),
+        cells.len(),
+        STEP_TIME_SAMPLES,
+        merged,
+        merge_cells(&cells).len(),
+        per_cell,
+        cells.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(cells: &[(i32, i32)]) -> HashSet<IVec2> {
+        cells.iter().map(|(x, y)| IVec2::new(*x, *y)).collect()
+    }
+
+    /// every cell is covered by exactly one rectangle and no rectangle covers anything else
+    fn assert_covers(rects: &[CellRect], cells: &HashSet<IVec2>) {
+        let mut covered = Vec::new();
+        for rect in rects {
+            for y in rect.min.y..rect.min.y + rect.size.y {
+                for x in rect.min.x..rect.min.x + rect.size.x {
+                    covered.push(IVec2::new(x, y));
+                }
+            }
+        }
+        assert_eq!(covered.len(), cells.len());
+        assert_eq!(covered.into_iter().collect::<HashSet<IVec2>>(), *cells);
+    }
+
+    #[test]
+    fn merges_l_shape() {
+        let l_shape = cells(&[(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (0, 1), (0, 2), (0, 3)]);
+        let rects = merge_cells(&l_shape);
+        assert_eq!(rects, vec![
+            CellRect { min: IVec2::new(0, 0), size: IVec2::new(5, 1) },
+            CellRect { min: IVec2::new(0, 1), size: IVec2::new(1, 3) }
+        ]);
+        assert_covers(&rects, &l_shape);
+    }
+
+    #[test]
+    fn gaps_split_rects() {
+        let row = cells(&[(0, 0), (1, 0), (2, 0), (4, 0), (5, 0)]);
+        let rects = merge_cells(&row);
+        assert_eq!(rects, vec![
+            CellRect { min: IVec2::new(0, 0), size: IVec2::new(3, 1) },
+            CellRect { min: IVec2::new(4, 0), size: IVec2::new(2, 1) }
+        ]);
+        assert_covers(&rects, &row);
+
+        let column = cells(&[(3, 0), (3, 1), (3, 3)]);
+        let rects = merge_cells(&column);
+        assert_eq!(rects.len(), 2);
+        assert_covers(&rects, &column);
+    }
+
+    #[test]
+    fn merges_blocks_and_pens() {
+        let block = cells(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(merge_cells(&block), vec![CellRect { min: IVec2::ZERO, size: IVec2::new(2, 2) }]);
+
+        // a hollow 6 by 4 pen is its bottom and top rows and the two sides between them
+        let mut pen = HashSet::new();
+        for x in 0..6 {
+            pen.insert(IVec2::new(x, 0));
+            pen.insert(IVec2::new(x, 3));
+        }
+        for y in 1..3 {
+            pen.insert(IVec2::new(0, y));
+            pen.insert(IVec2::new(5, y));
+        }
+        let rects = merge_cells(&pen);
+        assert_eq!(rects.len(), 4);
+        assert_covers(&rects, &pen);
+
+        assert!(merge_cells(&HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn per_cell_rects_match_the_old_colliders() {
+        let row = cells(&[(0, 0), (1, 0), (2, 0)]);
+        let rects = fence_rects(&row, FenceColliderMode::PerCell);
+        assert_eq!(rects.len(), 3);
+        assert_covers(&rects, &row);
+        for rect in rects.iter() {
+            assert_eq!(rect_collider(rect, 16).1, Vec2::new(8., 4.));
+        }
+    }
+}
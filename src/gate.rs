@@ -1,15 +1,49 @@
 use bevy_ecs_ldtk::prelude::*;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use crate::{ldtk::*, bevy_animations::*};
+use crate::{ldtk::*, bevy_animations::*, mechanics::interaction::*};
+
+pub const GATE_OPEN_PROMPT: &str = "Open";
+pub const GATE_CLOSE_PROMPT: &str = "Close";
 
 #[derive(Debug, Component, Clone, Default)]
 pub struct Gate {
+    /// whether anything that opens gates is standing in the gate
     pub in_collision: bool,
     pub open: bool
 }
 
-#[derive(Clone, Default, Bundle)]
+impl Gate {
+    /// plays the opening or closing animation and swaps the prompt, does nothing if the gate is already that way
+    pub fn set_open(
+        &mut self,
+        open: bool,
+        gate_entity: Entity,
+        interactable: &mut Interactable,
+        animation_event: &mut EventWriter<AnimationEvent>
+    ) {
+        if self.open == open {
+            return;
+        }
+        if open {
+            animation_event.send(AnimationEvent("gate_opening", gate_entity));
+            interactable.prompt = GATE_CLOSE_PROMPT.to_string();
+        }
+        else {
+            animation_event.send(AnimationEvent("gate_closing", gate_entity));
+            interactable.prompt = GATE_OPEN_PROMPT.to_string();
+        }
+        self.open = open;
+    }
+}
+
+/// Anything that walks through gates, the player and villagers
+///
+/// gates close on their own once every `GateOpener` has left them
+#[derive(Debug, Component, Clone, Copy, Default)]
+pub struct GateOpener;
+
+#[derive(Clone, Bundle)]
 pub struct LdtkGate {
     #[bundle]
     pub sprite_sheet_bundle: SpriteSheetBundle,
@@ -18,6 +52,7 @@ pub struct LdtkGate {
     pub sensor_bundle: SensorBundle,
     entity_instance: EntityInstance,
     pub direction: AnimationDirection,
+    pub interactable: Interactable,
     pub ldtk: Ldtk
 }
 
@@ -56,7 +91,29 @@ impl LdtkEntity for LdtkGate {
             },
             entity_instance: entity_instance.clone(),
             direction: AnimationDirection::default() ,
+            interactable: Interactable::new(GATE_OPEN_PROMPT, 1, InteractionHandler::Gate),
             ldtk: Ldtk
         }
     }
-}
\ No newline at end of file
+}
+/// closes an open gate once everything that walked through it has left
+pub fn close_gates_behind(
+    mut gate_query: Query<(
+        Entity,
+        &mut Gate,
+        &mut Interactable
+    )>,
+    opener_query: Query<Entity, With<GateOpener>>,
+    context: Res<RapierContext>,
+    mut animation_event: EventWriter<AnimationEvent>
+) {
+    for (gate_entity, mut gate, mut interactable) in gate_query.iter_mut() {
+        if opener_query.iter().any(|opener| context.intersection_pair(opener, gate_entity) == Some(true)) {
+            gate.in_collision = true;
+        }
+        else if gate.in_collision {
+            gate.in_collision = false;
+            gate.set_open(false, gate_entity, &mut interactable, &mut animation_event);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::prelude::*;
+use crate::{LdtkAssets, GameState, player::Player, crop::*, level::{CurrentLevel, LevelEntity}};
+
+/// **INFO** development only plugin that reloads the LDtk project in place when `Rusty_Farm_World.ldtk` is saved
+///
+/// bevy_ecs_ldtk respawns the levels on its own, this plugin keeps the game state intact around that. The player keeps their position
+/// and every crop is re-bound to the crop field at its grid coordinate in the new layout
+///
+/// **NOTE** only does anything when built with `--features dev` which also turns on asset watching
+pub struct HotReloadPlugin;
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        if !cfg!(feature = "dev") {
+            return;
+        }
+        app
+            .insert_resource(HotReloadSnapshot::default())
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(detect_ldtk_changes.label("detect_ldtk_changes"))
+                .with_system(rebind_after_reload.after("detect_ldtk_changes"))
+            )
+        ;
+    }
+}
+
+/// the state captured right before a reload that needs to be restored once the new level is in place
+#[derive(Debug, Resource, Default)]
+pub struct HotReloadSnapshot {
+    player_translation: Option<Vec3>
+}
+
+pub fn detect_ldtk_changes(
+    mut asset_events: EventReader<AssetEvent<LdtkAsset>>,
+    ldtk_assets: Res<LdtkAssets>,
+    player_query: Query<&Transform, With<Player>>,
+    mut snapshot: ResMut<HotReloadSnapshot>
+) {
+    for event in asset_events.iter() {
+        if let AssetEvent::Modified { handle } = event {
+            if *handle != ldtk_assets.ldtk_world {
+                continue;
+            }
+            log::info!("LDtk project changed, reloading the level in place");
+            if let Ok(player_transform) = player_query.get_single() {
+                snapshot.player_translation = Some(player_transform.translation);
+            }
+        }
+    }
+}
+
+/// waits for the reloaded level to finish transforming and then puts the game state back on top of it
+pub fn rebind_after_reload(
+    mut level_events: EventReader<LevelEvent>,
+    mut snapshot: ResMut<HotReloadSnapshot>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    crop_field_query: Query<(&CropField, &GlobalTransform)>,
+    mut crop_query: Query<(Entity, &mut Transform, &Crop, &LevelEntity), Without<Player>>,
+    current_level: Res<CurrentLevel>
+) {
+    let transformed = level_events.iter().any(|event| matches!(event, LevelEvent::Transformed(_)));
+
+    let player_translation = match snapshot.player_translation {
+        Some(translation) if transformed => translation,
+        _ => return
+    };
+    snapshot.player_translation = None;
+
+    if let Ok(mut player_transform) = player_query.get_single_mut() {
+        player_transform.translation = player_translation;
+    }
+
+    // every crop field in the new layout keyed by its grid coordinate
+    let mut crop_fields: HashMap<IVec2, (Vec3, i32)> = HashMap::new();
+    for (crop_field, transform) in crop_field_query.iter() {
+        crop_fields.insert(grid_coords(transform.translation(), crop_field.grid_size), (transform.translation(), crop_field.grid_size));
+    }
+    let grid_size = match crop_fields.values().next() {
+        Some((_, grid_size)) => *grid_size,
+        None => {
+            log::warn!("reloaded level {} has no crop fields, crops were left where they were", current_level.identifier());
+            return;
+        }
+    };
+
+    let mut rebound = 0;
+    let mut unmatched = 0;
+
+    for (crop_entity, mut crop_transform, crop, level_entity) in crop_query.iter_mut() {
+        if level_entity.0 != current_level.identifier() {
+            continue;
+        }
+        let coords = grid_coords(crop.crop_type.cell_center(crop_transform.translation, grid_size), grid_size);
+        match crop_fields.get(&coords) {
+            Some((cell_center, grid_size)) => {
+                crop_transform.translation = crop.crop_type.translation(*cell_center, *grid_size);
+                rebound += 1;
+            },
+            None => {
+                log::warn!("{:?} crop {:?} at {} is no longer on a crop field", crop.crop_type, crop_entity, coords);
+                unmatched += 1;
+            }
+        }
+    }
+
+    log::info!("hot reload finished, {} crops re-bound and {} crops no longer match a crop field", rebound, unmatched);
+}
@@ -0,0 +1,221 @@
+use std::{collections::{HashMap, HashSet}, fs::{File, read_to_string}, io::Write};
+
+use bevy::{prelude::*, input::InputSystem, log};
+use serde::{Serialize, Deserialize};
+
+pub const INPUT_SETTINGS_PATH: &str = "saves/input_settings.rson";
+
+/// **INFO** action based input layer
+///
+/// Systems never read `KeyCode`s or `GamepadButton`s directly, they ask `ActionInput` about an `Action`. Which keys and buttons trigger
+/// an action comes from `InputSettings` which is loaded from and saved to `saves/input_settings.rson` so players can rebind them
+pub struct ActionInputPlugin;
+
+impl Plugin for ActionInputPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(InputSettings::read())
+            .insert_resource(ActionInput::default())
+            .add_system_to_stage(CoreStage::PreUpdate, update_action_input.after(InputSystem))
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Run,
+    Interact,
+    Pause,
+    Inventory,
+    UseTool,
+    NextTool,
+    /// held while clicking an inventory slot to move the whole stack
+    QuickTransfer,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Run,
+        Action::Interact,
+        Action::Pause,
+        Action::Inventory,
+        Action::UseTool,
+        Action::NextTool,
+        Action::QuickTransfer,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Run => "Run",
+            Action::Interact => "Interact",
+            Action::Pause => "Pause",
+            Action::Inventory => "Inventory",
+            Action::UseTool => "Use Tool",
+            Action::NextTool => "Next Tool",
+            Action::QuickTransfer => "Quick Transfer",
+        }
+    }
+}
+
+/// the keys and gamepad buttons that trigger a single `Action`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Binding {
+    pub keys: Vec<KeyCode>,
+    pub gamepad_buttons: Vec<GamepadButtonType>
+}
+
+impl Binding {
+    fn new(keys: &[KeyCode], gamepad_buttons: &[GamepadButtonType]) -> Self {
+        Self {
+            keys: keys.to_vec(),
+            gamepad_buttons: gamepad_buttons.to_vec()
+        }
+    }
+
+    /// a short description for the controls menu i.e `W / DPadUp`
+    pub fn describe(&self) -> String {
+        let mut names: Vec<String> = self.keys.iter().map(|key| format!("{:?}", key)).collect();
+        names.extend(self.gamepad_buttons.iter().map(|button| format!("{:?}", button)));
+        if names.is_empty() {
+            return "Unbound".to_string();
+        }
+        names.join(" / ")
+    }
+}
+
+/// whether the run action has to be held down or toggles running on and off
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RunMode {
+    #[default]
+    Hold,
+    Toggle
+}
+
+#[derive(Debug, Resource, Serialize, Deserialize)]
+pub struct InputSettings {
+    pub bindings: HashMap<Action, Binding>,
+    #[serde(default)]
+    pub run_mode: RunMode,
+    /// clicking somewhere in the level walks the player there
+    #[serde(default)]
+    pub click_to_move: bool
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        let mut bindings = HashMap::new();
+        bindings.insert(Action::MoveUp, Binding::new(&[KeyCode::W], &[GamepadButtonType::DPadUp]));
+        bindings.insert(Action::MoveDown, Binding::new(&[KeyCode::S], &[GamepadButtonType::DPadDown]));
+        bindings.insert(Action::MoveLeft, Binding::new(&[KeyCode::A], &[GamepadButtonType::DPadLeft]));
+        bindings.insert(Action::MoveRight, Binding::new(&[KeyCode::D], &[GamepadButtonType::DPadRight]));
+        bindings.insert(Action::Run, Binding::new(&[KeyCode::LShift], &[GamepadButtonType::East]));
+        bindings.insert(Action::Interact, Binding::new(&[KeyCode::Space], &[GamepadButtonType::South]));
+        bindings.insert(Action::Pause, Binding::new(&[KeyCode::Escape], &[GamepadButtonType::Start]));
+        bindings.insert(Action::Inventory, Binding::new(&[KeyCode::E], &[GamepadButtonType::North]));
+        bindings.insert(Action::UseTool, Binding::new(&[KeyCode::F], &[GamepadButtonType::West]));
+        bindings.insert(Action::NextTool, Binding::new(&[KeyCode::Q], &[GamepadButtonType::RightTrigger]));
+        // only used with the mouse so it shares shift with running
+        bindings.insert(Action::QuickTransfer, Binding::new(&[KeyCode::LShift, KeyCode::RShift], &[]));
+        Self {
+            bindings,
+            run_mode: RunMode::default(),
+            click_to_move: false
+        }
+    }
+}
+
+impl InputSettings {
+    /// falls back to the default bindings if the file is missing or invalid
+    pub fn read() -> Self {
+        let mut settings = match read_to_string(INPUT_SETTINGS_PATH) {
+            Ok(settings) => ron::from_str::<InputSettings>(&settings.as_str()).unwrap_or_else(|err| {
+                log::warn!("invalid input settings using defaults: {}", err);
+                InputSettings::default()
+            }),
+            Err(_) => InputSettings::default()
+        };
+        // actions added after the settings file was written get their default binding
+        let defaults = InputSettings::default();
+        for action in Action::ALL {
+            if !settings.bindings.contains_key(&action) {
+                settings.bindings.insert(action, defaults.bindings.get(&action).unwrap().clone());
+            }
+        }
+        settings
+    }
+
+    pub fn write(&self) {
+        let mut file = File::create(INPUT_SETTINGS_PATH).unwrap();
+        file.write_all(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap().as_bytes()).unwrap();
+    }
+
+    pub fn binding(&self, action: Action) -> &Binding {
+        self.bindings.get(&action).expect("every action has a binding")
+    }
+}
+
+/// the state of every `Action` this frame, read it like `Input<KeyCode>`
+#[derive(Debug, Resource, Default)]
+pub struct ActionInput {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionInput {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// clears `action` for the rest of the frame so a state entered this frame doesn't see the same press again
+    pub fn consume(&mut self, action: Action) {
+        self.pressed.remove(&action);
+        self.just_pressed.remove(&action);
+    }
+}
+
+pub fn update_action_input(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    settings: Res<InputSettings>,
+    mut action_input: ResMut<ActionInput>
+) {
+    action_input.pressed.clear();
+    action_input.just_pressed.clear();
+
+    for (action, binding) in settings.bindings.iter() {
+        let mut pressed = binding.keys.iter().any(|key| keys.pressed(*key));
+        let mut just_pressed = binding.keys.iter().any(|key| keys.just_pressed(*key));
+
+        for gamepad in gamepads.iter() {
+            for button_type in binding.gamepad_buttons.iter() {
+                let button = GamepadButton::new(gamepad, *button_type);
+                pressed |= gamepad_buttons.pressed(button);
+                just_pressed |= gamepad_buttons.just_pressed(button);
+            }
+        }
+
+        if pressed {
+            action_input.pressed.insert(*action);
+        }
+        if just_pressed {
+            action_input.just_pressed.insert(*action);
+        }
+    }
+}
@@ -0,0 +1,446 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize, Deserializer, de::{self, Visitor, EnumAccess, VariantAccess, SeqAccess}};
+use crate::{crop::CropType, tool::{Tool, ToolKind}};
+
+/// how many slots the players inventory has
+pub const PLAYER_INVENTORY_SIZE: usize = 36;
+/// how many slots a placed chest has
+pub const CHEST_INVENTORY_SIZE: usize = 36;
+/// the most of a single item that fits in one slot, tools never stack
+pub const MAX_STACK_SIZE: u32 = 99;
+/// seeds sell for a fraction of the crop they grow into
+pub const SEED_PRICE_DIVISOR: u32 = 4;
+pub const WOOD_SELL_PRICE: u32 = 2;
+pub const STONE_SELL_PRICE: u32 = 2;
+pub const FERTILIZER_SELL_PRICE: u32 = 5;
+pub const HAY_SELL_PRICE: u32 = 2;
+pub const EGG_SELL_PRICE: u32 = 25;
+pub const MILK_SELL_PRICE: u32 = 60;
+pub const CHEST_SELL_PRICE: u32 = 40;
+pub const SPRINKLER_SELL_PRICE: u32 = 50;
+pub const SCARECROW_SELL_PRICE: u32 = 20;
+pub const FENCE_SELL_PRICE: u32 = 1;
+
+/// how good harvested produce is, better quality comes from a higher farming skill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Quality {
+    #[default]
+    Normal,
+    Silver,
+    Gold
+}
+
+impl Quality {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quality::Normal => "Normal",
+            Quality::Silver => "Silver",
+            Quality::Gold => "Gold"
+        }
+    }
+
+    pub fn price_multiplier(&self) -> f32 {
+        match self {
+            Quality::Normal => 1.,
+            Quality::Silver => 1.25,
+            Quality::Gold => 1.5
+        }
+    }
+}
+
+/// **NOTE** `Deserialize` is written by hand so produce from saves made before it had a quality still loads
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub enum Item {
+    Tool(Tool),
+    /// harvested produce, stacks only with produce of the same quality
+    Crop(CropType, Quality),
+    Seeds(CropType),
+    Wood,
+    Stone,
+    Fertilizer,
+    /// what farm animals eat
+    Hay,
+    Egg(Quality),
+    Milk(Quality),
+    /// holds items once placed
+    Chest,
+    /// waters the crops around it every morning once placed
+    Sprinkler,
+    /// keeps crows away from the crops around it once placed
+    Scarecrow,
+    Fence
+}
+
+impl Item {
+    pub fn name(&self) -> String {
+        match self {
+            Item::Tool(tool) => tool.name(),
+            Item::Crop(crop_type, Quality::Normal) => format!("{:?}", crop_type),
+            Item::Crop(crop_type, quality) => format!("{} {:?}", quality.name(), crop_type),
+            Item::Seeds(crop_type) => format!("{:?} Seeds", crop_type),
+            Item::Wood => "Wood".to_string(),
+            Item::Stone => "Stone".to_string(),
+            Item::Fertilizer => "Fertilizer".to_string(),
+            Item::Hay => "Hay".to_string(),
+            Item::Egg(Quality::Normal) => "Egg".to_string(),
+            Item::Egg(quality) => format!("{} Egg", quality.name()),
+            Item::Milk(Quality::Normal) => "Milk".to_string(),
+            Item::Milk(quality) => format!("{} Milk", quality.name()),
+            Item::Chest => "Chest".to_string(),
+            Item::Sprinkler => "Sprinkler".to_string(),
+            Item::Scarecrow => "Scarecrow".to_string(),
+            Item::Fence => "Fence".to_string()
+        }
+    }
+
+    /// the quality of produce, `None` for everything else
+    pub fn quality(&self) -> Option<Quality> {
+        match self {
+            Item::Crop(_, quality) | Item::Egg(quality) | Item::Milk(quality) => Some(*quality),
+            _ => None
+        }
+    }
+
+    /// the same item in normal quality
+    pub fn normal_quality(&self) -> Item {
+        match self {
+            Item::Crop(crop_type, _) => Item::Crop(crop_type.clone(), Quality::Normal),
+            Item::Egg(_) => Item::Egg(Quality::Normal),
+            Item::Milk(_) => Item::Milk(Quality::Normal),
+            item => item.clone()
+        }
+    }
+
+    /// what the shipping bin pays for one of the item, `None` for items that can't be sold
+    pub fn sell_price(&self) -> Option<u32> {
+        match self {
+            Item::Tool(_) => None,
+            Item::Crop(CropType::Dead, _) => None,
+            Item::Crop(crop_type, quality) => Some((crop_type.sell_price() as f32 * quality.price_multiplier()).round() as u32),
+            Item::Seeds(crop_type) => Some(crop_type.sell_price() / SEED_PRICE_DIVISOR),
+            Item::Wood => Some(WOOD_SELL_PRICE),
+            Item::Stone => Some(STONE_SELL_PRICE),
+            Item::Fertilizer => Some(FERTILIZER_SELL_PRICE),
+            Item::Hay => Some(HAY_SELL_PRICE),
+            Item::Egg(quality) => Some((EGG_SELL_PRICE as f32 * quality.price_multiplier()).round() as u32),
+            Item::Milk(quality) => Some((MILK_SELL_PRICE as f32 * quality.price_multiplier()).round() as u32),
+            Item::Chest => Some(CHEST_SELL_PRICE),
+            Item::Sprinkler => Some(SPRINKLER_SELL_PRICE),
+            Item::Scarecrow => Some(SCARECROW_SELL_PRICE),
+            Item::Fence => Some(FENCE_SELL_PRICE)
+        }
+    }
+
+    pub fn max_stack(&self) -> u32 {
+        match self {
+            Item::Tool(_) => 1,
+            _ => MAX_STACK_SIZE
+        }
+    }
+}
+
+/// the variant names of `Item` in the order they are declared
+const ITEM_VARIANTS: &[&str] = &[
+    "Tool", "Crop", "Seeds", "Wood", "Stone", "Fertilizer", "Hay", "Egg", "Milk", "Chest", "Sprinkler", "Scarecrow", "Fence"
+];
+
+#[derive(Deserialize)]
+#[serde(variant_identifier)]
+enum ItemVariant {
+    Tool,
+    Crop,
+    Seeds,
+    Wood,
+    Stone,
+    Fertilizer,
+    Hay,
+    Egg,
+    Milk,
+    Chest,
+    Sprinkler,
+    Scarecrow,
+    Fence
+}
+
+impl<'de> Deserialize<'de> for Item {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Item", ITEM_VARIANTS, ItemVisitor)
+    }
+}
+
+struct ItemVisitor;
+
+impl<'de> Visitor<'de> for ItemVisitor {
+    type Value = Item;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an item")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Item, A::Error> {
+        let (variant, access) = data.variant::<ItemVariant>()?;
+        let item = match variant {
+            ItemVariant::Tool => Item::Tool(access.newtype_variant()?),
+            ItemVariant::Crop => access.tuple_variant(2, CropVisitor)?,
+            ItemVariant::Seeds => Item::Seeds(access.newtype_variant()?),
+            ItemVariant::Egg => Item::Egg(access.newtype_variant()?),
+            ItemVariant::Milk => Item::Milk(access.newtype_variant()?),
+            unit => {
+                access.unit_variant()?;
+                match unit {
+                    ItemVariant::Wood => Item::Wood,
+                    ItemVariant::Stone => Item::Stone,
+                    ItemVariant::Fertilizer => Item::Fertilizer,
+                    ItemVariant::Hay => Item::Hay,
+                    ItemVariant::Chest => Item::Chest,
+                    ItemVariant::Sprinkler => Item::Sprinkler,
+                    ItemVariant::Scarecrow => Item::Scarecrow,
+                    ItemVariant::Fence => Item::Fence,
+                    ItemVariant::Tool | ItemVariant::Crop | ItemVariant::Seeds | ItemVariant::Egg | ItemVariant::Milk => unreachable!()
+                }
+            }
+        };
+        Ok(item)
+    }
+}
+
+/// reads `Item::Crop`, version 2 saves wrote it as `Crop(Potato)` without a quality which loads as normal quality
+struct CropVisitor;
+
+impl<'de> Visitor<'de> for CropVisitor {
+    type Value = Item;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a crop type followed by an optional quality")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Item, A::Error> {
+        let crop_type = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let quality = seq.next_element()?.unwrap_or_default();
+        Ok(Item::Crop(crop_type, quality))
+    }
+}
+
+/// what clicking a slot in the open inventory does, reset to `InventoryMode::Use` whenever the inventory closes
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryMode {
+    /// eats food or places objects in front of the player
+    #[default]
+    Use,
+    /// puts the stack in the shipping bin
+    Ship,
+    /// gives one of the item to the villager
+    Gift(Entity),
+    /// moves items between the player and the chest
+    Chest(Entity)
+}
+
+impl InventoryMode {
+    /// shown under the inventory
+    pub fn hint(&self) -> &'static str {
+        match self {
+            InventoryMode::Use => "Click food to eat it, an object to place it or seeds and fertilizer to use them",
+            InventoryMode::Ship => "Click an item to ship it, it is paid for tomorrow morning",
+            InventoryMode::Gift(_) => "Click an item to give one as a gift",
+            InventoryMode::Chest(_) => "Click an item to move one to the other side, hold shift to move the whole stack"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32
+}
+
+impl ItemStack {
+    pub fn new(item: Item, count: u32) -> Self {
+        Self { item, count }
+    }
+}
+
+/// A fixed number of slots that each hold a single `ItemStack`
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(PLAYER_INVENTORY_SIZE)
+    }
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size]
+        }
+    }
+
+    /// the inventory a new game starts with, one of every tool
+    pub fn starting() -> Self {
+        let mut inventory = Self::default();
+        for kind in ToolKind::ALL {
+            inventory.add(Item::Tool(Tool::new(kind)), 1);
+        }
+        inventory
+    }
+
+    /// adds as much of `count` as fits, topping up existing stacks first. Returns how many didn't fit
+    pub fn add(&mut self, item: Item, mut count: u32) -> u32 {
+        let max_stack = item.max_stack();
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                break;
+            }
+            if stack.item == item && stack.count < max_stack {
+                let added = count.min(max_stack - stack.count);
+                stack.count += added;
+                count -= added;
+            }
+        }
+        for slot in self.slots.iter_mut() {
+            if count == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let added = count.min(max_stack);
+                *slot = Some(ItemStack::new(item.clone(), added));
+                count -= added;
+            }
+        }
+        count
+    }
+
+    /// whether all of `count` would fit
+    pub fn can_add(&self, item: &Item, count: u32) -> bool {
+        let mut inventory = self.clone();
+        inventory.add(item.clone(), count) == 0
+    }
+
+    pub fn count(&self, item: &Item) -> u32 {
+        self.slots.iter()
+            .flatten()
+            .filter(|stack| stack.item == *item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /// the best tool of `kind` in the inventory
+    pub fn tool(&self, kind: ToolKind) -> Option<Tool> {
+        self.slots.iter()
+            .flatten()
+            .filter_map(|stack| match stack.item {
+                Item::Tool(tool) if tool.kind == kind => Some(tool),
+                _ => None
+            })
+            .max_by_key(|tool| tool.level)
+    }
+
+    /// replaces the best tool of `kind` with its next level in the same slot. Returns the upgraded tool, `None` if there is no tool of `kind` or it is already at the highest level
+    pub fn upgrade_tool(&mut self, kind: ToolKind) -> Option<Tool> {
+        let tool = Item::Tool(self.tool(kind)?);
+        let stack = self.slots.iter_mut()
+            .flatten()
+            .find(|stack| stack.item == tool)?;
+        let upgraded = match stack.item {
+            Item::Tool(tool) => tool.upgraded()?,
+            _ => return None
+        };
+        stack.item = Item::Tool(upgraded);
+        Some(upgraded)
+    }
+
+    /// removes `count` of `item` only if there is enough of it
+    pub fn remove(&mut self, item: &Item, mut count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+        for slot in self.slots.iter_mut() {
+            if count == 0 {
+                break;
+            }
+            if let Some(stack) = slot {
+                if stack.item != *item {
+                    continue;
+                }
+                let removed = count.min(stack.count);
+                stack.count -= removed;
+                count -= removed;
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+        true
+    }
+
+    /// removes `count` from the stack in `index`
+    pub fn remove_from_slot(&mut self, index: usize, count: u32) -> Option<ItemStack> {
+        let slot = self.slots.get_mut(index)?;
+        let stack = slot.as_mut()?;
+        let removed = count.min(stack.count);
+        stack.count -= removed;
+        let item = stack.item.clone();
+        if stack.count == 0 {
+            *slot = None;
+        }
+        Some(ItemStack::new(item, removed))
+    }
+
+    /// moves up to `count` from the stack in `index` into `other`, whatever doesn't fit stays. Returns how many were moved
+    pub fn move_to(&mut self, index: usize, count: u32, other: &mut Inventory) -> u32 {
+        let (item, count) = match self.get(index) {
+            Some(stack) => (stack.item.clone(), count.min(stack.count)),
+            None => return 0
+        };
+        let moved = count - other.add(item, count);
+        self.remove_from_slot(index, moved);
+        moved
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.is_none())
+    }
+
+    pub fn get(&self, index: usize) -> Option<&ItemStack> {
+        self.slots.get(index).and_then(|slot| slot.as_ref())
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut ItemStack> {
+        self.slots.get_mut(index).and_then(|slot| slot.as_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tool::TOOL_MAX_LEVEL;
+    use super::*;
+
+    #[test]
+    fn upgrades_tool_in_place() {
+        let mut inventory = Inventory::starting();
+        let slot = inventory.slots.iter()
+            .position(|slot| matches!(slot, Some(ItemStack { item: Item::Tool(Tool { kind: ToolKind::Axe, .. }), .. })))
+            .unwrap();
+
+        assert_eq!(inventory.upgrade_tool(ToolKind::Axe), Some(Tool { kind: ToolKind::Axe, level: 1 }));
+        assert_eq!(inventory.upgrade_tool(ToolKind::Axe), Some(Tool { kind: ToolKind::Axe, level: 2 }));
+        assert_eq!(inventory.get(slot).map(|stack| stack.item.clone()), Some(Item::Tool(Tool { kind: ToolKind::Axe, level: 2 })));
+        assert_eq!(inventory.count(&Item::Tool(Tool::new(ToolKind::Axe))), 0);
+        assert_eq!(inventory.tool(ToolKind::Hoe), Some(Tool::new(ToolKind::Hoe)));
+    }
+
+    #[test]
+    fn doesnt_upgrade_past_max_level_or_missing_tools() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.upgrade_tool(ToolKind::Pickaxe), None);
+
+        inventory.add(Item::Tool(Tool { kind: ToolKind::Pickaxe, level: TOOL_MAX_LEVEL }), 1);
+        assert_eq!(inventory.upgrade_tool(ToolKind::Pickaxe), None);
+        assert_eq!(inventory.tool(ToolKind::Pickaxe), Some(Tool { kind: ToolKind::Pickaxe, level: TOOL_MAX_LEVEL }));
+    }
+}
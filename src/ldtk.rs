@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 // use bevy_animations::*;
-use crate::{gate::*, animations::*, player::*, path::*, crop::*, LdtkAssets, GameState, save::SaveName};
-use bevy_ecs_ldtk::{prelude::*, ldtk::Level};
+use crate::{gate::*, fence::*, animations::*, player::*, path::*, crop::*, LdtkAssets, GameState, save::SaveName, level::MAIN_LEVEL, tool::BrokenBreakables, calendar::{Calendar, Clock}, economy::{ShippingBinContents, CropMarket, market::Market}, shop::ShopPurchases, dialogue::DialogueFlags, npc::{Relationships, friendship::KnownRecipes}, rng::GameRng};
+use bevy_ecs_ldtk::prelude::*;
 
 use bevy_rapier2d::prelude::*;
 
@@ -27,16 +27,20 @@ impl Plugin for FarmWorldPlugin {
             .add_system_set(SystemSet::on_enter(GameState::LoadingAnimations)
                 .with_system(set_animations)
             )
-            .insert_resource(LevelSelection::Identifier("Main_Farm".to_string()))
-            .insert_resource(CurrentLevel::default())
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(set_gate_animations)
+                .with_system(close_gates_behind)
+            )
+            .insert_resource(FenceColliderStats::default())
+            .add_system(rebuild_fence_colliders)
             // .add_system(set_animations)
         ;
+        if cfg!(feature = "fence_benchmark") {
+            app.add_system(benchmark_fence_colliders);
+        }
     }
 }
 
-#[derive(Debug, Resource, Clone, Default)]
-pub struct CurrentLevel(Level);
-
 #[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
 pub struct ColliderBundle {
     pub collider: Collider,
@@ -57,35 +61,35 @@ pub struct SensorBundle {
     pub rotation_constraints: LockedAxes,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
-pub struct Fence;
-
-#[derive(Clone, Debug, Default, Bundle)]
-pub struct FenceBundle {
-    fence: Fence,
-    #[bundle]
-    pub collider_bundle: ColliderBundle,
-    pub ldtk: Ldtk
-}
-
-impl LdtkIntCell for FenceBundle {
-    fn bundle_int_cell(_: IntGridCell, _: &LayerInstance) -> Self {
-        Self {
-            fence: Fence, 
-            collider_bundle: ColliderBundle { 
-                collider: Collider::cuboid(8., 4.), 
-                rigid_body: RigidBody::Fixed,
-                ..Default::default()
-            } ,
-            ldtk: Ldtk
-        }
-    }
-}
-
 fn spawn_world(
     mut commands: Commands, 
     ldtk_assets: Res<LdtkAssets>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut broken_breakables: ResMut<BrokenBreakables>,
+    mut calendar: ResMut<Calendar>,
+    mut clock: ResMut<Clock>,
+    mut shipping_bin_contents: ResMut<ShippingBinContents>,
+    mut shop_purchases: ResMut<ShopPurchases>,
+    mut crop_market: ResMut<CropMarket>,
+    mut dialogue_flags: ResMut<DialogueFlags>,
+    mut relationships: ResMut<Relationships>,
+    mut known_recipes: ResMut<KnownRecipes>,
+    mut game_rng: ResMut<GameRng>
 ) {
+    // the player always spawns in the main level, loading a save moves them to their saved level afterwards
+    *level_selection = LevelSelection::Identifier(MAIN_LEVEL.to_string());
+    // a save restores its own broken breakables, date, time, shipping bin, shop purchases, market, dialogue flags, relationships, recipes and rng seed
+    broken_breakables.0.clear();
+    *calendar = Calendar::default();
+    *clock = Clock::default();
+    shipping_bin_contents.0.clear();
+    shop_purchases.0.clear();
+    crop_market.market = Market::new(rand::random());
+    dialogue_flags.0.clear();
+    relationships.0.clear();
+    known_recipes.0.clear();
+    *game_rng = GameRng::default();
+
     let ldtk_handle = ldtk_assets.ldtk_world.clone();
 
     let ldtk_world = LdtkWorldBundle {
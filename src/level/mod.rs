@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, ldtk::Level};
+use crate::{door::*, GameState};
+
+use self::systems::*;
+
+pub mod systems;
+
+/// the level a new game starts in and the level saves made before multiple levels existed belong to
+pub const MAIN_LEVEL: &str = "Main_Farm";
+pub const FADE_DURATION: f32 = 0.35;
+/// how long to wait on the target door to show up before giving up and fading back in wherever the player is
+pub const DOOR_SEARCH_TIMEOUT: f32 = 2.;
+/// how thick the invisible walls around the edges of a level are
+pub const BOUNDARY_THICKNESS: f32 = 16.;
+/// used before the first level has spawned
+pub const DEFAULT_GRID_SIZE: i32 = 16;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkDoor>("Door")
+            .insert_resource(LevelSelection::Identifier(MAIN_LEVEL.to_string()))
+            .insert_resource(CurrentLevel::default())
+            .insert_resource(LevelTransition::default())
+            .insert_resource(IgnoredDoor::default())
+            .add_system(update_current_level)
+            .add_system(toggle_level_entities)
+            .add_system(rebuild_level_boundaries)
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(check_door_collisions)
+                .with_system(update_level_transition)
+            )
+        ;
+    }
+}
+
+/// The LDtk level the player is currently in
+#[derive(Debug, Resource, Clone, Default)]
+pub struct CurrentLevel(pub Level);
+
+impl CurrentLevel {
+    pub fn identifier(&self) -> &str {
+        &self.0.identifier
+    }
+
+    /// the grid size of the levels first layer, every layer in the project shares the same one
+    pub fn grid_size(&self) -> i32 {
+        self.0.layer_instances.as_ref()
+            .and_then(|layer_instances| layer_instances.first())
+            .map(|layer_instance| layer_instance.grid_size)
+            .unwrap_or(DEFAULT_GRID_SIZE)
+    }
+}
+
+/// Marks an entity that isn't spawned by LDtk but still belongs to a level i.e crops
+///
+/// these entities stay alive when the player leaves their level, they are just hidden and their colliders disabled until the player comes back
+#[derive(Debug, Component, Clone, Default, PartialEq, Eq)]
+pub struct LevelEntity(pub String);
+
+#[derive(Debug, Resource, Default)]
+pub enum LevelTransition {
+    #[default]
+    Idle,
+    FadingOut {
+        target_level: String,
+        target_door: String,
+        timer: Timer
+    },
+    /// waiting on the new level to spawn the door we are heading to
+    Loading {
+        target_level: String,
+        target_door: String,
+        /// the global transforms of the new doors are only set once the level has been transformed
+        transformed: bool,
+        timer: Timer
+    },
+    FadingIn {
+        timer: Timer
+    }
+}
+
+impl LevelTransition {
+    pub fn is_idle(&self) -> bool {
+        matches!(self, LevelTransition::Idle)
+    }
+}
+
+/// the door the player arrived on. It can't be used again until the player steps off of it otherwise we would bounce between levels forever
+#[derive(Debug, Resource, Default)]
+pub struct IgnoredDoor(pub Option<Entity>);
+
+/// one of the four fixed colliders that keep the player inside the current level
+#[derive(Component)]
+pub struct LevelBoundary;
+
+/// full screen node used to fade between levels
+#[derive(Component)]
+pub struct FadeOverlay;
@@ -0,0 +1,242 @@
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::{player::Player, ldtk::ColliderBundle};
+
+use super::*;
+
+/// keeps `CurrentLevel` in sync with whichever level LDtk spawned last
+pub fn update_current_level(
+    mut level_events: EventReader<LevelEvent>,
+    ldtk_handle_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut current_level: ResMut<CurrentLevel>
+) {
+    for event in level_events.iter() {
+        let iid = match event {
+            LevelEvent::Spawned(iid) => iid,
+            _ => continue
+        };
+        for ldtk_handle in ldtk_handle_query.iter() {
+            let ldtk_asset = match ldtk_assets.get(ldtk_handle) {
+                Some(ldtk_asset) => ldtk_asset,
+                None => continue
+            };
+            if let Some(level) = ldtk_asset.project.levels.iter().find(|level| level.iid == *iid) {
+                log::info!("entered level {}", level.identifier);
+                *current_level = CurrentLevel(level.clone());
+            }
+        }
+    }
+}
+
+/// hides and disables the colliders of every `LevelEntity` that isn't in the current level
+pub fn toggle_level_entities(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    mut level_entity_query: Query<(Entity, &LevelEntity, ChangeTrackers<LevelEntity>, &mut Visibility, Option<&Children>)>
+) {
+    for (entity, level_entity, level_entity_tracker, mut visibility, children) in level_entity_query.iter_mut() {
+        // only touch entities when the level changes or they were just spawned
+        if !current_level.is_changed() && !level_entity_tracker.is_added() {
+            continue;
+        }
+        let active = level_entity.0 == current_level.identifier();
+
+        *visibility = if active { Visibility::VISIBLE } else { Visibility::INVISIBLE };
+
+        let mut entities = vec![entity];
+        if let Some(children) = children {
+            entities.extend(children.iter());
+        }
+        for entity in entities {
+            if active {
+                commands.entity(entity).remove::<ColliderDisabled>();
+            }
+            else {
+                commands.entity(entity).insert(ColliderDisabled);
+            }
+        }
+    }
+}
+
+/// walls the current level in with fixed colliders once it has finished spawning
+pub fn rebuild_level_boundaries(
+    mut commands: Commands,
+    mut level_events: EventReader<LevelEvent>,
+    level_query: Query<(&Handle<LdtkLevel>, &GlobalTransform)>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+    boundary_query: Query<Entity, With<LevelBoundary>>,
+    current_level: Res<CurrentLevel>
+) {
+    if !level_events.iter().any(|event| matches!(event, LevelEvent::Transformed(_))) {
+        return;
+    }
+
+    for entity in boundary_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for (level_handle, level_transform) in level_query.iter() {
+        let ldtk_level = match ldtk_levels.get(level_handle) {
+            Some(ldtk_level) => ldtk_level,
+            None => continue
+        };
+        if ldtk_level.level.iid != current_level.0.iid {
+            continue;
+        }
+
+        let min = level_transform.translation().truncate();
+        let size = Vec2::new(ldtk_level.level.px_wid as f32, ldtk_level.level.px_hei as f32);
+        let half_thickness = BOUNDARY_THICKNESS / 2.;
+
+        // (center, half extents) for the left, right, bottom and top walls
+        let walls = [
+            (Vec2::new(min.x - half_thickness, min.y + size.y / 2.), Vec2::new(half_thickness, size.y / 2. + BOUNDARY_THICKNESS)),
+            (Vec2::new(min.x + size.x + half_thickness, min.y + size.y / 2.), Vec2::new(half_thickness, size.y / 2. + BOUNDARY_THICKNESS)),
+            (Vec2::new(min.x + size.x / 2., min.y - half_thickness), Vec2::new(size.x / 2. + BOUNDARY_THICKNESS, half_thickness)),
+            (Vec2::new(min.x + size.x / 2., min.y + size.y + half_thickness), Vec2::new(size.x / 2. + BOUNDARY_THICKNESS, half_thickness)),
+        ];
+
+        for (center, half_extents) in walls {
+            commands.spawn(ColliderBundle {
+                collider: Collider::cuboid(half_extents.x, half_extents.y),
+                rigid_body: RigidBody::Fixed,
+                ..Default::default()
+            })
+                .insert(TransformBundle::from(Transform::from_translation(center.extend(0.))))
+                .insert(LevelBoundary)
+            ;
+        }
+    }
+}
+
+/// starts a level transition when the player walks into a door
+pub fn check_door_collisions(
+    mut commands: Commands,
+    door_query: Query<(Entity, &Door)>,
+    player_query: Query<Entity, With<Player>>,
+    context: Res<RapierContext>,
+    mut transition: ResMut<LevelTransition>,
+    mut ignored_door: ResMut<IgnoredDoor>
+) {
+    if !transition.is_idle() {
+        return;
+    }
+
+    // the player entity might not be loaded in yet
+    let player_entity = match player_query.get_single() {
+        Ok(entity) => entity,
+        Err(_) => return
+    };
+
+    for (door_entity, door) in door_query.iter() {
+        if context.intersection_pair(player_entity, door_entity) != Some(true) {
+            if ignored_door.0 == Some(door_entity) {
+                ignored_door.0 = None;
+            }
+            continue;
+        }
+        if ignored_door.0 == Some(door_entity) || door.target_level.is_empty() {
+            continue;
+        }
+
+        log::info!("door {} leads to {} in {}", door.id, door.target_door, door.target_level);
+
+        commands.spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.).into(),
+            z_index: ZIndex::Global(100),
+            ..default()
+        })
+            .insert(FadeOverlay)
+        ;
+
+        *transition = LevelTransition::FadingOut {
+            target_level: door.target_level.clone(),
+            target_door: door.target_door.clone(),
+            timer: Timer::from_seconds(FADE_DURATION, TimerMode::Once)
+        };
+        return;
+    }
+}
+
+/// drives the fade out -> level swap -> fade in sequence
+pub fn update_level_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<LevelTransition>,
+    mut ignored_door: ResMut<IgnoredDoor>,
+    mut level_selection: ResMut<LevelSelection>,
+    current_level: Res<CurrentLevel>,
+    mut overlay_query: Query<(Entity, &mut BackgroundColor), With<FadeOverlay>>,
+    mut level_events: EventReader<LevelEvent>,
+    door_query: Query<(Entity, &Door, &GlobalTransform)>,
+    mut player_query: Query<&mut Transform, With<Player>>
+) {
+    let level_transformed = level_events.iter().any(|event| matches!(event, LevelEvent::Transformed(_)));
+
+    let next_transition = match transition.as_mut() {
+        LevelTransition::Idle => return,
+        LevelTransition::FadingOut { target_level, target_door, timer } => {
+            timer.tick(time.delta());
+            for (_, mut color) in overlay_query.iter_mut() {
+                *color = Color::rgba(0., 0., 0., timer.percent()).into();
+            }
+            if !timer.finished() {
+                return;
+            }
+            *level_selection = LevelSelection::Identifier(target_level.clone());
+            LevelTransition::Loading {
+                target_level: target_level.clone(),
+                target_door: target_door.clone(),
+                transformed: false,
+                timer: Timer::from_seconds(DOOR_SEARCH_TIMEOUT, TimerMode::Once)
+            }
+        },
+        LevelTransition::Loading { target_level, target_door, transformed, timer } => {
+            timer.tick(time.delta());
+            *transformed |= level_transformed;
+            let door = door_query.iter()
+                .find(|(_, door, _)| door.id == *target_door)
+                .filter(|_| *transformed && current_level.identifier() == target_level.as_str());
+            match door {
+                Some((door_entity, _, door_transform)) => {
+                    // doors are children of their level and layer so only their global translation is in world space
+                    let mut player_transform = player_query.single_mut();
+                    player_transform.translation.x = door_transform.translation().x;
+                    player_transform.translation.y = door_transform.translation().y;
+                    ignored_door.0 = Some(door_entity);
+                },
+                None => {
+                    if !timer.finished() {
+                        return;
+                    }
+                    // most likely a typo in the LDtk fields
+                    log::error!("door {} wasn't found in {}", target_door, target_level);
+                }
+            }
+            LevelTransition::FadingIn {
+                timer: Timer::from_seconds(FADE_DURATION, TimerMode::Once)
+            }
+        },
+        LevelTransition::FadingIn { timer } => {
+            timer.tick(time.delta());
+            for (_, mut color) in overlay_query.iter_mut() {
+                *color = Color::rgba(0., 0., 0., 1. - timer.percent()).into();
+            }
+            if !timer.finished() {
+                return;
+            }
+            for (overlay_entity, _) in overlay_query.iter() {
+                commands.entity(overlay_entity).despawn_recursive();
+            }
+            LevelTransition::Idle
+        }
+    };
+    *transition = next_transition;
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use bevy::{prelude::*};
 
-use crate::{OtherAssets, GameState};
+use crate::{OtherAssets, GameState, crop::{CropType, CROP_MAX_STAGE}, animal::AnimalKind};
 
 #[derive(Resource)]
 pub struct Atlases {
@@ -16,30 +16,42 @@ pub fn load_altases(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     mut state: ResMut<State<GameState>>
 ) {
-    let crop_growth_highlighted_atlas = TextureAtlas::from_grid(textures.corn_growth_highlighted.clone(), Vec2::new(16., 29.), 5, 1, None, None);
-    let crop_growth_atlas = TextureAtlas::from_grid(textures.corn_growth.clone(), Vec2::new(16., 29.), 5, 1, None, None);
-    let dead_crop_atlas = TextureAtlas::from_grid(textures.dead_crop.clone(), Vec2::new(16., 29.), 3, 1, None, None);
-    let dead_crop_highlighted_atlas = TextureAtlas::from_grid(textures.dead_crop_highlighted.clone(), Vec2::new(16., 29.), 3, 1, None, None);
+    let potato_growth_atlas = TextureAtlas::from_grid(textures.potato_growth.clone(), CropType::Potato.sprite_size(), CROP_MAX_STAGE, 1, None, None);
+    let carrot_growth_atlas = TextureAtlas::from_grid(textures.carrot_growth.clone(), CropType::Carrot.sprite_size(), CROP_MAX_STAGE, 1, None, None);
+    let cabbage_growth_atlas = TextureAtlas::from_grid(textures.cabbage_growth.clone(), CropType::Cabbage.sprite_size(), CROP_MAX_STAGE, 1, None, None);
+    let crop_growth_atlas = TextureAtlas::from_grid(textures.corn_growth.clone(), CropType::Corn.sprite_size(), CROP_MAX_STAGE, 1, None, None);
+    let dead_crop_atlas = TextureAtlas::from_grid(textures.dead_crop.clone(), CropType::Dead.sprite_size(), 3, 1, None, None);
     let gate_atlas = TextureAtlas::from_grid(textures.gate.clone(), Vec2::new(32., 50.), 3, 1, None, Some(Vec2::new(16., 16.)));
     let player_atlas = TextureAtlas::from_grid(textures.player.clone(), Vec2::new(64., 64.), 8, 8, None, None);
     let player_farming_atlas = TextureAtlas::from_grid(textures.player_farming.clone(), Vec2::new(64., 64.), 4, 4,None, None);
+    let hoe_and_axe_atlas = TextureAtlas::from_grid(textures.hoe_and_axe.clone(), Vec2::new(32., 32.), 7, 2, None, None);
+    let chicken_atlas = TextureAtlas::from_grid(textures.chicken.clone(), AnimalKind::Chicken.sprite_size(), 4, 4, None, None);
+    let cow_atlas = TextureAtlas::from_grid(textures.cow.clone(), AnimalKind::Cow.sprite_size(), 4, 4, None, None);
     
-    let crop_growth_highlighted_handle = texture_atlases.add(crop_growth_highlighted_atlas);
+    let potato_growth_handle = texture_atlases.add(potato_growth_atlas);
+    let carrot_growth_handle = texture_atlases.add(carrot_growth_atlas);
+    let cabbage_growth_handle = texture_atlases.add(cabbage_growth_atlas);
     let crop_growth_handle = texture_atlases.add(crop_growth_atlas);
     let dead_crop_handle = texture_atlases.add(dead_crop_atlas);
-    let dead_crop_highlighted_handle = texture_atlases.add(dead_crop_highlighted_atlas);
     let player_handle = texture_atlases.add(player_atlas); 
     let player_farming_handle = texture_atlases.add(player_farming_atlas);
     let gate_handle = texture_atlases.add(gate_atlas);
+    let hoe_and_axe_handle = texture_atlases.add(hoe_and_axe_atlas);
+    let chicken_handle = texture_atlases.add(chicken_atlas);
+    let cow_handle = texture_atlases.add(cow_atlas);
 
     let mut map = HashMap::new();
-    map.insert("corn_growth_highlighted", crop_growth_highlighted_handle);
+    map.insert("potato_growth", potato_growth_handle);
+    map.insert("carrot_growth", carrot_growth_handle);
+    map.insert("cabbage_growth", cabbage_growth_handle);
     map.insert("corn_growth", crop_growth_handle);
     map.insert("dead_crop", dead_crop_handle);
-    map.insert("dead_crop_highlighted", dead_crop_highlighted_handle);
     map.insert("player", player_handle);
     map.insert("player_farming", player_farming_handle);
     map.insert("gate", gate_handle);
+    map.insert("hoe_and_axe", hoe_and_axe_handle);
+    map.insert(AnimalKind::Chicken.atlas_name(), chicken_handle);
+    map.insert(AnimalKind::Cow.atlas_name(), cow_handle);
 
     commands.insert_resource(Atlases {
         handles: map
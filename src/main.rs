@@ -1,7 +1,7 @@
 use bevy::{prelude::*, log};
 use bevy_asset_loader::prelude::*;
 use load_atlases::load_altases;
-use mechanics::perspective::PerspectiveMechanicsPlugin;
+use mechanics::{perspective::PerspectiveMechanicsPlugin, interaction::InteractionMechanicsPlugin, highlight::HighlightMechanicsPlugin};
 use save::SavePlugin;
 use ui::UIPlugin;
 // use bevy_animations::*;
@@ -9,6 +9,23 @@ use crate::{bevy_animations::*, player::*, crop::*, mechanics::*};
 use bevy_rapier2d::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use ldtk::FarmWorldPlugin;
+use level::LevelPlugin;
+use hot_reload::HotReloadPlugin;
+use input::ActionInputPlugin;
+use tool::ToolPlugin;
+use energy::EnergyPlugin;
+use skill::SkillPlugin;
+use calendar::CalendarPlugin;
+use economy::EconomyPlugin;
+use shop::ShopPlugin;
+use npc::NpcPlugin;
+use navigation::NavigationPlugin;
+use dialogue::DialoguePlugin;
+use animal::AnimalPlugin;
+use pest::PestPlugin;
+use placement::PlacementPlugin;
+use chest::ChestPlugin;
+use rng::GameRng;
 
 mod player;
 mod path;
@@ -16,11 +33,31 @@ mod crop;
 mod ldtk;
 mod bevy_animations;
 mod gate;
+mod fence;
+mod door;
+mod level;
 mod animations;
 mod load_atlases;
 mod ui;
 mod mechanics;
 mod save;
+mod hot_reload;
+mod input;
+mod item;
+mod tool;
+mod energy;
+mod skill;
+mod calendar;
+mod economy;
+mod shop;
+mod navigation;
+mod npc;
+mod dialogue;
+mod animal;
+mod pest;
+mod placement;
+mod chest;
+mod rng;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
 pub enum GameState {
@@ -38,6 +75,12 @@ pub enum GameState {
     Inventory,
     LoadingPause,
     Pause,
+    LoadingControls,
+    Controls,
+    LoadingShop,
+    Shop,
+    LoadingDialogue,
+    Dialogue,
     LoadingMainMenu,
     MainMenu,
     Unload,
@@ -108,27 +151,60 @@ pub struct OtherAssets {
     player: Handle<Image>,
     #[asset(path = "buildings/fence_gate.png")]
     gate: Handle<Image>,
+    #[asset(path = "20.01a - Character Base 2.5b/hoe_and_axe.png")]
+    hoe_and_axe: Handle<Image>,
+    #[asset(path = "crops/potato_growth.png")]
+    potato_growth: Handle<Image>,
+    #[asset(path = "crops/carrot_growth.png")]
+    carrot_growth: Handle<Image>,
+    #[asset(path = "crops/cabbage_growth.png")]
+    cabbage_growth: Handle<Image>,
     #[asset(path = "crops/corn_growth.png")]
     corn_growth: Handle<Image>,
-    #[asset(path = "crops/corn_growth_highlighted.png")]
-    corn_growth_highlighted: Handle<Image>,
     #[asset(path = "crops/dead_crop.png")]
     dead_crop: Handle<Image>,
-    #[asset(path = "crops/dead_crop_highlighted.png")]
-    dead_crop_highlighted: Handle<Image>,
+    #[asset(path = "animals/chicken.png")]
+    chicken: Handle<Image>,
+    #[asset(path = "animals/cow.png")]
+    cow: Handle<Image>,
 }
 
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugins(DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            // lets the LDtk project be edited while the game is running
+            .set(AssetPlugin {
+                watch_for_changes: cfg!(feature = "dev"),
+                ..Default::default()
+            })
+        )
         .add_plugin(RapierDebugRenderPlugin::default())
         .add_plugin(PlayerPlugin)
         .add_plugin(CropPlugin)
         .add_plugin(UIPlugin)
         .add_plugin(LdtkPlugin)
         .add_plugin(FarmWorldPlugin)
+        .add_plugin(LevelPlugin)
         .add_plugin(PerspectiveMechanicsPlugin)
+        .add_plugin(InteractionMechanicsPlugin)
+        .add_plugin(HighlightMechanicsPlugin)
         .add_plugin(SavePlugin)
+        .add_plugin(HotReloadPlugin)
+        .add_plugin(ActionInputPlugin)
+        .add_plugin(ToolPlugin)
+        .add_plugin(EnergyPlugin)
+        .add_plugin(SkillPlugin)
+        .add_plugin(CalendarPlugin)
+        .add_plugin(EconomyPlugin)
+        .add_plugin(ShopPlugin)
+        .add_plugin(NavigationPlugin)
+        .add_plugin(NpcPlugin)
+        .add_plugin(DialoguePlugin)
+        .add_plugin(AnimalPlugin)
+        .add_plugin(PestPlugin)
+        .add_plugin(PlacementPlugin)
+        .add_plugin(ChestPlugin)
         .add_state::<GameState>(GameState::default())
         .add_loading_state(
             LoadingState::new(GameState::LoadingAssets)
@@ -146,10 +222,17 @@ fn main() {
         })
         .insert_resource(RapierConfiguration {
             gravity: Vec2::ZERO,
+            // step the physics with the frame time so velocities are frame rate independent
+            timestep_mode: TimestepMode::Variable {
+                max_dt: 1. / 60.,
+                time_scale: 1.,
+                substeps: 1
+            },
             ..Default::default()
         })
         .insert_resource(NextState::default())
         .insert_resource(OldState::default())
+        .insert_resource(GameRng::default())
         .add_startup_system(setup)
         .add_system(debug_state)
         // .add_system(display_events)
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+/// how much brighter highlighted sprites are drawn unless `HighlightSettings` or the `Highlighted` component say otherwise
+pub const DEFAULT_HIGHLIGHT_COLOR: Color = Color::rgb(1.6, 1.6, 1.3);
+
+/// **INFO** mechanics plugin that highlights any `Sprite` or `TextureAtlasSprite` by tinting it
+///
+/// This replaces having a second `*_highlighted` sprite sheet for everything that can be highlighted. The color the sprite had before it was
+/// highlighted is put back when the `Highlighted` component is removed
+///
+/// # Example
+/// ```rust
+/// // uses the color from `HighlightSettings`
+/// commands.entity(entity).insert(Highlighted::default());
+/// // a red tint for just this entity
+/// commands.entity(entity).insert(Highlighted { color: Some(Color::rgb(1.5, 0.6, 0.6)) });
+/// // back to normal
+/// commands.entity(entity).remove::<Highlighted>();
+/// ```
+///
+/// **NOTE** the tint multiplies the sprite color so channels above 1 brighten the sprite and channels below 1 darken it
+pub struct HighlightMechanicsPlugin;
+
+impl Plugin for HighlightMechanicsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(HighlightSettings::default())
+            .add_system(apply_highlights)
+            // highlights are removed with commands during the update stage, the removals only show up after it
+            .add_system_to_stage(CoreStage::PostUpdate, remove_highlights)
+        ;
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct HighlightSettings {
+    pub color: Color
+}
+
+impl Default for HighlightSettings {
+    fn default() -> Self {
+        Self {
+            color: DEFAULT_HIGHLIGHT_COLOR
+        }
+    }
+}
+
+#[derive(Component, Debug, Default, Clone)]
+pub struct Highlighted {
+    /// overrides `HighlightSettings::color` for this entity
+    pub color: Option<Color>
+}
+
+/// the color the sprite had before it was highlighted
+#[derive(Component, Debug, Clone)]
+pub struct UnhighlightedColor(pub Color);
+
+pub fn apply_highlights(
+    mut commands: Commands,
+    settings: Res<HighlightSettings>,
+    mut atlas_sprite_query: Query<(Entity, &Highlighted, ChangeTrackers<Highlighted>, &mut TextureAtlasSprite, Option<&UnhighlightedColor>), Without<Sprite>>,
+    mut sprite_query: Query<(Entity, &Highlighted, ChangeTrackers<Highlighted>, &mut Sprite, Option<&UnhighlightedColor>), Without<TextureAtlasSprite>>
+) {
+    for (entity, highlighted, tracker, mut sprite, unhighlighted) in atlas_sprite_query.iter_mut() {
+        if !tracker.is_changed() && !settings.is_changed() {
+            continue;
+        }
+        if unhighlighted.is_none() {
+            commands.entity(entity).insert(UnhighlightedColor(sprite.color));
+        }
+        sprite.color = highlighted.color.unwrap_or(settings.color);
+    }
+    for (entity, highlighted, tracker, mut sprite, unhighlighted) in sprite_query.iter_mut() {
+        if !tracker.is_changed() && !settings.is_changed() {
+            continue;
+        }
+        if unhighlighted.is_none() {
+            commands.entity(entity).insert(UnhighlightedColor(sprite.color));
+        }
+        sprite.color = highlighted.color.unwrap_or(settings.color);
+    }
+}
+
+pub fn remove_highlights(
+    mut commands: Commands,
+    removed: RemovedComponents<Highlighted>,
+    mut atlas_sprite_query: Query<(&mut TextureAtlasSprite, &UnhighlightedColor), (Without<Highlighted>, Without<Sprite>)>,
+    mut sprite_query: Query<(&mut Sprite, &UnhighlightedColor), (Without<Highlighted>, Without<TextureAtlasSprite>)>
+) {
+    for entity in removed.iter() {
+        if let Ok((mut sprite, unhighlighted)) = atlas_sprite_query.get_mut(entity) {
+            sprite.color = unhighlighted.0;
+        }
+        else if let Ok((mut sprite, unhighlighted)) = sprite_query.get_mut(entity) {
+            sprite.color = unhighlighted.0;
+        }
+        else {
+            continue;
+        }
+        commands.entity(entity).remove::<UnhighlightedColor>();
+    }
+}
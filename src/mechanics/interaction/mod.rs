@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::{GameState, player::{Player, PlayerLargeCollider}, input::*, mechanics::highlight::Highlighted};
+
+/// **INFO** mechanics plugin for anything the player can interact with
+///
+/// Every frame the interactable the `PlayerLargeCollider` overlaps with the highest priority is focused, ties go to whatever is in the direction
+/// the player is facing and then to whatever is closest. The focused interactable gets a `Highlighted` tint and its prompt is shown at the bottom
+/// of the screen. Pressing the interact action sends an `InteractionEvent` with the interactables handler
+///
+/// **NOTE** the sensor overlapping the `PlayerLargeCollider` can either be on the interactable itself or on one of its children
+///
+/// # Example
+/// ```rust
+/// commands.spawn(SpriteSheetBundle {
+///     /* your sprite */
+/// })
+///     .insert(Interactable::new("Open", 0, InteractionHandler::Gate))
+/// ;
+///
+/// fn open_gates(mut interaction_events: EventReader<InteractionEvent>) {
+///     for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Gate) {
+///         /* open the gate on event.entity */
+///     }
+/// }
+/// ```
+pub struct InteractionMechanicsPlugin;
+
+impl Plugin for InteractionMechanicsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<InteractionEvent>()
+            .insert_resource(FocusedInteractable::default())
+            .add_system_set(SystemSet::on_enter(GameState::LoadingGame)
+                .with_system(spawn_interaction_prompt)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(focus_interactable.label("focus_interactable"))
+                .with_system(send_interaction_events.after("focus_interactable"))
+                .with_system(update_interaction_prompt.after("focus_interactable"))
+            )
+        ;
+    }
+}
+
+/// which system handles an interaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionHandler {
+    Harvest,
+    Gate,
+    Sleep,
+    ShippingBin,
+    Shop,
+    Talk,
+    Animal,
+    PickUp,
+    Chest
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Interactable {
+    /// shown next to the interact binding i.e `[Space] Harvest`
+    pub prompt: String,
+    /// higher priorities win when the player is next to more than one interactable
+    pub priority: i32,
+    pub handler: InteractionHandler,
+    /// interactables that are busy i.e a gate in the middle of its animation can't be focused
+    pub enabled: bool
+}
+
+impl Interactable {
+    pub fn new(prompt: &str, priority: i32, handler: InteractionHandler) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            priority,
+            handler,
+            enabled: true
+        }
+    }
+}
+
+/// sent when the player presses the interact action while an interactable is focused
+#[derive(Debug, Clone, Copy)]
+pub struct InteractionEvent {
+    pub entity: Entity,
+    pub handler: InteractionHandler
+}
+
+#[derive(Debug, Resource, Default)]
+pub struct FocusedInteractable(pub Option<Entity>);
+
+/// the direction the player last moved in, it sticks around after the player stops
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Facing(pub Vec2);
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self(Vec2::NEG_Y)
+    }
+}
+
+#[derive(Component)]
+pub struct InteractionPrompt;
+
+pub fn focus_interactable(
+    mut commands: Commands,
+    player_query: Query<(&GlobalTransform, &Facing), With<Player>>,
+    player_collider_query: Query<Entity, With<PlayerLargeCollider>>,
+    interactable_query: Query<(Entity, &Interactable, &GlobalTransform, Option<&Children>)>,
+    context: Res<RapierContext>,
+    mut focused: ResMut<FocusedInteractable>
+) {
+    // the player entity might not be loaded in yet
+    let ((player_transform, facing), player_collider) = match (player_query.get_single(), player_collider_query.get_single()) {
+        (Ok(player), Ok(player_collider)) => (player, player_collider),
+        _ => return
+    };
+    let player_position = player_transform.translation().truncate();
+
+    let mut best: Option<(Entity, (i32, bool, f32))> = None;
+
+    for (entity, interactable, transform, children) in interactable_query.iter() {
+        if !interactable.enabled {
+            continue;
+        }
+        let overlapping = std::iter::once(entity)
+            .chain(children.into_iter().flat_map(|children| children.iter().copied()))
+            .any(|collider| context.intersection_pair(player_collider, collider) == Some(true));
+        if !overlapping {
+            continue;
+        }
+
+        let offset = transform.translation().truncate() - player_position;
+        let in_front = offset.dot(facing.0) >= 0.;
+        let distance = offset.length();
+        let score = (interactable.priority, in_front, distance);
+
+        let better = match best {
+            Some((_, (priority, best_in_front, best_distance))) => {
+                (score.0, score.1) > (priority, best_in_front) || ((score.0, score.1) == (priority, best_in_front) && distance < best_distance)
+            },
+            None => true
+        };
+        if better {
+            best = Some((entity, score));
+        }
+    }
+
+    let next = best.map(|(entity, _)| entity);
+    if next == focused.0 {
+        return;
+    }
+    if let Some(previous) = focused.0 {
+        // the previous interactable might have been despawned i.e a harvested crop
+        if interactable_query.get(previous).is_ok() {
+            commands.entity(previous).remove::<Highlighted>();
+        }
+    }
+    if let Some(next) = next {
+        commands.entity(next).insert(Highlighted::default());
+    }
+    focused.0 = next;
+}
+
+pub fn send_interaction_events(
+    action_input: Res<ActionInput>,
+    focused: Res<FocusedInteractable>,
+    interactable_query: Query<&Interactable>,
+    mut interaction_events: EventWriter<InteractionEvent>
+) {
+    if !action_input.just_pressed(Action::Interact) {
+        return;
+    }
+    let entity = match focused.0 {
+        Some(entity) => entity,
+        None => return
+    };
+    if let Ok(interactable) = interactable_query.get(entity) {
+        interaction_events.send(InteractionEvent {
+            entity,
+            handler: interactable.handler
+        });
+    }
+}
+
+pub fn spawn_interaction_prompt(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>
+) {
+    commands
+        .spawn(TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 25.,
+                color: Color::WHITE
+            }
+        ).with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Percent(45.), Val::Auto, Val::Auto, Val::Percent(8.)),
+            ..default()
+        }))
+        .insert(InteractionPrompt)
+    ;
+}
+
+pub fn update_interaction_prompt(
+    focused: Res<FocusedInteractable>,
+    input_settings: Res<InputSettings>,
+    interactable_query: Query<&Interactable>,
+    mut prompt_query: Query<&mut Text, With<InteractionPrompt>>
+) {
+    let mut text = match prompt_query.get_single_mut() {
+        Ok(text) => text,
+        Err(_) => return
+    };
+    let prompt = focused.0
+        .and_then(|entity| interactable_query.get(entity).ok())
+        .map(|interactable| format!("[{}] {}", input_settings.binding(Action::Interact).describe(), interactable.prompt))
+        .unwrap_or_default();
+    if text.sections[0].value != prompt {
+        text.sections[0].value = prompt;
+    }
+}
@@ -1 +1,3 @@
-pub mod perspective;
\ No newline at end of file
+pub mod perspective;
+pub mod interaction;
+pub mod highlight;
\ No newline at end of file
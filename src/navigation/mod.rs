@@ -0,0 +1,531 @@
+use std::{collections::{BinaryHeap, HashMap}, cmp::Reverse};
+
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_ecs_ldtk::ldtk::LayerInstance;
+
+use self::systems::*;
+
+pub mod systems;
+
+/// the cost of stepping onto a path, the cheapest kind of cell. The A* heuristic assumes every remaining step costs this much
+pub const MIN_STEP_COST: u32 = 10;
+/// stepping onto grass costs a bit more than a path so anything walking sticks to paths when it can
+pub const GRASS_STEP_COST: u32 = 14;
+/// empty crop fields are only crossed when there is no other way around
+pub const CROP_FIELD_STEP_COST: u32 = 60;
+/// searches give up after visiting this many cells so an unreachable goal in a large level can't stall a frame
+pub const MAX_SEARCH_CELLS: usize = 20_000;
+pub const COLLISIONS_LAYER: &str = "Collisions";
+pub const PATHS_LAYER: &str = "Paths";
+
+const NEIGHBOURS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// **INFO** plugin for the navigation grid of the current level
+///
+/// The `NavGrid` is built from the `Collisions` and `Paths` int grid layers every time a level finishes spawning. After that it is kept up
+/// to date as soil is tilled, crops are planted or harvested, gates open and close and anything with a fixed collider is added or removed,
+/// so `NavGrid::find_path` can be called every frame without touching the ECS
+///
+/// # Example
+/// ```rust
+/// fn walk_somewhere(nav_grid: Res<NavGrid>) {
+///     let path = nav_grid.find_path(start, goal, NavAgent::PERSON);
+/// }
+/// ```
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(NavGrid::default())
+            .add_system(rebuild_nav_grid)
+            // crops and objects are despawned with commands during the update stage, the removals only show up after it
+            .add_system_to_stage(CoreStage::PostUpdate, update_nav_grid.after(TransformSystem::TransformPropagate))
+        ;
+    }
+}
+
+/// what a cell of the level is from its int grid layers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Terrain {
+    #[default]
+    Grass,
+    Path,
+    CropField,
+    Fence,
+    Gate
+}
+
+/// what is allowed to walk where
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavAgent {
+    /// closed gates are walked through instead of around
+    pub opens_gates: bool,
+    /// planted crops are walked around so they don't get trampled
+    pub avoids_crops: bool,
+    /// gates are never walked through even when they are open, which keeps anything penned in inside its fences
+    pub avoids_gates: bool
+}
+
+impl NavAgent {
+    /// the player and villagers
+    pub const PERSON: Self = Self {
+        opens_gates: true,
+        avoids_crops: true,
+        avoids_gates: false
+    };
+
+    /// farm animals
+    pub const ANIMAL: Self = Self {
+        opens_gates: false,
+        avoids_crops: true,
+        avoids_gates: true
+    };
+}
+
+/// The walkable cells of the current level in world grid cells
+#[derive(Debug, Resource, Default)]
+pub struct NavGrid {
+    /// identifier of the level the grid was built from
+    pub level: String,
+    pub grid_size: i32,
+    /// the bottom left cell of the level
+    pub origin: IVec2,
+    /// the size of the level in cells
+    pub size: IVec2,
+    /// bumped every time a cell changes so paths found before can be found again
+    pub revision: u32,
+    terrain: Vec<Terrain>,
+    tilled: Overlay,
+    crops: Overlay,
+    open_gates: Overlay,
+    obstacles: Overlay
+}
+
+/// the cells covered by entities on top of the terrain, kept by entity so they can be taken off again
+#[derive(Debug, Default)]
+struct Overlay {
+    entities: HashMap<Entity, Vec<IVec2>>,
+    /// how many entities cover each cell
+    cells: HashMap<IVec2, u32>
+}
+
+impl Overlay {
+    /// returns whether anything changed
+    fn set(&mut self, entity: Entity, cells: Option<Vec<IVec2>>) -> bool {
+        if self.entities.get(&entity) == cells.as_ref() {
+            return false;
+        }
+        for cell in self.entities.remove(&entity).unwrap_or_default() {
+            if let Some(count) = self.cells.get_mut(&cell) {
+                *count -= 1;
+                if *count == 0 {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+        if let Some(cells) = cells {
+            for cell in cells.iter() {
+                *self.cells.entry(*cell).or_insert(0) += 1;
+            }
+            self.entities.insert(entity, cells);
+        }
+        true
+    }
+
+    fn contains(&self, cell: IVec2) -> bool {
+        self.cells.contains_key(&cell)
+    }
+}
+
+impl NavGrid {
+    /// a grid for `level_identifier` from its layers, `origin` is the cell the bottom left of the level is in
+    pub fn from_layers(level_identifier: &str, layer_instances: &[LayerInstance], origin: IVec2) -> Self {
+        let mut grid = Self {
+            level: level_identifier.to_string(),
+            origin,
+            ..Default::default()
+        };
+        let layers = layer_instances.iter().filter(|layer| layer.identifier == PATHS_LAYER || layer.identifier == COLLISIONS_LAYER);
+        for layer in layers {
+            if grid.terrain.is_empty() {
+                grid.grid_size = layer.grid_size;
+                grid.size = IVec2::new(layer.c_wid, layer.c_hei);
+                grid.terrain = vec![Terrain::Grass; (layer.c_wid * layer.c_hei) as usize];
+            }
+            for (index, value) in layer.int_grid_csv.iter().enumerate() {
+                let index = index as i32;
+                // LDtk counts rows from the top of the level
+                let cell = origin + IVec2::new(index % layer.c_wid, layer.c_hei - 1 - index / layer.c_wid);
+                let terrain = match (layer.identifier.as_str(), value) {
+                    (PATHS_LAYER, 1) | (PATHS_LAYER, 2) => Terrain::Path,
+                    (PATHS_LAYER, 3) => Terrain::CropField,
+                    (COLLISIONS_LAYER, 1) => Terrain::Fence,
+                    (COLLISIONS_LAYER, 2) => Terrain::Gate,
+                    _ => continue
+                };
+                grid.set_terrain(cell, terrain);
+            }
+        }
+        grid
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        let local = cell - self.origin;
+        if local.cmplt(IVec2::ZERO).any() || local.cmpge(self.size).any() {
+            return None;
+        }
+        Some((local.y * self.size.x + local.x) as usize)
+    }
+
+    /// fences and gates are on a different layer than paths so they always win
+    fn set_terrain(&mut self, cell: IVec2, terrain: Terrain) {
+        if let Some(index) = self.index(cell) {
+            if matches!(self.terrain[index], Terrain::Fence | Terrain::Gate) && !matches!(terrain, Terrain::Fence | Terrain::Gate) {
+                return;
+            }
+            self.terrain[index] = terrain;
+        }
+    }
+
+    pub fn terrain(&self, cell: IVec2) -> Option<Terrain> {
+        self.index(cell).map(|index| self.terrain[index])
+    }
+
+    /// the cell containing a world position
+    pub fn cell(&self, translation: Vec3) -> IVec2 {
+        (translation.truncate() / self.grid_size as f32).floor().as_ivec2()
+    }
+
+    /// the world position of the center of a cell
+    pub fn cell_center(&self, cell: IVec2) -> Vec2 {
+        (cell.as_vec2() + 0.5) * self.grid_size as f32
+    }
+
+    /// every cell a rectangle centered on `center` overlaps
+    pub fn cells_in(&self, center: Vec2, half_extents: Vec2) -> Vec<IVec2> {
+        let grid_size = self.grid_size as f32;
+        let min = ((center - half_extents) / grid_size).floor().as_ivec2();
+        // a rectangle that ends exactly on a cell edge doesn't overlap the next cell
+        let max = ((center + half_extents) / grid_size).ceil().as_ivec2() - IVec2::ONE;
+        let mut cells = Vec::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                cells.push(IVec2::new(x, y));
+            }
+        }
+        cells
+    }
+
+    /// the cost of stepping onto a cell or `None` if the agent can't walk on it
+    pub fn cost(&self, cell: IVec2, agent: NavAgent) -> Option<u32> {
+        let terrain = self.terrain(cell)?;
+        if self.obstacles.contains(cell) || (agent.avoids_crops && self.crops.contains(cell)) {
+            return None;
+        }
+        match terrain {
+            Terrain::Fence => None,
+            Terrain::Gate if agent.avoids_gates => None,
+            Terrain::Gate if !agent.opens_gates && !self.open_gates.contains(cell) => None,
+            Terrain::Gate | Terrain::Path => Some(MIN_STEP_COST),
+            Terrain::CropField => Some(CROP_FIELD_STEP_COST),
+            Terrain::Grass if self.tilled.contains(cell) => Some(CROP_FIELD_STEP_COST),
+            Terrain::Grass => Some(GRASS_STEP_COST)
+        }
+    }
+
+    pub fn is_walkable(&self, cell: IVec2, agent: NavAgent) -> bool {
+        self.cost(cell, agent).is_some()
+    }
+
+    /// whether an object can be put down on the cell, it has to be grass with nothing on top of it so paths stay clear
+    pub fn is_free(&self, cell: IVec2) -> bool {
+        self.terrain(cell) == Some(Terrain::Grass)
+            && !self.tilled.contains(cell)
+            && !self.crops.contains(cell)
+            && !self.obstacles.contains(cell)
+    }
+
+    /// the cheapest path from `start` to `goal` for the agent, see `find_path`
+    pub fn find_path(&self, start: IVec2, goal: IVec2, agent: NavAgent) -> Option<Vec<IVec2>> {
+        find_path(start, goal, |cell| self.cost(cell, agent))
+    }
+
+    /// `cell` is `None` once the soil is back to grass
+    pub fn set_tilled(&mut self, entity: Entity, cell: Option<IVec2>) {
+        let changed = self.tilled.set(entity, cell.map(|cell| vec![cell]));
+        self.bump(changed);
+    }
+
+    /// `cell` is `None` once the crop is gone
+    pub fn set_crop(&mut self, entity: Entity, cell: Option<IVec2>) {
+        let changed = self.crops.set(entity, cell.map(|cell| vec![cell]));
+        self.bump(changed);
+    }
+
+    /// `cells` is `None` once the gate is closed
+    pub fn set_gate(&mut self, entity: Entity, cells: Option<Vec<IVec2>>) {
+        let changed = self.open_gates.set(entity, cells);
+        self.bump(changed);
+    }
+
+    /// `cells` is `None` once the obstacle is gone
+    pub fn set_obstacle(&mut self, entity: Entity, cells: Option<Vec<IVec2>>) {
+        let changed = self.obstacles.set(entity, cells);
+        self.bump(changed);
+    }
+
+    fn bump(&mut self, changed: bool) {
+        if changed {
+            self.revision = self.revision.wrapping_add(1);
+        }
+    }
+}
+
+/// A* over grid cells from `start` to `goal` moving in the four directions the character sprite sheets have
+///
+/// `cost` gives the cost of stepping onto a cell or `None` if it can't be walked on, costs should never be lower than `MIN_STEP_COST`.
+/// The path doesn't include `start` and ends on `goal`, it is `None` if the goal can't be reached
+pub fn find_path(start: IVec2, goal: IVec2, cost: impl Fn(IVec2) -> Option<u32>) -> Option<Vec<IVec2>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+    cost(goal)?;
+
+    let heuristic = |cell: IVec2| {
+        let diff = (goal - cell).abs();
+        (diff.x + diff.y) as u32 * MIN_STEP_COST
+    };
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+    let mut best_cost: HashMap<IVec2, u32> = HashMap::new();
+
+    best_cost.insert(start, 0);
+    open.push(Reverse((heuristic(start), 0, start.x, start.y)));
+
+    while let Some(Reverse((_, cell_cost, x, y))) = open.pop() {
+        let cell = IVec2::new(x, y);
+        if cell == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current) {
+                if *previous == start {
+                    break;
+                }
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        // a cheaper way here was already expanded
+        if best_cost.get(&cell).map_or(false, |best| *best < cell_cost) {
+            continue;
+        }
+        if best_cost.len() > MAX_SEARCH_CELLS {
+            return None;
+        }
+
+        for offset in NEIGHBOURS {
+            let next = cell + offset;
+            let step = match cost(next) {
+                Some(step) => step,
+                None => continue
+            };
+            let next_cost = cell_cost + step;
+            if best_cost.get(&next).map_or(true, |best| next_cost < *best) {
+                best_cost.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(Reverse((next_cost + heuristic(next), next_cost, next.x, next.y)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an int grid layer from rows written top to bottom like LDtk stores them
+    fn layer(identifier: &str, rows: &[&str], value: impl Fn(char) -> i32) -> LayerInstance {
+        LayerInstance {
+            identifier: identifier.to_string(),
+            c_wid: rows[0].len() as i32,
+            c_hei: rows.len() as i32,
+            grid_size: 16,
+            int_grid_csv: rows.iter().flat_map(|row| row.chars().map(&value)).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// `.` is grass, `=` a path, `p` a crop field, `#` a fence and `g` a gate
+    fn grid(rows: &[&str]) -> NavGrid {
+        let collisions = layer(COLLISIONS_LAYER, rows, |c| match c { '#' => 1, 'g' => 2, _ => 0 });
+        let paths = layer(PATHS_LAYER, rows, |c| match c { '=' => 1, 'p' => 3, _ => 0 });
+        NavGrid::from_layers("Test", &[collisions, paths], IVec2::ZERO)
+    }
+
+    /// every step of `path` goes to a neighbouring walkable cell
+    fn assert_walkable(nav_grid: &NavGrid, start: IVec2, path: &[IVec2], agent: NavAgent) {
+        let mut previous = start;
+        for cell in path {
+            let step = (*cell - previous).abs();
+            assert_eq!(step.x + step.y, 1, "{} doesn't follow {}", cell, previous);
+            assert!(nav_grid.is_walkable(*cell, agent), "{} isn't walkable", cell);
+            previous = *cell;
+        }
+    }
+
+    #[test]
+    fn from_layers_flips_rows_and_keeps_fences_over_paths() {
+        let collisions = layer(COLLISIONS_LAYER, &["#..", "..g"], |c| match c { '#' => 1, 'g' => 2, _ => 0 });
+        let paths = layer(PATHS_LAYER, &["===", "p.."], |c| match c { '=' => 1, 'p' => 3, _ => 0 });
+        let origin = IVec2::new(10, 20);
+        let nav_grid = NavGrid::from_layers("Test", &[collisions, paths], origin);
+
+        assert_eq!(nav_grid.size, IVec2::new(3, 2));
+        assert_eq!(nav_grid.grid_size, 16);
+        // the top row of the layers is the highest row of cells
+        assert_eq!(nav_grid.terrain(origin + IVec2::new(0, 1)), Some(Terrain::Fence));
+        assert_eq!(nav_grid.terrain(origin + IVec2::new(1, 1)), Some(Terrain::Path));
+        assert_eq!(nav_grid.terrain(origin + IVec2::new(0, 0)), Some(Terrain::CropField));
+        assert_eq!(nav_grid.terrain(origin + IVec2::new(1, 0)), Some(Terrain::Grass));
+        assert_eq!(nav_grid.terrain(origin + IVec2::new(2, 0)), Some(Terrain::Gate));
+        assert_eq!(nav_grid.terrain(origin + IVec2::new(3, 0)), None);
+        assert_eq!(nav_grid.terrain(IVec2::ZERO), None);
+    }
+
+    #[test]
+    fn cost_depends_on_terrain_and_overlays() {
+        let mut nav_grid = grid(&["=.p#g"]);
+        let agent = NavAgent::PERSON;
+        assert_eq!(nav_grid.cost(IVec2::new(0, 0), agent), Some(MIN_STEP_COST));
+        assert_eq!(nav_grid.cost(IVec2::new(1, 0), agent), Some(GRASS_STEP_COST));
+        assert_eq!(nav_grid.cost(IVec2::new(2, 0), agent), Some(CROP_FIELD_STEP_COST));
+        assert_eq!(nav_grid.cost(IVec2::new(3, 0), agent), None);
+        assert_eq!(nav_grid.cost(IVec2::new(4, 0), agent), Some(MIN_STEP_COST));
+        assert_eq!(nav_grid.cost(IVec2::new(5, 0), agent), None);
+
+        nav_grid.set_tilled(Entity::from_raw(0), Some(IVec2::new(1, 0)));
+        assert_eq!(nav_grid.cost(IVec2::new(1, 0), agent), Some(CROP_FIELD_STEP_COST));
+        nav_grid.set_obstacle(Entity::from_raw(1), Some(vec![IVec2::new(0, 0)]));
+        assert_eq!(nav_grid.cost(IVec2::new(0, 0), agent), None);
+        nav_grid.set_obstacle(Entity::from_raw(1), None);
+        assert_eq!(nav_grid.cost(IVec2::new(0, 0), agent), Some(MIN_STEP_COST));
+    }
+
+    #[test]
+    fn shortest_path_goes_around_a_fence() {
+        let nav_grid = grid(&[
+            ".......",
+            "...#...",
+            "...#...",
+            "...#..."
+        ]);
+        let start = IVec2::new(1, 1);
+        let goal = IVec2::new(5, 1);
+        let path = nav_grid.find_path(start, goal, NavAgent::PERSON).unwrap();
+
+        assert_walkable(&nav_grid, start, &path, NavAgent::PERSON);
+        assert_eq!(path.last(), Some(&goal));
+        // two rows up to get over the fence, four across and two back down
+        assert_eq!(path.len(), 8);
+        assert!(path.contains(&IVec2::new(3, 3)));
+    }
+
+    #[test]
+    fn paths_are_preferred_over_grass_and_crop_fields() {
+        let nav_grid = grid(&[
+            "==========",
+            "..........",
+            "pppppppppp"
+        ]);
+        // the path is a detour of two steps but cheaper than walking straight over the grass
+        let path = nav_grid.find_path(IVec2::new(0, 1), IVec2::new(9, 1), NavAgent::PERSON).unwrap();
+        assert_eq!(path.len(), 11);
+        assert!(path.contains(&IVec2::new(5, 2)));
+
+        // only the goal is a crop field, the rest of the way goes around them
+        let goal = IVec2::new(9, 0);
+        let path = nav_grid.find_path(IVec2::new(0, 0), goal, NavAgent::PERSON).unwrap();
+        assert!(path.iter().all(|cell| cell.y > 0 || *cell == goal), "{:?} crosses the crop fields", path);
+    }
+
+    #[test]
+    fn closed_gates_are_only_walked_through_by_agents_that_open_them() {
+        let mut nav_grid = grid(&[
+            "#####",
+            "#.#.#",
+            "#.g.#",
+            "#.#.#",
+            "#####"
+        ]);
+        let start = IVec2::new(1, 2);
+        let goal = IVec2::new(3, 2);
+        let gate = IVec2::new(2, 2);
+        let closed_gate_agent = NavAgent {
+            opens_gates: false,
+            ..NavAgent::PERSON
+        };
+
+        assert_eq!(nav_grid.find_path(start, goal, closed_gate_agent), None);
+        assert_eq!(nav_grid.find_path(start, goal, NavAgent::PERSON), Some(vec![gate, goal]));
+
+        nav_grid.set_gate(Entity::from_raw(0), Some(vec![gate]));
+        assert_eq!(nav_grid.find_path(start, goal, closed_gate_agent), Some(vec![gate, goal]));
+        // animals stay in their pen even with the gate open
+        assert_eq!(nav_grid.find_path(start, goal, NavAgent::ANIMAL), None);
+
+        nav_grid.set_gate(Entity::from_raw(0), None);
+        assert_eq!(nav_grid.find_path(start, goal, closed_gate_agent), None);
+    }
+
+    #[test]
+    fn crops_are_walked_around() {
+        let mut nav_grid = grid(&[
+            ".....",
+            ".....",
+            "....."
+        ]);
+        let start = IVec2::new(0, 1);
+        let goal = IVec2::new(4, 1);
+        let crop = IVec2::new(2, 1);
+        nav_grid.set_crop(Entity::from_raw(0), Some(crop));
+
+        let path = nav_grid.find_path(start, goal, NavAgent::PERSON).unwrap();
+        assert_walkable(&nav_grid, start, &path, NavAgent::PERSON);
+        assert!(!path.contains(&crop));
+        assert_eq!(path.len(), 6);
+
+        let tramples = NavAgent {
+            avoids_crops: false,
+            ..NavAgent::PERSON
+        };
+        assert_eq!(nav_grid.find_path(start, goal, tramples).map(|path| path.len()), Some(4));
+        // a crop on the goal can't be reached at all
+        assert_eq!(nav_grid.find_path(start, crop, NavAgent::PERSON), None);
+    }
+
+    #[test]
+    fn gives_up_on_unreachable_goals() {
+        let goal = IVec2::new(5, 5);
+        // the goal is walled in on an endless grass plain so the search would never run out of cells on its own
+        let path = find_path(IVec2::ZERO, goal, |cell| {
+            let offset = (cell - goal).abs();
+            if offset.max_element() == 1 {
+                None
+            }
+            else {
+                Some(MIN_STEP_COST)
+            }
+        });
+        assert_eq!(path, None);
+    }
+
+    #[test]
+    fn start_is_the_goal() {
+        assert_eq!(find_path(IVec2::ONE, IVec2::ONE, |_| None), Some(Vec::new()));
+    }
+}
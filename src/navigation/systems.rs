@@ -0,0 +1,128 @@
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::{gate::Gate, crop::{Crop, TilledSoil}, fence::MergedFenceCollider, level::{CurrentLevel, LevelEntity, LevelBoundary}};
+
+use super::*;
+
+/// rebuilds the grid from the int grid layers every time a level finishes spawning, this includes level transitions and hot reloads
+pub fn rebuild_nav_grid(
+    mut level_events: EventReader<LevelEvent>,
+    level_query: Query<(&Handle<LdtkLevel>, &GlobalTransform)>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+    current_level: Res<CurrentLevel>,
+    tilled_query: Query<(Entity, &Transform, &LevelEntity), With<TilledSoil>>,
+    crop_query: Query<(Entity, &Crop, &Transform, &LevelEntity)>,
+    gate_query: Query<(Entity, &Gate, &Collider, &GlobalTransform)>,
+    obstacle_query: Query<(Entity, &Collider, &RigidBody, &GlobalTransform), (Without<Sensor>, Without<MergedFenceCollider>, Without<LevelBoundary>)>,
+    mut nav_grid: ResMut<NavGrid>
+) {
+    // wait for the global transforms of the new level
+    if !level_events.iter().any(|event| matches!(event, LevelEvent::Transformed(_))) {
+        return;
+    }
+
+    for (level_handle, level_transform) in level_query.iter() {
+        let ldtk_level = match ldtk_levels.get(level_handle) {
+            Some(ldtk_level) => ldtk_level,
+            None => continue
+        };
+        if ldtk_level.level.iid != current_level.0.iid {
+            continue;
+        }
+        let layer_instances = match ldtk_level.level.layer_instances.as_ref() {
+            Some(layer_instances) => layer_instances,
+            None => continue
+        };
+
+        let origin = (level_transform.translation().truncate() / current_level.grid_size() as f32).floor().as_ivec2();
+        let revision = nav_grid.revision;
+        *nav_grid = NavGrid::from_layers(current_level.identifier(), layer_instances, origin);
+        // anything holding on to a path from the old level has to find a new one
+        nav_grid.revision = revision.wrapping_add(1);
+
+        for (entity, transform, level_entity) in tilled_query.iter() {
+            if level_entity.0 == nav_grid.level {
+                let cell = nav_grid.cell(transform.translation);
+                nav_grid.set_tilled(entity, Some(cell));
+            }
+        }
+        for (entity, crop, transform, level_entity) in crop_query.iter() {
+            if level_entity.0 == nav_grid.level {
+                let cell = crop.cell(transform.translation, nav_grid.grid_size);
+                nav_grid.set_crop(entity, Some(cell));
+            }
+        }
+        for (entity, gate, collider, transform) in gate_query.iter() {
+            let cells = gate.open.then(|| collider_cells(&nav_grid, collider, transform)).flatten();
+            nav_grid.set_gate(entity, cells);
+        }
+        for (entity, collider, rigid_body, transform) in obstacle_query.iter() {
+            if *rigid_body == RigidBody::Fixed {
+                let cells = collider_cells(&nav_grid, collider, transform);
+                nav_grid.set_obstacle(entity, cells);
+            }
+        }
+        log::info!("built a {}x{} navigation grid for {}", nav_grid.size.x, nav_grid.size.y, nav_grid.level);
+    }
+}
+
+/// keeps the grid up to date as soil is tilled, crops come and go, gates open and close and obstacles are placed or broken
+pub fn update_nav_grid(
+    tilled_query: Query<(Entity, &Transform, &LevelEntity), Added<TilledSoil>>,
+    crop_query: Query<(Entity, &Crop, &Transform, &LevelEntity), Added<Crop>>,
+    gate_query: Query<(Entity, &Gate, &Collider, &GlobalTransform), Changed<Gate>>,
+    // obstacles spawned this frame don't have a global transform yet so this waits for it to be propagated
+    obstacle_query: Query<(Entity, &Collider, &RigidBody, &GlobalTransform), (Changed<GlobalTransform>, Without<Sensor>, Without<MergedFenceCollider>, Without<LevelBoundary>)>,
+    removed_tilled: RemovedComponents<TilledSoil>,
+    removed_crops: RemovedComponents<Crop>,
+    removed_colliders: RemovedComponents<Collider>,
+    mut nav_grid: ResMut<NavGrid>
+) {
+    // nothing to update before the first level has spawned
+    if nav_grid.grid_size == 0 {
+        return;
+    }
+
+    for (entity, transform, level_entity) in tilled_query.iter() {
+        if level_entity.0 == nav_grid.level {
+            let cell = nav_grid.cell(transform.translation);
+            nav_grid.set_tilled(entity, Some(cell));
+        }
+    }
+    for entity in removed_tilled.iter() {
+        nav_grid.set_tilled(entity, None);
+    }
+
+    for (entity, crop, transform, level_entity) in crop_query.iter() {
+        if level_entity.0 == nav_grid.level {
+            let cell = crop.cell(transform.translation, nav_grid.grid_size);
+            nav_grid.set_crop(entity, Some(cell));
+        }
+    }
+    for entity in removed_crops.iter() {
+        nav_grid.set_crop(entity, None);
+    }
+
+    for (entity, gate, collider, transform) in gate_query.iter() {
+        let cells = gate.open.then(|| collider_cells(&nav_grid, collider, transform)).flatten();
+        nav_grid.set_gate(entity, cells);
+    }
+
+    for (entity, collider, rigid_body, transform) in obstacle_query.iter() {
+        if *rigid_body == RigidBody::Fixed {
+            let cells = collider_cells(&nav_grid, collider, transform);
+            nav_grid.set_obstacle(entity, cells);
+        }
+    }
+    for entity in removed_colliders.iter() {
+        nav_grid.set_obstacle(entity, None);
+        nav_grid.set_gate(entity, None);
+    }
+}
+
+/// the cells a box collider covers, other shapes aren't used for anything that blocks the way
+fn collider_cells(nav_grid: &NavGrid, collider: &Collider, transform: &GlobalTransform) -> Option<Vec<IVec2>> {
+    let cuboid = collider.as_cuboid()?;
+    Some(nav_grid.cells_in(transform.translation().truncate(), cuboid.half_extents()))
+}
@@ -0,0 +1,186 @@
+use std::{collections::{HashMap, HashSet}, fs::read_to_string};
+
+use bevy::{prelude::*, log};
+use serde::{Serialize, Deserialize};
+use crate::item::Item;
+
+pub const FRIENDSHIP_PATH: &str = "assets/data/friendship.rson";
+
+/// how the villager feels about an item given to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GiftTaste {
+    Liked,
+    Neutral,
+    Disliked
+}
+
+impl GiftTaste {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GiftTaste::Liked => "liked",
+            GiftTaste::Neutral => "didn't mind",
+            GiftTaste::Disliked => "disliked"
+        }
+    }
+}
+
+/// something a villager shares with the player once they like them enough
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FriendshipUnlock {
+    /// learnt by the player and listed on the relationships page
+    Recipe(String),
+    /// set in the `DialogueFlags` so dialogue can check for it with `Condition::Flag`
+    Flag(String)
+}
+
+/// what a single villager likes, read from `assets/data/friendship.rson`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VillagerTastes {
+    /// produce matches any quality, better quality produce is worth more
+    #[serde(default)]
+    pub liked: Vec<Item>,
+    #[serde(default)]
+    pub disliked: Vec<Item>,
+    /// unlocked the first time the relationship reaches the points, they stay unlocked if the points drop again
+    #[serde(default)]
+    pub unlocks: Vec<(i32, FriendshipUnlock)>
+}
+
+impl VillagerTastes {
+    pub fn taste(&self, item: &Item) -> GiftTaste {
+        let item = item.normal_quality();
+        if self.liked.iter().any(|liked| liked.normal_quality() == item) {
+            GiftTaste::Liked
+        }
+        else if self.disliked.iter().any(|disliked| disliked.normal_quality() == item) {
+            GiftTaste::Disliked
+        }
+        else {
+            GiftTaste::Neutral
+        }
+    }
+}
+
+/// user config for how relationships change, read from `assets/data/friendship.rson`
+#[derive(Debug, Resource, Clone, Serialize, Deserialize)]
+pub struct FriendshipConfig {
+    /// earned the first time the player talks to a villager each day
+    pub talk_points: i32,
+    pub liked_gift_points: i32,
+    pub neutral_gift_points: i32,
+    /// usually negative
+    pub disliked_gift_points: i32,
+    /// how many days a villager can go without being talked to before the relationship starts to fade
+    pub decay_after_days: u32,
+    /// lost every day after `decay_after_days`
+    pub decay_points: i32,
+    pub max_points: i32,
+    /// the points shown as one heart on the relationships page
+    pub points_per_heart: i32,
+    /// by villager name, villagers that aren't listed don't mind any gift
+    #[serde(default)]
+    pub villagers: HashMap<String, VillagerTastes>
+}
+
+impl Default for FriendshipConfig {
+    fn default() -> Self {
+        Self {
+            talk_points: 10,
+            liked_gift_points: 45,
+            neutral_gift_points: 20,
+            disliked_gift_points: -20,
+            decay_after_days: 2,
+            decay_points: 5,
+            max_points: 1000,
+            points_per_heart: 100,
+            villagers: HashMap::new()
+        }
+    }
+}
+
+impl FriendshipConfig {
+    /// falls back to the default config if the file is missing or invalid
+    pub fn read() -> Self {
+        match read_to_string(FRIENDSHIP_PATH) {
+            Ok(config) => ron::from_str::<FriendshipConfig>(&config.as_str()).unwrap_or_else(|err| {
+                log::warn!("invalid friendship config using defaults: {}", err);
+                FriendshipConfig::default()
+            }),
+            Err(_) => FriendshipConfig::default()
+        }
+    }
+
+    pub fn taste(&self, villager: &str, item: &Item) -> GiftTaste {
+        self.villagers.get(villager).map_or(GiftTaste::Neutral, |tastes| tastes.taste(item))
+    }
+
+    /// the points a gift is worth, better quality produce is worth more
+    pub fn gift_points(&self, villager: &str, item: &Item) -> (GiftTaste, i32) {
+        let taste = self.taste(villager, item);
+        let points = match taste {
+            GiftTaste::Liked => self.liked_gift_points,
+            GiftTaste::Neutral => self.neutral_gift_points,
+            GiftTaste::Disliked => return (taste, self.disliked_gift_points)
+        };
+        let multiplier = item.quality().map_or(1., |quality| quality.price_multiplier());
+        (taste, (points as f32 * multiplier).round() as i32)
+    }
+}
+
+/// how well the player knows a single villager
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RelationshipRepr")]
+pub struct Relationship {
+    pub points: i32,
+    /// the `Calendar::total_days` the player last talked to or gave a gift to the villager
+    pub last_seen: u32,
+    pub talked_today: bool,
+    pub gifted_today: bool
+}
+
+impl Relationship {
+    pub fn add_points(&mut self, points: i32, config: &FriendshipConfig) {
+        self.points = (self.points + points).clamp(0, config.max_points);
+    }
+
+    pub fn hearts(&self, config: &FriendshipConfig) -> i32 {
+        self.points / config.points_per_heart.max(1)
+    }
+
+    /// forgets about today and fades the relationship if the villager has been ignored for too long
+    pub fn new_day(&mut self, today: u32, config: &FriendshipConfig) {
+        self.talked_today = false;
+        self.gifted_today = false;
+        if today.saturating_sub(self.last_seen) > config.decay_after_days {
+            self.add_points(-config.decay_points, config);
+        }
+    }
+}
+
+/// How `Relationship` is read from saves
+///
+/// saves from before version 4 only kept the friendship points of each villager
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RelationshipRepr {
+    Points(i32),
+    Full {
+        points: i32,
+        last_seen: u32,
+        talked_today: bool,
+        gifted_today: bool
+    }
+}
+
+impl From<RelationshipRepr> for Relationship {
+    fn from(repr: RelationshipRepr) -> Self {
+        match repr {
+            RelationshipRepr::Points(points) => Relationship { points, ..Default::default() },
+            RelationshipRepr::Full { points, last_seen, talked_today, gifted_today } => Relationship { points, last_seen, talked_today, gifted_today }
+        }
+    }
+}
+
+/// the recipes villagers have shared with the player
+#[derive(Debug, Resource, Default, Clone)]
+pub struct KnownRecipes(pub HashSet<String>);
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use crate::{bevy_animations::*, ldtk::*, GameState, gate::GateOpener, animations::set_villager_animations, dialogue::TALK_PROMPT, mechanics::{perspective::PrimaryPerspectiveBody, interaction::*}};
+
+use self::{systems::*, friendship::*};
+
+pub mod systems;
+pub mod friendship;
+
+// villagers stroll a little slower than the player walks, in pixels per second
+pub const VILLAGER_WALKING_SPEED: f32 = 40.;
+
+/// **INFO** plugin for the villagers
+///
+/// Villagers are LDtk `Villager` entities with a `schedule` of points and the `hours` they should be at each one. Whenever the `Clock`
+/// reaches the next hour in the schedule the villager finds a path there on the `NavGrid` and walks it, keeping to paths,
+/// walking around fences and planted crops and opening any gates on the way the same way the player does
+///
+/// Interacting with a villager starts their dialogue, the `dialogue` field picks which one and defaults to the villagers `name`
+///
+/// Talking to a villager once a day and giving them gifts raises their `Relationship` with the player, ignoring them for a few days lowers
+/// it again. What each villager likes and what they share with the player once they like them enough is read from
+/// `assets/data/friendship.rson`, gifts are given through the inventory after a dialogue with the `OfferGift` effect
+///
+/// **NOTE** villagers use the same sprite sheet and walking animation as the player, they are set up once the villager spawns
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkVillager>("Villager")
+            .insert_resource(Relationships::default())
+            .insert_resource(KnownRecipes::default())
+            .insert_resource(FriendshipConfig::read())
+            .add_event::<GiftEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(set_villager_animations)
+                .with_system(talk_to_villagers)
+                .with_system(fade_relationships)
+                .with_system(plan_villager_routes.before("walk_villagers"))
+                .with_system(walk_villagers.label("walk_villagers"))
+                .with_system(open_gates_for_villagers)
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Game)
+                .with_system(stop_villagers)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Inventory)
+                .with_system(give_gifts)
+            )
+            // relationships change in dialogue and the inventory as well as in the game
+            .add_system(unlock_friendship_rewards)
+        ;
+    }
+}
+
+/// where a villager should be from `hour` until the next stop in their schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleStop {
+    pub hour: u32,
+    /// the grid cell relative to the villagers level
+    pub cell: IVec2
+}
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct Villager {
+    pub name: String,
+    /// the name of the dialogue in the `DialogueLibrary`
+    pub dialogue: String,
+    /// sorted by hour
+    pub schedule: Vec<ScheduleStop>,
+    pub grid_size: i32
+}
+
+impl Villager {
+    /// the stop the villager should be at, before the first stop of the day they stay wherever the last one left them
+    pub fn stop_at(&self, hour: u32) -> Option<&ScheduleStop> {
+        self.schedule.iter()
+            .rev()
+            .find(|stop| stop.hour <= hour)
+            .or(self.schedule.last())
+    }
+}
+
+/// how much each villager likes the player by name, villagers the player hasn't talked to yet aren't in here
+#[derive(Debug, Resource, Default, Clone)]
+pub struct Relationships(pub HashMap<String, Relationship>);
+
+/// gives one of the item in the inventory slot to the villager the `InventoryMode` is gifting to
+#[derive(Debug, Clone, Copy)]
+pub struct GiftEvent(pub usize);
+
+/// the path a villager is walking, in world grid cells
+#[derive(Component, Debug, Clone, Default)]
+pub struct VillagerRoute {
+    pub destination: Option<IVec2>,
+    pub path: VecDeque<IVec2>,
+    /// the `NavGrid` revision the path was found on
+    pub revision: u32
+}
+
+#[derive(Clone, Bundle)]
+pub struct LdtkVillager {
+    #[bundle]
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    pub villager: Villager,
+    pub route: VillagerRoute,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    // kinematic bodies don't touch sensors by default and the gates need to know when a villager is in them
+    pub active_collision_types: ActiveCollisionTypes,
+    entity_instance: EntityInstance,
+    pub direction: AnimationDirection,
+    pub gate_opener: GateOpener,
+    pub interactable: Interactable,
+    pub perpective_body: PrimaryPerspectiveBody,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkVillager {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            layer_instance: &LayerInstance,
+            _: Option<&Handle<Image>>,
+            _: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            _: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        let mut name = entity_instance.identifier.clone();
+        let mut dialogue = None;
+        let mut points = Vec::new();
+        let mut hours = Vec::new();
+
+        for field in entity_instance.field_instances.iter() {
+            match (field.identifier.as_str(), &field.value) {
+                ("name", FieldValue::String(Some(value))) => name = value.clone(),
+                ("dialogue", FieldValue::String(Some(value))) => dialogue = Some(value.clone()),
+                ("schedule", FieldValue::Points(value)) => points = value.clone(),
+                ("hours", FieldValue::Ints(value)) => hours = value.clone(),
+                _ => {}
+            }
+        }
+        if points.len() != hours.len() {
+            log::warn!("{} has {} schedule points but {} hours, the extra ones are ignored", name, points.len(), hours.len());
+        }
+
+        let mut schedule: Vec<ScheduleStop> = points.iter()
+            .zip(hours.iter())
+            .filter_map(|(point, hour)| Some(ScheduleStop {
+                hour: (*hour)?.max(0) as u32,
+                // LDtk counts rows from the top of the level
+                cell: IVec2::new(point.as_ref()?.x, layer_instance.c_hei - 1 - point.as_ref()?.y)
+            }))
+            .collect();
+        schedule.sort_by_key(|stop| stop.hour);
+
+        Self {
+            sprite_sheet_bundle: SpriteSheetBundle::default(),
+            villager: Villager {
+                dialogue: dialogue.unwrap_or_else(|| name.clone()),
+                name,
+                schedule,
+                grid_size: layer_instance.grid_size
+            },
+            route: VillagerRoute::default(),
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(4., 18.),
+                rigid_body: RigidBody::KinematicVelocityBased,
+                velocity: Velocity::zero(),
+                rotation_constraints: LockedAxes::ROTATION_LOCKED,
+                ..Default::default()
+            },
+            active_collision_types: ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+            interactable: Interactable::new(TALK_PROMPT, 2, InteractionHandler::Talk),
+            entity_instance: entity_instance.clone(),
+            direction: AnimationDirection::default(),
+            gate_opener: GateOpener,
+            perpective_body: PrimaryPerspectiveBody,
+            ldtk: Ldtk
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{prelude::*, log};
+use bevy_rapier2d::prelude::*;
+use crate::{GameState, gate::Gate, calendar::{Calendar, Clock, NewDayEvent}, item::{Inventory, InventoryMode}, player::{Player, PLAYER_FOOT_OFFSET, systems::nearest_direction}, dialogue::DialogueFlags, navigation::*};
+
+use super::*;
+
+/// finds a new path whenever the schedule moves a villager on to their next stop or the navigation grid changes under them
+pub fn plan_villager_routes(
+    mut villager_query: Query<(&Villager, &mut VillagerRoute, &GlobalTransform)>,
+    nav_grid: Res<NavGrid>,
+    clock: Res<Clock>
+) {
+    // the first level hasn't spawned yet
+    if nav_grid.grid_size == 0 {
+        return;
+    }
+    for (villager, mut route, transform) in villager_query.iter_mut() {
+        let stop = match villager.stop_at(clock.hour()) {
+            Some(stop) => stop,
+            None => continue
+        };
+        // the schedule is relative to the level the villager was placed in
+        let destination = stop.cell + nav_grid.origin;
+        if route.destination == Some(destination) && route.revision == nav_grid.revision {
+            continue;
+        }
+
+        let start = nav_grid.cell(transform.translation() + PLAYER_FOOT_OFFSET);
+        route.destination = Some(destination);
+        route.revision = nav_grid.revision;
+        route.path = match nav_grid.find_path(start, destination, NavAgent::PERSON) {
+            Some(path) => path.into(),
+            None => {
+                log::warn!("{} can't find a way to {:?}", villager.name, stop.cell);
+                VecDeque::new()
+            }
+        };
+    }
+}
+
+/// walks villagers from cell center to cell center along their route
+pub fn walk_villagers(
+    mut villager_query: Query<(
+        Entity,
+        &Villager,
+        &mut VillagerRoute,
+        &mut Velocity,
+        &mut AnimationDirection,
+        &GlobalTransform
+    )>,
+    animations: Res<Animations>,
+    mut animation_event: EventWriter<AnimationEvent>,
+    time: Res<Time>
+) {
+    for (entity, villager, mut route, mut velocity, mut direction, transform) in villager_query.iter_mut() {
+        // the animations are set up the frame after the villager spawns
+        if !animations.is_inserted(&entity) {
+            continue;
+        }
+        velocity.linvel = Vec2::ZERO;
+        *direction = AnimationDirection::Still;
+
+        let feet = (transform.translation() + PLAYER_FOOT_OFFSET).truncate();
+        let step = VILLAGER_WALKING_SPEED * time.delta_seconds();
+        let mut target = None;
+        while let Some(cell) = route.path.front() {
+            let cell_center = (cell.as_vec2() + 0.5) * villager.grid_size as f32;
+            if cell_center.distance(feet) > step {
+                target = Some(cell_center);
+                break;
+            }
+            route.path.pop_front();
+        }
+
+        if let Some(target) = target {
+            let move_vec = (target - feet).normalize_or_zero();
+            *direction = nearest_direction(move_vec);
+            velocity.linvel = move_vec * VILLAGER_WALKING_SPEED;
+            animation_event.send(AnimationEvent("villager_walking", entity));
+        }
+    }
+}
+
+/// opens a closed gate when a villager walks into it, `close_gates_behind` closes it again once they have left
+pub fn open_gates_for_villagers(
+    mut gate_query: Query<(Entity, &mut Gate, &mut Interactable)>,
+    villager_query: Query<Entity, With<Villager>>,
+    context: Res<RapierContext>,
+    animations: Res<Animations>,
+    mut animation_event: EventWriter<AnimationEvent>
+) {
+    for (gate_entity, mut gate, mut interactable) in gate_query.iter_mut() {
+        if gate.open || animations.in_animation(gate_entity) != Some(false) {
+            continue;
+        }
+        if villager_query.iter().any(|villager| context.intersection_pair(villager, gate_entity) == Some(true)) {
+            gate.set_open(true, gate_entity, &mut interactable, &mut animation_event);
+        }
+    }
+}
+
+/// nothing sets the velocity of villagers outside the game so they would keep walking through menus and conversations
+pub fn stop_villagers(
+    mut villager_query: Query<&mut Velocity, With<Villager>>
+) {
+    for mut velocity in villager_query.iter_mut() {
+        velocity.linvel = Vec2::ZERO;
+    }
+}
+
+/// the first conversation of the day with a villager raises their relationship
+pub fn talk_to_villagers(
+    mut interaction_events: EventReader<InteractionEvent>,
+    villager_query: Query<&Villager>,
+    mut relationships: ResMut<Relationships>,
+    config: Res<FriendshipConfig>,
+    calendar: Res<Calendar>
+) {
+    for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Talk) {
+        let villager = match villager_query.get(event.entity) {
+            Ok(villager) => villager,
+            Err(_) => continue
+        };
+        let relationship = relationships.0.entry(villager.name.clone()).or_default();
+        relationship.last_seen = calendar.total_days();
+        if !relationship.talked_today {
+            relationship.talked_today = true;
+            relationship.add_points(config.talk_points, &config);
+        }
+    }
+}
+
+/// gives a single item from the clicked slot to the villager and closes the inventory, villagers only take one gift a day
+pub fn give_gifts(
+    mut gift_events: EventReader<GiftEvent>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    villager_query: Query<&Villager>,
+    inventory_mode: Res<InventoryMode>,
+    mut relationships: ResMut<Relationships>,
+    config: Res<FriendshipConfig>,
+    calendar: Res<Calendar>,
+    mut state: ResMut<State<GameState>>
+) {
+    let villager = match *inventory_mode {
+        InventoryMode::Gift(entity) => match villager_query.get(entity) {
+            Ok(villager) => villager,
+            Err(_) => return
+        },
+        _ => return
+    };
+    for GiftEvent(slot) in gift_events.iter() {
+        let mut inventory = player_query.single_mut();
+        // tools and dead crops can't be given away
+        if inventory.get(*slot).map_or(true, |stack| stack.item.sell_price().is_none()) {
+            continue;
+        }
+        let relationship = relationships.0.entry(villager.name.clone()).or_default();
+        if relationship.gifted_today {
+            log::info!("{} already had a gift today", villager.name);
+            continue;
+        }
+        let gift = match inventory.remove_from_slot(*slot, 1) {
+            Some(gift) => gift,
+            None => continue
+        };
+        let (taste, points) = config.gift_points(&villager.name, &gift.item);
+        relationship.add_points(points, &config);
+        relationship.gifted_today = true;
+        relationship.last_seen = calendar.total_days();
+        log::info!("{} {} the {} ({} points)", villager.name, taste.name(), gift.item.name(), points);
+        state.overwrite_set(GameState::Game).unwrap();
+        return;
+    }
+}
+
+/// villagers the player hasn't seen for a while start to forget about them
+pub fn fade_relationships(
+    mut new_day_events: EventReader<NewDayEvent>,
+    mut relationships: ResMut<Relationships>,
+    config: Res<FriendshipConfig>
+) {
+    for NewDayEvent(calendar) in new_day_events.iter() {
+        for relationship in relationships.0.values_mut() {
+            relationship.new_day(calendar.total_days(), &config);
+        }
+    }
+}
+
+/// shares the recipes and sets the dialogue flags of every unlock a relationship has reached
+pub fn unlock_friendship_rewards(
+    relationships: Res<Relationships>,
+    config: Res<FriendshipConfig>,
+    mut known_recipes: ResMut<KnownRecipes>,
+    mut flags: ResMut<DialogueFlags>
+) {
+    if !relationships.is_changed() {
+        return;
+    }
+    // only touches the resources when something is new so anything watching them doesn't see them change every time
+    for (name, relationship) in relationships.0.iter() {
+        let tastes = match config.villagers.get(name) {
+            Some(tastes) => tastes,
+            None => continue
+        };
+        for (points, unlock) in tastes.unlocks.iter().filter(|(points, _)| relationship.points >= *points) {
+            match unlock {
+                FriendshipUnlock::Recipe(recipe) => {
+                    if !known_recipes.0.contains(recipe) {
+                        known_recipes.0.insert(recipe.clone());
+                        log::info!("{} shared the {} recipe at {} points", name, recipe, points);
+                    }
+                },
+                FriendshipUnlock::Flag(flag) => {
+                    if !flags.0.contains(flag) {
+                        flags.0.insert(flag.clone());
+                    }
+                }
+            }
+        }
+    }
+}
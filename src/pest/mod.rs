@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use rand::Rng;
+use crate::GameState;
+
+use self::systems::*;
+
+pub mod systems;
+
+/// the chance every night that crows come for the crops
+pub const CROW_NIGHT_CHANCE: f64 = 0.35;
+/// the chance each unprotected crop is eaten on a night the crows come
+pub const CROP_EATEN_CHANCE: f64 = 0.2;
+/// crops this many cells or closer to a scarecrow are safe from crows
+pub const SCARECROW_RADIUS: i32 = 4;
+
+/// **INFO** plugin for the crows that come for the crops at night and the scarecrows that keep them away
+///
+/// Every new day there is a chance crows came overnight, if they did every living crop that isn't within `SCARECROW_RADIUS` of a scarecrow
+/// in its level has a chance of being eaten and dies. How many crops were lost is shown in a notification in the morning
+///
+/// Scarecrows are bought from the shop and put down with the `PlacementPlugin`
+///
+/// **NOTE** every roll comes from the `GameRng` so a game with the same seed loses the same crops
+pub struct PestPlugin;
+
+impl Plugin for PestPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(crows_visit_overnight)
+            )
+        ;
+    }
+}
+
+/// Which of `crops` the crows eat tonight, `None` if the crows didn't come at all
+///
+/// crops and scarecrows are given with the identifier of their level and their grid cell, a crop is safe if a scarecrow in the same level is
+/// within `SCARECROW_RADIUS` of it. The eaten crops are in the order of their level and cell
+pub fn eaten_crops<T: Copy>(crops: &[(T, &str, IVec2)], scarecrows: &[(&str, IVec2)], rng: &mut impl Rng) -> Option<Vec<T>> {
+    if !rng.gen_bool(CROW_NIGHT_CHANCE) {
+        return None;
+    }
+    // queries don't keep their order between runs so the crops are sorted for the same rng to always eat the same ones
+    let mut crops = crops.to_vec();
+    crops.sort_by_key(|(_, level, cell)| (*level, cell.x, cell.y));
+    let protected = |level: &str, cell: IVec2| scarecrows.iter().any(|(scarecrow_level, scarecrow_cell)| {
+        let offset = *scarecrow_cell - cell;
+        *scarecrow_level == level && offset.x * offset.x + offset.y * offset.y <= SCARECROW_RADIUS * SCARECROW_RADIUS
+    });
+    Some(crops.iter()
+        .filter(|(_, level, cell)| !protected(level, *cell))
+        // rolled for every unprotected crop so the rolls don't shift when a crop is protected
+        .filter(|_| rng.gen_bool(CROP_EATEN_CHANCE))
+        .map(|(id, _, _)| *id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{rng::{GameRng, RngStream}, level::MAIN_LEVEL};
+    use super::*;
+
+    const SEED: u64 = 0x5EED;
+
+    /// a square field of crops in `MAIN_LEVEL` numbered row by row
+    fn field(size: i32) -> Vec<(usize, &'static str, IVec2)> {
+        (0..size * size)
+            .map(|i| (i as usize, MAIN_LEVEL, IVec2::new(i % size, i / size)))
+            .collect()
+    }
+
+    /// what the crows eat every day of the first `days` with `seed`
+    fn nights(seed: u64, days: u32, crops: &[(usize, &str, IVec2)]) -> Vec<Option<Vec<usize>>> {
+        let rng = GameRng::new(seed);
+        (0..days)
+            .map(|day| eaten_crops(crops, &[], &mut rng.for_day(day, RngStream::Pests)))
+            .collect()
+    }
+
+    #[test]
+    fn days_and_seeds_eat_different_crops() {
+        let crops = field(10);
+        let first_seed = nights(SEED, 50, &crops);
+        assert_eq!(first_seed, nights(SEED, 50, &crops));
+
+        // the crows come on some nights but not all of them and don't eat the same crops every time they do
+        let visits: Vec<&Vec<usize>> = first_seed.iter().flatten().collect();
+        assert!(!visits.is_empty() && visits.len() < first_seed.len(), "crows came on {} of {} nights", visits.len(), first_seed.len());
+        assert!(visits.windows(2).any(|pair| pair[0] != pair[1]));
+
+        assert_ne!(first_seed, nights(SEED + 1, 50, &crops));
+    }
+
+    #[test]
+    fn crop_order_doesnt_matter() {
+        let crops = field(10);
+        let mut reversed = crops.clone();
+        reversed.reverse();
+        // every third crop first, then the rest
+        let mut interleaved: Vec<_> = crops.iter().copied().step_by(3).collect();
+        interleaved.extend(crops.iter().copied().enumerate().filter(|(i, _)| i % 3 != 0).map(|(_, crop)| crop));
+        assert_eq!(interleaved.len(), crops.len());
+
+        let scarecrows = [(MAIN_LEVEL, IVec2::new(2, 2))];
+        let rng = GameRng::new(SEED);
+        for day in 0..50 {
+            let eaten = eaten_crops(&crops, &scarecrows, &mut rng.for_day(day, RngStream::Pests));
+            assert_eq!(eaten, eaten_crops(&reversed, &scarecrows, &mut rng.for_day(day, RngStream::Pests)), "day {}", day);
+            assert_eq!(eaten, eaten_crops(&interleaved, &scarecrows, &mut rng.for_day(day, RngStream::Pests)), "day {}", day);
+        }
+    }
+
+    #[test]
+    fn crops_near_scarecrows_are_never_eaten() {
+        let crops = field(30);
+        let scarecrow = IVec2::new(15, 15);
+        let rng = GameRng::new(SEED);
+        let mut eaten_any = false;
+        for day in 0..100 {
+            let eaten = eaten_crops(&crops, &[(MAIN_LEVEL, scarecrow)], &mut rng.for_day(day, RngStream::Pests)).unwrap_or_default();
+            eaten_any |= !eaten.is_empty();
+            for id in eaten {
+                let offset = crops[id].2 - scarecrow;
+                assert!(offset.x * offset.x + offset.y * offset.y > SCARECROW_RADIUS * SCARECROW_RADIUS, "crop at {} was eaten", crops[id].2);
+            }
+        }
+        // otherwise the test proves nothing
+        assert!(eaten_any);
+    }
+
+    #[test]
+    fn scarecrows_in_other_levels_dont_protect() {
+        let crops = field(SCARECROW_RADIUS);
+        let rng = GameRng::new(SEED);
+        for day in 0..50 {
+            let unprotected = eaten_crops(&crops, &[], &mut rng.for_day(day, RngStream::Pests));
+            let other_level = eaten_crops(&crops, &[("Town", IVec2::ZERO)], &mut rng.for_day(day, RngStream::Pests));
+            assert_eq!(unprotected, other_level, "day {}", day);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::{prelude::*, log};
+use crate::{crop::{Crop, CropType, grid_coords}, calendar::NewDayEvent, load_atlases::Atlases, level::{CurrentLevel, LevelEntity}, placement::{PlacedObject, ObjectKind}, rng::{GameRng, RngStream}, ui::notification::NotificationEvent};
+
+use super::*;
+
+/// rolls for crows every morning and kills the crops they ate
+pub fn crows_visit_overnight(
+    mut new_day_events: EventReader<NewDayEvent>,
+    mut crop_query: Query<(Entity, &Transform, &mut Crop, &mut TextureAtlasSprite, &mut Handle<TextureAtlas>, &LevelEntity)>,
+    object_query: Query<(&PlacedObject, &Transform, &LevelEntity)>,
+    game_rng: Res<GameRng>,
+    current_level: Res<CurrentLevel>,
+    atlases: Res<Atlases>,
+    mut notification_events: EventWriter<NotificationEvent>
+) {
+    for NewDayEvent(calendar) in new_day_events.iter() {
+        let grid_size = current_level.grid_size();
+        let crops: Vec<(Entity, &str, IVec2)> = crop_query.iter()
+            .filter(|(_, _, crop, ..)| crop.crop_type != CropType::Dead)
+            .map(|(entity, transform, crop, _, _, level_entity)| (entity, level_entity.0.as_str(), crop.cell(transform.translation, grid_size)))
+            .collect();
+        let scarecrows: Vec<(&str, IVec2)> = object_query.iter()
+            .filter(|(object, ..)| object.kind == ObjectKind::Scarecrow)
+            .map(|(_, transform, level_entity)| (level_entity.0.as_str(), grid_coords(transform.translation, grid_size)))
+            .collect();
+
+        let mut rng = game_rng.for_day(calendar.total_days(), RngStream::Pests);
+        let eaten = match eaten_crops(&crops, &scarecrows, &mut rng) {
+            Some(eaten) => eaten,
+            None => continue
+        };
+        log::info!("crows ate {} of {} crops", eaten.len(), crops.len());
+
+        for entity in eaten.iter() {
+            if let Ok((_, _, mut crop, mut sprite, mut texture, _)) = crop_query.get_mut(*entity) {
+                crop.kill(&mut sprite, &mut texture, &atlases);
+            }
+        }
+
+        let message = match eaten.len() {
+            0 => "Crows came by last night but didn't get any crops".to_string(),
+            1 => "Crows ate 1 crop last night".to_string(),
+            count => format!("Crows ate {} crops last night", count)
+        };
+        notification_events.send(NotificationEvent(message));
+    }
+}
@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{ldtk::ColliderBundle, GameState, item::{Item, Inventory, CHEST_INVENTORY_SIZE}, save::Savable, level::LevelEntity, crop::{CropType, TILLED_SOIL_Z}, mechanics::{perspective::SecondaryPerspectiveBody, interaction::*}};
+
+use self::systems::*;
+
+pub mod systems;
+
+// placed objects are drawn over tilled soil like crops
+pub const PLACED_OBJECT_Z: f32 = TILLED_SOIL_Z + 5.;
+/// the ghost is drawn over everything in the level
+pub const PLACEMENT_GHOST_Z: f32 = 60.;
+pub const PLACEMENT_GHOST_ALPHA: f32 = 0.5;
+pub const INVALID_PLACEMENT_COLOR: Color = Color::rgba(0.9, 0.2, 0.2, PLACEMENT_GHOST_ALPHA);
+/// sprinklers water the crops this many cells or closer every morning, diagonals included
+pub const SPRINKLER_RADIUS: i32 = 1;
+pub const PICK_UP_PROMPT: &str = "Pick up";
+pub const OPEN_PROMPT: &str = "Open";
+
+/// **INFO** plugin for the objects the player can put down in the world
+///
+/// Clicking a placeable item in the inventory closes it and starts placement mode, a ghost of the object shows the cell in front of the player
+/// it would go on and turns red when it can't go there. Objects only go on free grass, so never on fences, gates, paths, soil or other objects.
+/// Using the tool action places one and placement mode lasts until the stack runs out or the inventory is opened again
+///
+/// Placed objects are `LevelEntity`s that are saved like crops, they block the player and the `NavGrid` with a fixed collider and interacting
+/// with one picks it back up into the inventory. Chests open instead and are picked up by hitting them with the axe or pickaxe once empty
+///
+/// Seeds and fertilizer use placement mode too. Seeds are planted on crop fields and tilled soil without a crop and fertilizer goes on crops
+/// that are still growing
+///
+/// **NOTE** there is no art for placed objects yet so they are colored sprites like tilled soil
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Placement::default())
+            .add_event::<StartPlacementEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Inventory)
+                .with_system(start_placement)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(update_placement_ghost)
+                .with_system(place_objects)
+                .with_system(pick_up_objects)
+                .with_system(water_crops_with_sprinklers)
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Game)
+                .with_system(stop_placement)
+            )
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ObjectKind {
+    Chest,
+    Sprinkler,
+    Scarecrow,
+    Fence
+}
+
+impl ObjectKind {
+    /// the object an item turns into once placed, `None` for items that can't be placed
+    pub fn from_item(item: &Item) -> Option<Self> {
+        match item {
+            Item::Chest => Some(ObjectKind::Chest),
+            Item::Sprinkler => Some(ObjectKind::Sprinkler),
+            Item::Scarecrow => Some(ObjectKind::Scarecrow),
+            Item::Fence => Some(ObjectKind::Fence),
+            _ => None
+        }
+    }
+
+    /// what picking the object up gives back
+    pub fn item(&self) -> Item {
+        match self {
+            ObjectKind::Chest => Item::Chest,
+            ObjectKind::Sprinkler => Item::Sprinkler,
+            ObjectKind::Scarecrow => Item::Scarecrow,
+            ObjectKind::Fence => Item::Fence
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        match self {
+            ObjectKind::Chest => Vec2::new(14., 12.),
+            ObjectKind::Sprinkler => Vec2::new(8., 8.),
+            ObjectKind::Scarecrow => Vec2::new(12., 22.),
+            ObjectKind::Fence => Vec2::new(16., 16.)
+        }
+    }
+
+    /// only the bottom of tall objects blocks the player so they stay inside their cell
+    pub fn collider_half_extents(&self) -> Vec2 {
+        match self {
+            ObjectKind::Chest => Vec2::new(7., 6.),
+            ObjectKind::Sprinkler => Vec2::new(4., 4.),
+            ObjectKind::Scarecrow => Vec2::new(4., 4.),
+            ObjectKind::Fence => Vec2::new(8., 8.)
+        }
+    }
+
+    /// chests are opened, everything else is picked up
+    pub fn interactable(&self) -> Interactable {
+        match self {
+            ObjectKind::Chest => Interactable::new(OPEN_PROMPT, 0, InteractionHandler::Chest),
+            _ => Interactable::new(PICK_UP_PROMPT, 0, InteractionHandler::PickUp)
+        }
+    }
+
+    /// the empty inventory of objects that hold items, it is added next to the `PlacedObject`
+    pub fn contents(&self) -> Option<Inventory> {
+        match self {
+            ObjectKind::Chest => Some(Inventory::new(CHEST_INVENTORY_SIZE)),
+            _ => None
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ObjectKind::Chest => Color::rgb(0.55, 0.35, 0.15),
+            ObjectKind::Sprinkler => Color::rgb(0.6, 0.65, 0.75),
+            ObjectKind::Scarecrow => Color::rgb(0.72, 0.56, 0.3),
+            ObjectKind::Fence => Color::rgb(0.45, 0.3, 0.18)
+        }
+    }
+}
+
+/// what using the item being placed does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Placing {
+    Object(ObjectKind),
+    Seeds(CropType),
+    Fertilizer
+}
+
+impl Placing {
+    /// `None` for items that can't be used in placement mode
+    pub fn from_item(item: &Item) -> Option<Self> {
+        match item {
+            Item::Seeds(CropType::Dead) => None,
+            Item::Seeds(crop_type) => Some(Placing::Seeds(crop_type.clone())),
+            Item::Fertilizer => Some(Placing::Fertilizer),
+            item => ObjectKind::from_item(item).map(Placing::Object)
+        }
+    }
+
+    /// the size of the ghost
+    pub fn size(&self) -> Vec2 {
+        match self {
+            Placing::Object(kind) => kind.size(),
+            _ => Vec2::new(10., 10.)
+        }
+    }
+
+    /// the color of the ghost
+    pub fn color(&self) -> Color {
+        match self {
+            Placing::Object(kind) => kind.color(),
+            Placing::Seeds(_) => Color::rgb(0.4, 0.65, 0.3),
+            Placing::Fertilizer => Color::rgb(0.5, 0.4, 0.25)
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlacedObject {
+    pub kind: ObjectKind
+}
+
+/// the inventory slot holding what is being placed, `None` outside of placement mode
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Placement(pub Option<usize>);
+
+/// starts placement mode with the item in the inventory slot
+#[derive(Debug, Clone, Copy)]
+pub struct StartPlacementEvent(pub usize);
+
+/// shows where the object being placed would go
+#[derive(Component)]
+pub struct PlacementGhost;
+
+#[derive(Bundle)]
+pub struct PlacedObjectBundle {
+    #[bundle]
+    pub sprite_bundle: SpriteBundle,
+    pub placed_object: PlacedObject,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    pub interactable: Interactable,
+    pub secondary_perspective_body: SecondaryPerspectiveBody,
+    pub level_entity: LevelEntity,
+    pub savable: Savable
+}
+
+impl PlacedObjectBundle {
+    pub fn new(kind: ObjectKind, cell: IVec2, grid_size: i32, level: String) -> Self {
+        let half_extents = kind.collider_half_extents();
+        Self {
+            sprite_bundle: SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(kind.size()),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(((cell.as_vec2() + 0.5) * grid_size as f32).extend(PLACED_OBJECT_Z)),
+                ..Default::default()
+            },
+            placed_object: PlacedObject { kind },
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(half_extents.x, half_extents.y),
+                rigid_body: RigidBody::Fixed,
+                ..Default::default()
+            },
+            interactable: kind.interactable(),
+            secondary_perspective_body: SecondaryPerspectiveBody,
+            level_entity: LevelEntity(level),
+            savable: Savable
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::{prelude::*, log};
+use crate::{player::{Player, PLAYER_FOOT_OFFSET}, item::Inventory, input::*, crop::{Crop, CropType, CropBundle, CropField, SmallCropColliderBundle, Watered, HARVEST_PROMPT, grid_coords}, load_atlases::Atlases, calendar::NewDayEvent, level::{CurrentLevel, LevelEntity, LevelTransition}, tool::systems::facing_cell, mechanics::interaction::*, navigation::NavGrid, ui::notification::NotificationEvent};
+
+use super::*;
+
+/// the cell in front of the players feet, the same one tools are used on
+fn placement_cell(transform: &Transform, facing: &Facing, grid_size: i32) -> IVec2 {
+    grid_coords(transform.translation + PLAYER_FOOT_OFFSET, grid_size) + facing_cell(facing.0)
+}
+
+/// objects go on free grass in the level the player is in, never on the cell the player is standing on
+fn can_place(cell: IVec2, player_cell: IVec2, nav_grid: &NavGrid, current_level: &CurrentLevel) -> bool {
+    nav_grid.level == current_level.identifier() && cell != player_cell && nav_grid.is_free(cell)
+}
+
+/// the center of the crop field on `cell` in the level the player is in
+fn crop_field_center<'a>(cell: IVec2, crop_fields: impl Iterator<Item = (&'a CropField, &'a GlobalTransform, Option<&'a LevelEntity>)>, current_level: &CurrentLevel) -> Option<Vec3> {
+    crop_fields
+        .filter(|(_, _, level_entity)| level_entity.map_or(true, |level_entity| level_entity.0 == current_level.identifier()))
+        .map(|(crop_field, transform, _)| (grid_coords(transform.translation(), crop_field.grid_size), transform.translation()))
+        .find(|(crop_field_cell, _)| *crop_field_cell == cell)
+        .map(|(_, translation)| translation)
+}
+
+/// seeds go on crop fields without a crop and fertilizer on crops that are still growing, objects use `can_place`
+fn can_use(placing: &Placing, crop_field: Option<Vec3>, crop: Option<&Crop>, can_place: bool) -> bool {
+    match placing {
+        Placing::Object(_) => can_place,
+        Placing::Seeds(_) => crop_field.is_some() && crop.is_none(),
+        Placing::Fertilizer => crop.map_or(false, Crop::can_fertilize)
+    }
+}
+
+/// what the item in the slot being placed does, `None` once the stack has run out
+fn placing(placement: &Placement, inventory: &Inventory) -> Option<Placing> {
+    placement.0
+        .and_then(|slot| inventory.get(slot))
+        .and_then(|stack| Placing::from_item(&stack.item))
+}
+
+pub fn start_placement(
+    mut start_events: EventReader<StartPlacementEvent>,
+    mut placement: ResMut<Placement>,
+    input_settings: Res<InputSettings>,
+    mut notification_events: EventWriter<NotificationEvent>,
+    mut state: ResMut<State<GameState>>
+) {
+    if let Some(StartPlacementEvent(slot)) = start_events.iter().last() {
+        placement.0 = Some(*slot);
+        notification_events.send(NotificationEvent(format!(
+            "[{}] Place  [{}] Stop placing",
+            input_settings.binding(Action::UseTool).describe(),
+            input_settings.binding(Action::Inventory).describe()
+        )));
+        state.overwrite_set(GameState::Game).unwrap();
+    }
+}
+
+/// leaving the game i.e opening the inventory ends placement mode
+pub fn stop_placement(
+    mut commands: Commands,
+    mut placement: ResMut<Placement>,
+    ghost_query: Query<Entity, With<PlacementGhost>>
+) {
+    placement.0 = None;
+    for entity in ghost_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// keeps the ghost on the cell in front of the player and ends placement mode once there is nothing left to place
+pub fn update_placement_ghost(
+    mut commands: Commands,
+    mut placement: ResMut<Placement>,
+    player_query: Query<(&Inventory, &Transform, &Facing), With<Player>>,
+    mut ghost_query: Query<(Entity, &mut Transform, &mut Sprite), (With<PlacementGhost>, Without<Player>)>,
+    crop_field_query: Query<(&CropField, &GlobalTransform, Option<&LevelEntity>)>,
+    crop_query: Query<(&Crop, &Transform, &LevelEntity), Without<PlacementGhost>>,
+    nav_grid: Res<NavGrid>,
+    current_level: Res<CurrentLevel>
+) {
+    let (inventory, player_transform, facing) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return
+    };
+    let placing = match placing(&placement, inventory) {
+        Some(placing) => placing,
+        None => {
+            if placement.0.is_some() {
+                placement.0 = None;
+            }
+            for (entity, ..) in ghost_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            return;
+        }
+    };
+
+    let grid_size = current_level.grid_size();
+    let cell = placement_cell(player_transform, facing, grid_size);
+    let player_cell = grid_coords(player_transform.translation + PLAYER_FOOT_OFFSET, grid_size);
+    let translation = ((cell.as_vec2() + 0.5) * grid_size as f32).extend(PLACEMENT_GHOST_Z);
+    let crop_field = crop_field_center(cell, crop_field_query.iter(), &current_level);
+    let crop = crop_query.iter()
+        .find(|(crop, transform, level_entity)| level_entity.0 == current_level.identifier() && crop.cell(transform.translation, grid_size) == cell)
+        .map(|(crop, ..)| crop);
+    let color = if can_use(&placing, crop_field, crop, can_place(cell, player_cell, &nav_grid, &current_level)) {
+        *placing.color().set_a(PLACEMENT_GHOST_ALPHA)
+    }
+    else {
+        INVALID_PLACEMENT_COLOR
+    };
+
+    match ghost_query.get_single_mut() {
+        Ok((_, mut transform, mut sprite)) => {
+            transform.translation = translation;
+            sprite.color = color;
+            sprite.custom_size = Some(placing.size());
+        },
+        Err(_) => {
+            commands.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(placing.size()),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(translation),
+                ..Default::default()
+            })
+                .insert(PlacementGhost)
+            ;
+        }
+    }
+}
+
+pub fn place_objects(
+    mut commands: Commands,
+    action_input: Res<ActionInput>,
+    placement: Res<Placement>,
+    mut player_query: Query<(&mut Inventory, &Transform, &Facing), With<Player>>,
+    crop_field_query: Query<(&CropField, &GlobalTransform, Option<&LevelEntity>)>,
+    mut crop_query: Query<(&mut Crop, &Transform, &LevelEntity)>,
+    nav_grid: Res<NavGrid>,
+    current_level: Res<CurrentLevel>,
+    transition: Res<LevelTransition>,
+    atlases: Res<Atlases>
+) {
+    if !action_input.just_pressed(Action::UseTool) || !transition.is_idle() {
+        return;
+    }
+    let (mut inventory, transform, facing) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return
+    };
+    let placing = match placing(&placement, &inventory) {
+        Some(placing) => placing,
+        None => return
+    };
+
+    let grid_size = current_level.grid_size();
+    let cell = placement_cell(transform, facing, grid_size);
+    let player_cell = grid_coords(transform.translation + PLAYER_FOOT_OFFSET, grid_size);
+    let crop_field = crop_field_center(cell, crop_field_query.iter(), &current_level);
+    let mut crop = crop_query.iter_mut()
+        .find(|(crop, transform, level_entity)| level_entity.0 == current_level.identifier() && crop.cell(transform.translation, grid_size) == cell)
+        .map(|(crop, ..)| crop);
+    if !can_use(&placing, crop_field, crop.as_deref(), can_place(cell, player_cell, &nav_grid, &current_level)) {
+        log::info!("can't use {:?} on {}", placing, cell);
+        return;
+    }
+    // the ghost ends placement mode once the stack runs out
+    inventory.remove_from_slot(placement.0.unwrap(), 1);
+    match placing {
+        Placing::Object(kind) => {
+            let mut object = commands.spawn(PlacedObjectBundle::new(kind, cell, grid_size, current_level.identifier().to_string()));
+            if let Some(contents) = kind.contents() {
+                object.insert(contents);
+            }
+            log::info!("placed a {:?} on {}", kind, cell);
+        },
+        Placing::Seeds(crop_type) => {
+            let cell_center = crop_field.unwrap();
+            commands.spawn(CropBundle::new(Crop::new(crop_type.clone()), cell_center, grid_size, current_level.identifier().to_string(), &atlases))
+                .insert(Interactable::new(HARVEST_PROMPT, 0, InteractionHandler::Harvest))
+                .with_children(|parent| {
+                    parent.spawn(SmallCropColliderBundle::default());
+                })
+            ;
+            log::info!("planted {:?} on {}", crop_type, cell);
+        },
+        Placing::Fertilizer => {
+            if let Some(crop) = crop.as_mut() {
+                crop.fertilized = true;
+            }
+            log::info!("fertilized the crop on {}", cell);
+        }
+    }
+}
+
+pub fn pick_up_objects(
+    mut commands: Commands,
+    mut interaction_events: EventReader<InteractionEvent>,
+    object_query: Query<&PlacedObject>,
+    mut player_query: Query<&mut Inventory, With<Player>>
+) {
+    for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::PickUp) {
+        let object = match object_query.get(event.entity) {
+            Ok(object) => object,
+            Err(_) => continue
+        };
+        let mut inventory = player_query.single_mut();
+        if inventory.add(object.kind.item(), 1) > 0 {
+            log::info!("no room in the inventory for the {:?}", object.kind);
+            continue;
+        }
+        commands.entity(event.entity).despawn_recursive();
+    }
+}
+
+/// every morning sprinklers water the living crops around them in their level
+pub fn water_crops_with_sprinklers(
+    mut commands: Commands,
+    mut new_day_events: EventReader<NewDayEvent>,
+    object_query: Query<(&PlacedObject, &Transform, &LevelEntity)>,
+    crop_query: Query<(Entity, &Crop, &Transform, &LevelEntity)>,
+    current_level: Res<CurrentLevel>
+) {
+    for _ in new_day_events.iter() {
+        let grid_size = current_level.grid_size();
+        let sprinklers: Vec<(&str, IVec2)> = object_query.iter()
+            .filter(|(object, ..)| object.kind == ObjectKind::Sprinkler)
+            .map(|(_, transform, level_entity)| (level_entity.0.as_str(), grid_coords(transform.translation, grid_size)))
+            .collect();
+        if sprinklers.is_empty() {
+            continue;
+        }
+
+        for (entity, crop, transform, level_entity) in crop_query.iter() {
+            if crop.crop_type == CropType::Dead {
+                continue;
+            }
+            let cell = crop.cell(transform.translation, grid_size);
+            let watered = sprinklers.iter().any(|(level, sprinkler)| {
+                let offset = (*sprinkler - cell).abs();
+                *level == level_entity.0 && offset.x <= SPRINKLER_RADIUS && offset.y <= SPRINKLER_RADIUS
+            });
+            if watered {
+                commands.entity(entity).insert(Watered);
+            }
+        }
+    }
+}
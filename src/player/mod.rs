@@ -1,27 +1,37 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use serde::{Serialize, Deserialize};
-use crate::{bevy_animations::*, ldtk::*, GameState, mechanics::perspective::PrimaryPerspectiveBody, save::Savable};
+use crate::{bevy_animations::*, ldtk::*, GameState, mechanics::{perspective::PrimaryPerspectiveBody, interaction::Facing}, save::Savable, item::Inventory, tool::{EquippedTool, PendingToolUse}, energy::Energy, skill::Skills, economy::Wallet, gate::GateOpener};
 
 use self::systems::*;
 
 pub mod systems;
 
-pub const PLAYER_WALKING_VEL: f32 = 0.90;
-pub const PLAYER_RUNNUNG_VEL: f32 = 1.25;
+// player speeds in pixels per second
+pub const PLAYER_WALKING_SPEED: f32 = 54.;
+pub const PLAYER_RUNNING_SPEED: f32 = 75.;
+// how far the left stick has to be pushed before the player moves
+pub const STICK_DEADZONE: f32 = 0.15;
+// where the players feet are relative to the center of the sprite
+pub const PLAYER_FOOT_OFFSET: Vec3 = Vec3::new(0., -8., 0.);
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
+            .insert_resource(RunState::default())
+            .insert_resource(PlayerRoute::default())
             .add_system_set(SystemSet::on_enter(GameState::LoadingGame)
                 .with_system(spawn_extra_colliders)
             )
             .add_system_set(SystemSet::on_update(GameState::Game)
-                .with_system(movement)
-                .with_system(check_gate_collisions)
+                .with_system(click_to_move.before("player_movement"))
+                .with_system(movement.label("player_movement"))
+                .with_system(interact_with_gates)
                 .with_system(center_camera_around_player)
                 .with_system(harvest_crop)
             )
@@ -31,11 +41,23 @@ impl Plugin for PlayerPlugin {
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component, Serialize, Deserialize)]
 pub struct Player {
+    /// the crop being harvested
     pub crop_colliding: Option<Entity>,
-    pub previous_crop_colliding: Option<Entity>,
     pub harvesting: bool,
 }
 
+/// whether the player is running, only used with `RunMode::Toggle`
+#[derive(Debug, Resource, Default)]
+pub struct RunState {
+    pub toggled: bool
+}
+
+/// the cells the player is walking through after clicking somewhere with click to move on
+#[derive(Debug, Resource, Default)]
+pub struct PlayerRoute {
+    pub path: VecDeque<IVec2>
+}
+
 #[derive(Component)]
 pub struct PlayerFootCollider;
 
@@ -50,7 +72,17 @@ pub struct LdtkPlayer {
     #[bundle]
     pub collider_bundle: ColliderBundle,
     entity_instance: EntityInstance,
+    // the player isn't despawned with its level so it can walk between levels
+    pub worldly: Worldly,
     pub direction: AnimationDirection,
+    pub facing: Facing,
+    pub gate_opener: GateOpener,
+    pub inventory: Inventory,
+    pub equipped_tool: EquippedTool,
+    pub pending_tool_use: PendingToolUse,
+    pub energy: Energy,
+    pub skills: Skills,
+    pub wallet: Wallet,
     pub perpective_body: PrimaryPerspectiveBody,
     pub savable: Savable,
     pub ldtk: Ldtk
@@ -65,32 +97,20 @@ impl LdtkEntity for LdtkPlayer {
             _: &AssetServer,
             _: &mut Assets<TextureAtlas>,
         ) -> LdtkPlayer {
-        let fields = &entity_instance.field_instances;
-
-        let mut linvel = 0.;
-
-        for field in fields {
-            if field.identifier == "velocity" {
-                if let FieldValue::Float(Some(vel)) = field.value {
-                    linvel = vel;
-                }
-            }
-        }
         LdtkPlayer { 
             sprite_sheet_bundle: SpriteSheetBundle::default(),
             player: Player::default(),
             collider_bundle: ColliderBundle { 
                 collider: Collider::cuboid(4., 18.), 
                 rigid_body: RigidBody::Dynamic, 
-                velocity: Velocity { linvel: Vec2::new(linvel, linvel), angvel: 0. }, 
+                velocity: Velocity::zero(), 
                 rotation_constraints: LockedAxes::ROTATION_LOCKED,
-                damping: Damping {
-                    linear_damping: 100.,
-                    angular_damping: 100.
-                },
                 ..Default::default()
             },
             entity_instance: entity_instance.clone(),
+            worldly: Worldly::from_entity_info(entity_instance),
+            inventory: Inventory::starting(),
+            wallet: Wallet::from_entity_instance(entity_instance),
             ..Default::default()
         }
     }
@@ -1,146 +1,221 @@
+use std::collections::VecDeque;
+
 use bevy::{prelude::*, log};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
-use crate::{gate::*, player::*, OtherAssets};
+use crate::{gate::*, player::*, OtherAssets, level::{CurrentLevel, LevelTransition}, input::*, mechanics::interaction::*, navigation::*, crop::Crop, item::{Inventory, Item}, energy::*, skill::*};
 
-pub fn check_gate_collisions(
-    mut gate_query: Query<(
-        Entity,
-        &mut Gate
-    )>,
-    player_query: Query<(
-        Entity,  
-        &Player    
-    )>,
-    context: Res<RapierContext>,
+/// opens and closes gates the player interacts with
+pub fn interact_with_gates(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut gate_query: Query<(&mut Gate, &mut Interactable)>,
     animations: Res<Animations>,
     mut animation_event: EventWriter<AnimationEvent>
 ) {
-    // the player entity might not be loaded in yet
-    let player_entity = match player_query.get_single() {
-        Ok(entity) => entity.0,
+    for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Gate) {
+        let (mut gate, mut interactable) = match gate_query.get_mut(event.entity) {
+            Ok(q) => q,
+            Err(_) => continue
+        };
+        if animations.in_animation(event.entity) != Some(false) {
+            continue;
+        }
+        let open = !gate.open;
+        gate.set_open(open, event.entity, &mut interactable, &mut animation_event);
+    }
+}
+
+/// finds a path to the cell that was clicked on when click to move is turned on
+pub fn click_to_move(
+    mouse_buttons: Res<Input<MouseButton>>,
+    input_settings: Res<InputSettings>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    player_query: Query<&Transform, With<Player>>,
+    nav_grid: Res<NavGrid>,
+    mut route: ResMut<PlayerRoute>
+) {
+    if !input_settings.click_to_move || !mouse_buttons.just_pressed(MouseButton::Left) || nav_grid.grid_size == 0 {
+        return;
+    }
+    let cursor = match windows.get_primary().and_then(|window| window.cursor_position().map(|cursor| (window, cursor))) {
+        Some((window, cursor)) => cursor - Vec2::new(window.width(), window.height()) / 2.,
+        None => return
+    };
+    let (camera_transform, projection) = match camera_query.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return
+    };
+    let player_transform = match player_query.get_single() {
+        Ok(transform) => transform,
         Err(_) => return
     };
 
-    for gate_entity in gate_query.iter_mut() {
-        let (gate_entity, mut gate) = gate_entity;
-
-        if let Some(_) = context.intersection_pair(player_entity, gate_entity) {
-            if let Some(in_animation) = animations.in_animation(gate_entity) {
-                if !gate.open && !gate.in_collision && !in_animation {
-                    animation_event.send(AnimationEvent("gate_opening", gate_entity));
-                    gate.in_collision = true;
-                    gate.open = true;
-                }
-            }
-        }
-        else if gate.in_collision && gate.open {
-            animation_event.send(AnimationEvent("gate_closing", gate_entity));
-            gate.in_collision = false;
-            gate.open = false;
+    // the cursor is relative to the center of the window which the camera is looking at
+    let clicked = camera_transform.translation + (cursor * projection.scale).extend(0.);
+    let start = nav_grid.cell(player_transform.translation + PLAYER_FOOT_OFFSET);
+    route.path = match nav_grid.find_path(start, nav_grid.cell(clicked), NavAgent::PERSON) {
+        Some(path) => path.into(),
+        None => {
+            log::info!("no way to get to {:?}", nav_grid.cell(clicked));
+            VecDeque::new()
         }
-    }
+    };
 }
 
 pub fn movement(
-    input: Res<Input<KeyCode>>,
+    action_input: Res<ActionInput>,
+    input_settings: Res<InputSettings>,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut run_state: ResMut<RunState>,
+    mut route: ResMut<PlayerRoute>,
+    nav_grid: Res<NavGrid>,
     mut player_query: Query<(
         Entity,
-        &mut Transform,
+        &Transform,
         &mut Velocity,
         &mut AnimationDirection,
+        &mut Facing,
+        &mut Energy,
         &Player
     )>,
-    level_query: Query<(&Handle<LdtkLevel>, &Transform), (Without<Player>, Without<Camera2d>)>,
-    ldtk_levels: Res<Assets<LdtkLevel>>,
     mut animation_event_writer: EventWriter<AnimationEvent>,
     animations: ResMut<Animations>,
+    transition: Res<LevelTransition>,
+    energy_settings: Res<EnergySettings>,
+    time: Res<Time>
 ) {
-    let (player_entity, mut transform, mut vel, mut direction, _) = player_query.single_mut();
+    let (player_entity, transform, mut vel, mut direction, mut facing, mut energy, _) = player_query.single_mut();
 
     // if we haven't initialized our animations yet
     if !animations.is_inserted(&player_entity) {
         return;
     }
 
-    let inputs = input.get_pressed();
+    // the player only moves while a key is held so we start every frame standing still
+    vel.linvel = Vec2::ZERO;
 
-    let mut dir = AnimationDirection::default();
+    // the player can't move while the screen is fading between levels
+    if !transition.is_idle() {
+        route.path.clear();
+        return;
+    }
 
-    let mut running = false;
+    let mut move_vec = Vec2::ZERO;
 
-    for key in inputs {
-        match *key {
-            KeyCode::A => dir = AnimationDirection::Left,
-            KeyCode::S => dir = AnimationDirection::Down,
-            KeyCode::D => dir = AnimationDirection::Right,
-            KeyCode::W => dir = AnimationDirection::Up,
-            KeyCode::LShift => {
-                running = true;
-                vel.linvel.x = PLAYER_RUNNUNG_VEL;
-                vel.linvel.y = PLAYER_RUNNUNG_VEL;
-            }
-            _ => {}
+    // every held direction adds to the movement so diagonals work and the order they were pressed in doesn't matter
+    if action_input.pressed(Action::MoveLeft) {
+        move_vec.x -= 1.;
+    }
+    if action_input.pressed(Action::MoveDown) {
+        move_vec.y -= 1.;
+    }
+    if action_input.pressed(Action::MoveRight) {
+        move_vec.x += 1.;
+    }
+    if action_input.pressed(Action::MoveUp) {
+        move_vec.y += 1.;
+    }
+    // normalized so walking diagonally isn't faster
+    move_vec = move_vec.normalize_or_zero();
+
+    for gamepad in gamepads.iter() {
+        let stick = Vec2::new(
+            gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.),
+            gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.)
+        );
+        // the stick is analog so a half tilt walks at half speed
+        if stick.length() > STICK_DEADZONE && move_vec == Vec2::ZERO {
+            move_vec = stick.clamp_length_max(1.);
         }
     }
 
-    *direction = dir;
+    // moving by hand cancels click to move
+    if move_vec != Vec2::ZERO {
+        route.path.clear();
+    }
+    let feet = (transform.translation + PLAYER_FOOT_OFFSET).truncate();
+    while let Some(cell) = route.path.front() {
+        let target = nav_grid.cell_center(*cell) - feet;
+        // close enough that the next frame would overshoot it
+        if target.length() > PLAYER_WALKING_SPEED * time.delta_seconds() {
+            move_vec = target.normalize();
+            break;
+        }
+        route.path.pop_front();
+    }
+
+    let running = match input_settings.run_mode {
+        RunMode::Hold => action_input.pressed(Action::Run),
+        RunMode::Toggle => {
+            if action_input.just_pressed(Action::Run) {
+                run_state.toggled = !run_state.toggled;
+            }
+            run_state.toggled
+        }
+    };
+    // an exhausted player is too tired to run
+    let running = running && !energy.is_exhausted();
+
+    *direction = nearest_direction(move_vec);
+    if move_vec != Vec2::ZERO {
+        facing.0 = move_vec.normalize();
+    }
 
     if *direction != AnimationDirection::default() {
         if let Some(in_animation) = animations.in_blocking_animation(player_entity) {
             if in_animation {
                 return;
             }
-            if !running {
-                vel.linvel.x = PLAYER_WALKING_VEL;
-                vel.linvel.y = PLAYER_WALKING_VEL;
-                animation_event_writer.send(AnimationEvent("player_walking", player_entity));
-            } 
-            else {
+            let speed = if running {
                 animation_event_writer.send(AnimationEvent("player_running", player_entity));
+                energy.spend(energy_settings.drain(EnergyAction::Run) * time.delta_seconds());
+                PLAYER_RUNNING_SPEED
+            } 
+            else if energy.is_exhausted() {
+                animation_event_writer.send(AnimationEvent("player_walking", player_entity));
+                PLAYER_WALKING_SPEED * energy_settings.exhausted_speed_multiplier
             }
-    
-            let translation = transform.translation;
-    
-            for (level_handle, level_transform) in level_query.iter() {
-                let level = ldtk_levels.get(level_handle).unwrap();
-                let level_bounds = Rect {
-                    min: Vec2::new(level_transform.translation.x, level_transform.translation.y),
-                    max: Vec2::new(
-                        level_transform.translation.x + level.level.px_wid as f32,
-                        level_transform.translation.y + level.level.px_hei as f32,
-                    ),
-                };
-                let new_transform = Transform::from_translation(
-                    Vec3::new(
-                        translation.x + AnimationDirection::get_direction(&direction).x * vel.linvel.x, 
-                        translation.y + AnimationDirection::get_direction(&direction).y * vel.linvel.y, 
-                        translation.z
-                    )
-                );
-                if new_transform.translation.x < level_bounds.max.x &&
-                    new_transform.translation.x > level_bounds.min.x &&
-                    new_transform.translation.y < level_bounds.max.y &&
-                    new_transform.translation.y > level_bounds.min.y 
-                {
-                    *transform = new_transform;
-                }
-            }
+            else {
+                animation_event_writer.send(AnimationEvent("player_walking", player_entity));
+                PLAYER_WALKING_SPEED
+            };
+            // rapier integrates the velocity with the frame time so the speed doesn't depend on the frame rate
+            // and fences, buildings and the level boundaries stop the player instead of being walked through
+            vel.linvel = move_vec * speed;
         }
     }
 }
 
+/// the sprite sheets only have four rows so pick whichever one is closest to the way the player is moving
+pub fn nearest_direction(move_vec: Vec2) -> AnimationDirection {
+    if move_vec == Vec2::ZERO {
+        return AnimationDirection::Still;
+    }
+    if move_vec.x.abs() >= move_vec.y.abs() {
+        if move_vec.x > 0. { AnimationDirection::Right } else { AnimationDirection::Left }
+    }
+    else {
+        if move_vec.y > 0. { AnimationDirection::Up } else { AnimationDirection::Down }
+    }
+}
+
 pub fn center_camera_around_player(
     player_query: Query<&Transform, With<Player>>,
     mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<Player>, Without<Handle<LdtkLevel>>)>,
     windows: Res<Windows>,
     level_query: Query<(&Handle<LdtkLevel>, &Transform), (Without<Player>, Without<Camera2d>)>,
     ldtk_levels: Res<Assets<LdtkLevel>>,
+    current_level: Res<CurrentLevel>
 ) {
     if let Ok(player_transform) = player_query.get_single() {
         let mut camera_transform = camera_query.single_mut();
         for (level_handle, level_transform) in &level_query {
             if let Some(ldtk_level) = ldtk_levels.get(level_handle) {
+                if ldtk_level.level.iid != current_level.0.iid {
+                    continue;
+                }
                 if let Some(window) = windows.get_primary() {
                     let window_width = window.width() / 2.0;
                     let window_height = window.height() / 2.0;
@@ -180,7 +255,7 @@ pub fn spawn_extra_colliders(
                 ..Default::default()
             })
                 .insert(PlayerFootCollider)
-                .insert(TransformBundle::from(Transform::from_xyz(0., -8., 0.)))
+                .insert(TransformBundle::from(Transform::from_translation(PLAYER_FOOT_OFFSET)))
             ;
             // second sensor collider is just a larger one mainly for crop collision detection
             children.spawn(SensorBundle {
@@ -198,33 +273,63 @@ pub fn spawn_extra_colliders(
 
 pub fn harvest_crop(
     mut commands: Commands,
+    mut interaction_events: EventReader<InteractionEvent>,
     mut animation_event: EventWriter<AnimationEvent>,
     animations: Res<Animations>,
-    mut player_query: Query<(&mut Player, Entity)>,
-    inputs: Res<Input<KeyCode>>
+    mut player_query: Query<(&mut Player, &mut Inventory, &mut Energy, &Skills, Entity)>,
+    mut interactable_query: Query<&mut Interactable>,
+    crop_query: Query<&Crop>,
+    energy_settings: Res<EnergySettings>,
+    mut experience_events: EventWriter<ExperienceEvent>
 ) {
-    let (mut player, entity) = player_query.single_mut();
+    let (mut player, mut inventory, mut energy, skills, entity) = player_query.single_mut();
 
     // check if we aren't in a blocking animation and we are in the harvesting state. If thats all true we should despawn the crop and set everything back to normal
     if let Some(in_animation) = animations.in_blocking_animation(entity) {
         if !in_animation && player.harvesting && player.crop_colliding.is_some() {
-            commands.entity(player.crop_colliding.unwrap()).despawn();
+            let crop_entity = player.crop_colliding.unwrap();
             player.crop_colliding = None;
             player.harvesting = false;
-            animation_event.send(AnimationEvent("player_walking", entity))
-        }
-    }
+            animation_event.send(AnimationEvent("player_walking", entity));
 
-    let mut input = false;
-
-    for key in inputs.get_pressed() {
-        if *key == KeyCode::Space {
-            input = true;
+            // only fully grown crops give produce, anything else is just pulled out
+            let produce = match crop_query.get(crop_entity) {
+                Ok(crop) if crop.is_ripe() => Some(Item::Crop(crop.crop_type.clone(), skills.roll_crop_quality(&mut rand::thread_rng()))),
+                _ => None
+            };
+            match produce {
+                // the crop stays in the ground and can be harvested again once there is room
+                Some(item) if !inventory.can_add(&item, 1) => {
+                    log::info!("no room in the inventory for {}", item.name());
+                    if let Ok(mut interactable) = interactable_query.get_mut(crop_entity) {
+                        interactable.enabled = true;
+                    }
+                },
+                Some(item) => {
+                    inventory.add(item, 1);
+                    experience_events.send(ExperienceEvent {
+                        skill: Skill::Farming,
+                        amount: HARVEST_EXPERIENCE
+                    });
+                    commands.entity(crop_entity).despawn_recursive();
+                },
+                None => commands.entity(crop_entity).despawn_recursive()
+            }
         }
     }
 
-    if player.crop_colliding.is_some() && input {
+    for event in interaction_events.iter().filter(|event| event.handler == InteractionHandler::Harvest) {
+        // a tool could still be swinging
+        if player.harvesting || animations.in_blocking_animation(entity) == Some(true) {
+            continue;
+        }
+        // the crop can't be focused again while it is being harvested
+        if let Ok(mut interactable) = interactable_query.get_mut(event.entity) {
+            interactable.enabled = false;
+        }
+        player.crop_colliding = Some(event.entity);
         player.harvesting = true;
+        energy.spend(energy_settings.drain(EnergyAction::Harvest));
         animation_event.send(AnimationEvent("player_harvesting", entity));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Serialize, Deserialize};
+
+/// what a generator from `GameRng` is used for, every kind of roll gets its own so adding one doesn't change the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    Pests
+}
+
+impl RngStream {
+    fn id(&self) -> u64 {
+        match self {
+            RngStream::Pests => 1
+        }
+    }
+}
+
+/// The seed the rolls that matter to the game are made from, a new game gets a random one and saves keep theirs
+///
+/// Generators are made for a single day and `RngStream` so the same seed always rolls the same no matter when the game was saved and
+/// loaded again, which also means the rolls can be reproduced by creating a `GameRng` with a known seed
+#[derive(Debug, Resource, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng {
+    pub seed: u64
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn for_day(&self, day: u32, stream: RngStream) -> StdRng {
+        // spreads the day and stream over all the bits so neighbouring days don't get similar seeds
+        let seed = self.seed
+            ^ (day as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ stream.id().wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        StdRng::seed_from_u64(seed)
+    }
+}
@@ -0,0 +1,153 @@
+use std::{fs::{File, read, create_dir_all}, io::{Write, Read}, path::Path, fmt};
+
+use bevy::log;
+use flate2::{write::GzEncoder, read::GzDecoder, Compression};
+use serde::*;
+
+use super::*;
+
+/// every exported archive starts with these bytes so we can reject random files dropped onto the window
+pub const ARCHIVE_MAGIC: &[u8; 6] = b"RFSAVE";
+pub const ARCHIVE_EXTENSION: &str = "rfsave";
+pub const ARCHIVE_EXPORT_DIR: &str = "saves/exports";
+
+/// A single portable file that bundles everything needed to recreate a save slot on another machine
+///
+/// On disk the layout is `ARCHIVE_MAGIC` followed by a little endian crc32 of the compressed body and then the gzipped ron of this struct
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveArchive {
+    pub meta: SaveGameMetaData,
+    /// the raw bytes of the save file in whichever codec it was written in so older versions can be migrated on import
+    pub data: Vec<u8>,
+    pub thumbnail: Option<Vec<u8>>
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(std::io::Error),
+    NotAnArchive,
+    ChecksumMismatch,
+    Parse(ron::error::SpannedError),
+    Save(CodecError),
+    MaxSaveGamesReached
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(err) => write!(f, "io error: {}", err),
+            ArchiveError::NotAnArchive => write!(f, "file is not a Rusty Farm save archive"),
+            ArchiveError::ChecksumMismatch => write!(f, "archive checksum doesn't match, the file is corrupted"),
+            ArchiveError::Parse(err) => write!(f, "failed to parse archive: {}", err),
+            ArchiveError::Save(err) => write!(f, "archive contains an invalid save: {}", err),
+            ArchiveError::MaxSaveGamesReached => write!(f, "there are already {} save games", MAX_SAVE_GAMES)
+        }
+    }
+}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(err: std::io::Error) -> Self {
+        ArchiveError::Io(err)
+    }
+}
+
+impl From<CodecError> for ArchiveError {
+    fn from(err: CodecError) -> Self {
+        ArchiveError::Save(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for ArchiveError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        ArchiveError::Parse(err)
+    }
+}
+
+/// writes the save slot `name` into `saves/exports/` and returns the path of the new archive
+pub fn export_save(name: &str) -> Result<String, ArchiveError> {
+    let save_games = SaveGames::read();
+    let meta = save_games.0.iter()
+        .find(|save_game| save_game.name == name)
+        .cloned()
+        .unwrap_or(SaveGameMetaData {
+            date: String::new(),
+            name: name.to_string()
+        });
+
+    let save_name = SaveName::Load(name.to_string());
+    let data = read(save_name.path().unwrap())?;
+    let thumbnail = read(save_name.thumbnail_path().unwrap()).ok();
+
+    let body = ron::to_string(&SaveArchive { meta, data, thumbnail }).unwrap();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_bytes())?;
+    let compressed = encoder.finish()?;
+
+    create_dir_all(ARCHIVE_EXPORT_DIR)?;
+    let archive_path = format!("{}/{}.{}", ARCHIVE_EXPORT_DIR, name.trim_end_matches(".rson"), ARCHIVE_EXTENSION);
+    let mut file = File::create(&archive_path)?;
+    file.write_all(ARCHIVE_MAGIC)?;
+    file.write_all(&crc32fast::hash(&compressed).to_le_bytes())?;
+    file.write_all(&compressed)?;
+
+    log::info!("exported {} to {}", name, archive_path);
+    Ok(archive_path)
+}
+
+/// reads an archive, verifies it, migrates the save data and registers it as a new save slot
+///
+/// the imported save is re-encoded with `codec` so it matches the rest of this machines saves
+///
+/// returns the name of the newly created slot
+pub fn import_save(path: &Path, codec: SaveCodec) -> Result<String, ArchiveError> {
+    let bytes = read(path)?;
+
+    let header_len = ARCHIVE_MAGIC.len() + 4;
+    if bytes.len() < header_len || &bytes[..ARCHIVE_MAGIC.len()] != ARCHIVE_MAGIC {
+        return Err(ArchiveError::NotAnArchive);
+    }
+
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&bytes[ARCHIVE_MAGIC.len()..header_len]);
+    let compressed = &bytes[header_len..];
+    if crc32fast::hash(compressed) != u32::from_le_bytes(checksum) {
+        return Err(ArchiveError::ChecksumMismatch);
+    }
+
+    let mut body = String::new();
+    GzDecoder::new(compressed).read_to_string(&mut body)?;
+    let archive = ron::from_str::<SaveArchive>(&body.as_str())?;
+
+    // parse the save itself so a bad archive never makes it into the save games list
+    let save_data = SaveCodec::decode::<SaveData>(&archive.data)?.migrate();
+
+    let mut save_games = SaveGames::read();
+    if save_games.0.len() >= MAX_SAVE_GAMES {
+        return Err(ArchiveError::MaxSaveGamesReached);
+    }
+
+    // the archive keeps its original slot name but that name is most likely already used on this machine
+    let name = save_games.next_free_name();
+    if name != archive.meta.name {
+        log::info!("save {} already exists importing as {}", archive.meta.name, name);
+    }
+    let save_name = SaveName::Load(name.clone());
+
+    let mut save_file = File::create(save_name.path().unwrap())?;
+    save_file.write_all(&codec.encode(&save_data))?;
+
+    if let Some(thumbnail) = archive.thumbnail {
+        let mut thumbnail_file = File::create(save_name.thumbnail_path().unwrap())?;
+        thumbnail_file.write_all(&thumbnail)?;
+    }
+
+    save_games.0.push(SaveGameMetaData {
+        date: archive.meta.date,
+        name: name.clone()
+    });
+    save_games.write();
+
+    log::info!("imported {} as {}", path.display(), name);
+    Ok(name)
+}
@@ -0,0 +1,246 @@
+use std::fmt;
+
+use serde::{*, de::DeserializeOwned};
+
+/// binary saves start with these bytes which is how `SaveCodec::decode` tells the two formats apart
+pub const BINARY_MAGIC: &[u8; 5] = b"RFMSG";
+pub const SAVE_CONFIG_PATH: &str = "saves/save_config.rson";
+
+/// The format save files are written in
+///
+/// Both codecs read and write the same `SaveData` structures. Loading never looks at the config, it detects the codec from the file itself
+/// so switching the config doesn't break older saves
+///
+/// **NOTE** both formats keep the field names so fields added later fall back to their `#[serde(default)]` and `SaveData::migrate` works
+/// the same for either codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SaveCodec {
+    /// human readable pretty ron, mainly for debugging
+    #[default]
+    Ron,
+    /// compact MessagePack, much smaller and faster to parse for large farms
+    Binary
+}
+
+#[derive(Debug)]
+pub enum CodecError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Binary(rmp_serde::decode::Error),
+    Utf8(std::str::Utf8Error)
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Io(err) => write!(f, "couldn't read save: {}", err),
+            CodecError::Ron(err) => write!(f, "invalid ron save: {}", err),
+            CodecError::Binary(err) => write!(f, "invalid binary save: {}", err),
+            CodecError::Utf8(err) => write!(f, "save is neither binary nor valid utf8: {}", err)
+        }
+    }
+}
+
+impl From<std::io::Error> for CodecError {
+    fn from(err: std::io::Error) -> Self {
+        CodecError::Io(err)
+    }
+}
+
+impl SaveCodec {
+    pub fn encode<T: Serialize>(&self, value: &T) -> Vec<u8> {
+        match self {
+            SaveCodec::Ron => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).unwrap().into_bytes(),
+            SaveCodec::Binary => {
+                let mut bytes = BINARY_MAGIC.to_vec();
+                bytes.extend(rmp_serde::to_vec_named(value).unwrap());
+                bytes
+            }
+        }
+    }
+
+    /// returns the codec `bytes` were written with
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(BINARY_MAGIC) {
+            return SaveCodec::Binary;
+        }
+        SaveCodec::Ron
+    }
+
+    /// decodes `bytes` with whichever codec they were written in
+    pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError> {
+        match SaveCodec::detect(bytes) {
+            SaveCodec::Ron => {
+                let data = std::str::from_utf8(bytes).map_err(CodecError::Utf8)?;
+                ron::from_str::<T>(data).map_err(CodecError::Ron)
+            },
+            SaveCodec::Binary => rmp_serde::from_slice::<T>(&bytes[BINARY_MAGIC.len()..]).map_err(CodecError::Binary)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use bevy::prelude::{Vec3, IVec2};
+
+    use crate::{player::Player, crop::{Crop, CropType}, item::{Inventory, Item, ItemStack, Quality}, calendar::{Calendar, Clock}, economy::market::Market, npc::friendship::Relationship, animal::{Animal, AnimalKind}, placement::ObjectKind, rng::GameRng, level::MAIN_LEVEL};
+    use super::super::*;
+    use super::*;
+
+    fn sample_save() -> SaveData {
+        SaveData {
+            version: SAVE_VERSION,
+            date: "2023-04-01 12:00".to_string(),
+            level: MAIN_LEVEL.to_string(),
+            player_data: PlayerData {
+                translation: Vec3::new(120., -48., 10.),
+                player: Player::default(),
+                inventory: Inventory::starting(),
+                equipped_tool: Some(1),
+                energy: Default::default(),
+                skills: Default::default(),
+                wallet: None
+            },
+            crop_data: vec![CropData {
+                translation: Vec3::new(8., 8., 0.),
+                crop: Crop::new(CropType::Potato),
+                level: MAIN_LEVEL.to_string()
+            }],
+            tilled_data: vec![TilledData { cell: IVec2::new(3, 4), grid_size: 16, level: MAIN_LEVEL.to_string() }],
+            broken_breakables: vec!["a1b2".to_string()],
+            calendar: Calendar::default(),
+            clock: Clock::default(),
+            shipping_bin: vec![ItemStack::new(Item::Crop(CropType::Potato, Quality::Normal), 5)],
+            shop_purchases: HashMap::from([(Item::Crop(CropType::Potato, Quality::Normal), 2)]),
+            market: Market::new(7),
+            dialogue_flags: HashSet::from(["met_mayor".to_string()]),
+            relationships: HashMap::from([("Abigail".to_string(), Relationship::default())]),
+            known_recipes: HashSet::new(),
+            animals: vec![AnimalData {
+                translation: Vec3::new(64., 32., 5.),
+                animal: Animal::new(AnimalKind::Cow, "Daisy".to_string()),
+                level: MAIN_LEVEL.to_string()
+            }],
+            objects: vec![PlacedObjectData {
+                kind: ObjectKind::Chest,
+                cell: IVec2::new(1, 1),
+                grid_size: 16,
+                level: MAIN_LEVEL.to_string(),
+                contents: Some(Inventory::new(4))
+            }],
+            scarecrows: Vec::new(),
+            rng: Some(GameRng::new(42))
+        }
+    }
+
+    /// the pretty ron of a save, used to compare saves since `SaveData` doesn't implement `PartialEq`
+    fn ron_of(save: &SaveData) -> String {
+        String::from_utf8(SaveCodec::Ron.encode(save)).unwrap()
+    }
+
+    #[test]
+    fn ron_round_trip() {
+        let save = sample_save();
+        let bytes = SaveCodec::Ron.encode(&save);
+        assert_eq!(SaveCodec::detect(&bytes), SaveCodec::Ron);
+        assert_eq!(ron_of(&SaveCodec::decode::<SaveData>(&bytes).unwrap()), ron_of(&save));
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let save = sample_save();
+        let bytes = SaveCodec::Binary.encode(&save);
+        assert_eq!(SaveCodec::detect(&bytes), SaveCodec::Binary);
+        assert_eq!(ron_of(&SaveCodec::decode::<SaveData>(&bytes).unwrap()), ron_of(&save));
+    }
+
+    #[test]
+    fn binary_is_smaller_than_ron() {
+        let save = sample_save();
+        assert!(SaveCodec::Binary.encode(&save).len() < SaveCodec::Ron.encode(&save).len());
+    }
+
+    /// a save from before most fields existed, both codecs have to fill the missing fields from `#[serde(default)]`
+    #[derive(Serialize)]
+    struct OldSave {
+        date: String,
+        player_data: PlayerData,
+        crop_data: Vec<CropData>
+    }
+
+    fn old_save() -> OldSave {
+        let save = sample_save();
+        OldSave { date: save.date, player_data: save.player_data, crop_data: save.crop_data }
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        for codec in [SaveCodec::Ron, SaveCodec::Binary] {
+            let save = SaveCodec::decode::<SaveData>(&codec.encode(&old_save())).unwrap();
+            assert_eq!(save.version, 0, "{:?}", codec);
+            assert!(save.objects.is_empty(), "{:?}", codec);
+            assert!(save.rng.is_none(), "{:?}", codec);
+
+            let save = save.migrate();
+            assert_eq!(save.version, SAVE_VERSION, "{:?}", codec);
+            assert_eq!(save.crop_data[0].level, MAIN_LEVEL, "{:?}", codec);
+        }
+    }
+
+    #[test]
+    fn produce_without_quality_is_normal_quality() {
+        let stack = SaveCodec::decode::<ItemStack>(b"(item: Crop(Carrot), count: 3)").unwrap();
+        assert_eq!(stack, ItemStack::new(Item::Crop(CropType::Carrot, Quality::Normal), 3));
+        let stack = SaveCodec::decode::<ItemStack>(b"(item: Crop(Carrot, Gold), count: 3)").unwrap();
+        assert_eq!(stack, ItemStack::new(Item::Crop(CropType::Carrot, Quality::Gold), 3));
+    }
+
+    #[test]
+    fn friendship_points_load_as_relationships() {
+        let relationships = SaveCodec::decode::<HashMap<String, Relationship>>(b"{\"Abigail\": 250}").unwrap();
+        assert_eq!(relationships["Abigail"], Relationship { points: 250, ..Default::default() });
+
+        let relationship = Relationship { points: 40, last_seen: 12, talked_today: true, gifted_today: false };
+        for codec in [SaveCodec::Ron, SaveCodec::Binary] {
+            assert_eq!(SaveCodec::decode::<Relationship>(&codec.encode(&relationship)).unwrap(), relationship, "{:?}", codec);
+        }
+    }
+
+    /// a version 4 save which kept scarecrows apart from the other placed objects
+    #[derive(Serialize)]
+    struct ScarecrowSave {
+        version: u32,
+        date: String,
+        player_data: PlayerData,
+        crop_data: Vec<CropData>,
+        scarecrows: Vec<ScarecrowData>
+    }
+
+    #[test]
+    fn scarecrows_become_placed_objects() {
+        for codec in [SaveCodec::Ron, SaveCodec::Binary] {
+            let old = old_save();
+            let bytes = codec.encode(&ScarecrowSave {
+                version: 4,
+                date: old.date,
+                player_data: old.player_data,
+                crop_data: old.crop_data,
+                scarecrows: vec![ScarecrowData { cell: IVec2::new(5, 6), grid_size: 16, level: MAIN_LEVEL.to_string() }]
+            });
+            let save = SaveCodec::decode::<SaveData>(&bytes).unwrap().migrate();
+            assert!(save.scarecrows.is_empty(), "{:?}", codec);
+            assert_eq!(save.objects.len(), 1, "{:?}", codec);
+            assert_eq!(save.objects[0].kind, ObjectKind::Scarecrow, "{:?}", codec);
+            assert_eq!(save.objects[0].cell, IVec2::new(5, 6), "{:?}", codec);
+        }
+    }
+
+    #[test]
+    fn garbage_is_an_error() {
+        assert!(SaveCodec::decode::<SaveData>(b"RFMSG\x01\x02\x03").is_err());
+        assert!(SaveCodec::decode::<SaveData>(b"(not a save").is_err());
+        assert!(SaveCodec::decode::<SaveData>(&[0xff, 0xfe, 0xfd]).is_err());
+    }
+}
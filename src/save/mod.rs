@@ -0,0 +1,551 @@
+use std::{collections::{HashMap, HashSet}, fs::{File, read_to_string, read, write, OpenOptions}, io::Write};
+
+use bevy::{prelude::{Component, Vec3, IVec2, GlobalTransform, Plugin, App, Query, With, Transform, ResMut, State, SystemSet, Resource, Commands, Res, BuildChildren}, time::Timer, sprite::SpriteSheetBundle, log};
+use bevy_ecs_ldtk::LevelSelection;
+use bevy_rapier2d::prelude::*;
+use serde::*;
+use time::{OffsetDateTime};
+
+use crate::{GameState, ui::MainMenuState, player::Player, crop::{Crop, CropBundle, SmallCropColliderBundle, TilledSoil, TilledSoilBundle, CropField, HARVEST_PROMPT, grid_coords}, item::{Inventory, ItemStack, Item}, tool::{EquippedTool, BrokenBreakables}, energy::Energy, skill::Skills, economy::{Wallet, ShippingBinContents, CropMarket, market::Market}, calendar::{Calendar, Clock}, shop::ShopPurchases, dialogue::DialogueFlags, npc::{Relationships, friendship::{Relationship, KnownRecipes}}, animal::{Animal, AnimalBundle, AnimalSpawn}, placement::{PlacedObject, PlacedObjectBundle, ObjectKind}, rng::GameRng, NextState, load_atlases::Atlases, ldtk::SensorBundle, bevy_animations::AnimationTimer, mechanics::{perspective::SecondaryPerspectiveBody, interaction::{Interactable, InteractionHandler}}, level::{CurrentLevel, LevelEntity, MAIN_LEVEL}};
+
+use self::{codec::*, thumbnail::render_thumbnail};
+
+pub mod archive;
+pub mod codec;
+pub mod thumbnail;
+
+/// the current version of the `SaveData` format. Bump this whenever `SaveData` changes and add a step to `SaveData::migrate`
+pub const SAVE_VERSION: u32 = 5;
+pub const MAX_SAVE_GAMES: usize = 6;
+
+#[derive(Component, Default, Debug, Clone)]
+pub struct Savable;
+
+/// user config for how saves are written, read from `saves/save_config.rson`
+#[derive(Debug, Default, Resource, Serialize, Deserialize)]
+pub struct SaveConfig {
+    pub codec: SaveCodec
+}
+
+impl SaveConfig {
+    /// falls back to the default config if the file is missing or invalid
+    pub fn read() -> Self {
+        match read_to_string(SAVE_CONFIG_PATH) {
+            Ok(config) => ron::from_str::<SaveConfig>(&config.as_str()).unwrap_or_else(|err| {
+                log::warn!("invalid save config using defaults: {}", err);
+                SaveConfig::default()
+            }),
+            Err(_) => SaveConfig::default()
+        }
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub enum SaveName {
+    #[default]
+    New,
+    Load(String)
+}
+
+impl SaveName {
+    pub fn name(&self) -> Option<String> {
+        match self {
+            SaveName::Load(name) => Some(name.clone()),
+            _ => None
+        }
+    }
+
+    pub fn path(&self) -> Option<String> {
+        match self {
+            SaveName::Load(name) => Some(format!("saves/{}", name.clone())),
+            _ => None
+        }
+    }
+
+    /// the optional thumbnail that sits next to the save file i.e `saves/save1.png`
+    pub fn thumbnail_path(&self) -> Option<String> {
+        match self {
+            SaveName::Load(name) => Some(format!("saves/{}.png", name.trim_end_matches(".rson"))),
+            _ => None
+        }
+    }
+}
+
+/// why the last save couldn't be loaded, imported or exported, shown on the load game menu the next time it is built
+#[derive(Debug, Default, Resource)]
+pub struct SaveLoadError(pub Option<String>);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaveGameMetaData {
+    pub date: String,
+    pub name: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveGames(pub Vec<SaveGameMetaData>);
+
+impl SaveGames {
+    pub fn read() -> Self {
+        let save_games = read_to_string("saves/save_games.rson").expect("Save Games File Not Found");
+        ron::from_str::<SaveGames>(&save_games.as_str()).unwrap()
+    }
+
+    pub fn write(&self) {
+        let mut save_games_file = File::create("saves/save_games.rson").unwrap();
+        save_games_file.write_all(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap().as_bytes()).unwrap();
+    }
+
+    /// returns the first `saveN.rson` name that isn't already taken by another save game
+    pub fn next_free_name(&self) -> String {
+        let mut index = self.0.len() + 1;
+        loop {
+            let name = format!("save{}.rson", index);
+            if !self.0.iter().any(|save_game| save_game.name == name) {
+                return name;
+            }
+            index += 1;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerData {
+    translation: Vec3,
+    player: Player,
+    // saves from before the inventory existed get the tools a new game starts with
+    #[serde(default = "Inventory::starting")]
+    inventory: Inventory,
+    #[serde(default)]
+    equipped_tool: Option<usize>,
+    // saves from before energy existed start with a full bar
+    #[serde(default)]
+    energy: Energy,
+    #[serde(default)]
+    skills: Skills,
+    // saves from before coins existed keep the coins the LDtk player starts with
+    #[serde(default)]
+    wallet: Option<Wallet>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TilledData {
+    cell: IVec2,
+    grid_size: i32,
+    level: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CropData {
+    translation: Vec3,
+    crop: Crop,
+    /// identifier of the LDtk level the crop was planted in
+    #[serde(default)]
+    level: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnimalData {
+    translation: Vec3,
+    animal: Animal,
+    /// identifier of the LDtk level the animal lives in
+    level: String
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlacedObjectData {
+    kind: ObjectKind,
+    cell: IVec2,
+    grid_size: i32,
+    level: String,
+    /// what is stored in a chest
+    #[serde(default)]
+    contents: Option<Inventory>
+}
+
+/// how version 4 saves stored scarecrows before every placed object was saved in `SaveData::objects`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScarecrowData {
+    cell: IVec2,
+    grid_size: i32,
+    level: String
+}
+
+#[derive(Debug, Serialize, Deserialize, Resource)]
+pub struct SaveData {
+    // saves made before versioning was added won't have this field so they default to version 0
+    #[serde(default)]
+    version: u32,
+    date: String,
+    /// identifier of the LDtk level the player was in
+    #[serde(default)]
+    level: String,
+    player_data: PlayerData,
+    crop_data: Vec<CropData>,
+    #[serde(default)]
+    tilled_data: Vec<TilledData>,
+    /// iids of the LDtk breakables that were broken
+    #[serde(default)]
+    broken_breakables: Vec<String>,
+    #[serde(default)]
+    calendar: Calendar,
+    /// saves from before the clock existed load in the morning
+    #[serde(default)]
+    clock: Clock,
+    /// what was shipped on the day the game was saved
+    #[serde(default)]
+    shipping_bin: Vec<ItemStack>,
+    /// what was bought from the shop on the day the game was saved so daily limits carry over
+    #[serde(default)]
+    shop_purchases: HashMap<Item, u32>,
+    /// recent shipments and the price history
+    #[serde(default)]
+    market: Market,
+    #[serde(default)]
+    dialogue_flags: HashSet<String>,
+    /// by villager name
+    #[serde(default)]
+    relationships: HashMap<String, Relationship>,
+    /// recipes shared by villagers
+    #[serde(default)]
+    known_recipes: HashSet<String>,
+    #[serde(default)]
+    animals: Vec<AnimalData>,
+    /// chests, sprinklers, scarecrows and fences the player put down
+    #[serde(default)]
+    objects: Vec<PlacedObjectData>,
+    /// only read from older saves, moved into `objects` by `SaveData::migrate`
+    #[serde(default, skip_serializing)]
+    scarecrows: Vec<ScarecrowData>,
+    /// saves from before the game rng existed get a new random seed
+    #[serde(default)]
+    rng: Option<GameRng>
+}
+
+impl SaveData {
+    /// reads and decodes the save file of `save_name` without migrating it
+    pub fn read(save_name: &SaveName) -> Result<Self, CodecError> {
+        let path = save_name.path().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no save was picked"))?;
+        SaveCodec::decode::<SaveData>(&read(path)?)
+    }
+
+    /// upgrades a `SaveData` from an older `version` to `SAVE_VERSION` one step at a time
+    pub fn migrate(mut self) -> Self {
+        if self.version < 1 {
+            // version 0 saves could have been written while a crop was highlighted, those crops are turned back into
+            // normal ones when `CropType` is deserialized
+            self.version = 1;
+        }
+        if self.version < 2 {
+            // version 1 saves only knew about the main farm
+            self.level = MAIN_LEVEL.to_string();
+            for crop_data in self.crop_data.iter_mut() {
+                crop_data.level = MAIN_LEVEL.to_string();
+            }
+            self.version = 2;
+        }
+        if self.version < 3 {
+            // version 2 saves wrote produce without a quality, `Item` reads those as normal quality
+            self.version = 3;
+        }
+        if self.version < 4 {
+            // version 3 saves only kept the friendship points of each villager, `Relationship` reads those as a villager that was
+            // last seen on the first day
+            self.version = 4;
+        }
+        if self.version < 5 {
+            // version 4 saves kept scarecrows apart since they were the only thing that could be placed
+            for scarecrow in self.scarecrows.drain(..) {
+                self.objects.push(PlacedObjectData {
+                    kind: ObjectKind::Scarecrow,
+                    cell: scarecrow.cell,
+                    grid_size: scarecrow.grid_size,
+                    level: scarecrow.level,
+                    contents: None
+                });
+            }
+            self.version = 5;
+        }
+        self
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(SaveName::default())
+            .insert_resource(SaveConfig::read())
+            .insert_resource(SaveLoadError::default())
+            .add_system_set(SystemSet::on_enter(GameState::Saving)
+                .with_system(save_game)
+            )
+            .add_system_set(SystemSet::on_enter(GameState::LoadingSave)
+                .with_system(load_save)
+            )
+        ;
+    }
+}
+
+pub fn save_game(
+    player_query: Query<(&Transform, &Player, &Inventory, &EquippedTool, &Energy, &Skills, &Wallet), With<Savable>>,
+    crops_query: Query<(&Transform, &Crop, &LevelEntity), With<Savable>>,
+    tilled_query: Query<(&Transform, &CropField, &LevelEntity), (With<TilledSoil>, With<Savable>)>,
+    // grouped since systems can't take more than 16 parameters
+    (animal_query, object_query): (
+        Query<(&Transform, &Animal, &LevelEntity), With<Savable>>,
+        Query<(&Transform, &PlacedObject, &LevelEntity, Option<&Inventory>), With<Savable>>
+    ),
+    broken_breakables: Res<BrokenBreakables>,
+    calendar: Res<Calendar>,
+    clock: Res<Clock>,
+    shipping_bin_contents: Res<ShippingBinContents>,
+    shop_purchases: Res<ShopPurchases>,
+    crop_market: Res<CropMarket>,
+    (dialogue_flags, relationships, known_recipes, game_rng): (Res<DialogueFlags>, Res<Relationships>, Res<KnownRecipes>, Res<GameRng>),
+    current_level: Res<CurrentLevel>,
+    mut app_state: ResMut<State<GameState>>,
+    mut next_state: ResMut<NextState>,
+    mut save_name: ResMut<SaveName>,
+    save_config: Res<SaveConfig>
+) {
+    let (player_transform, player, inventory, equipped_tool, energy, skills, wallet) = player_query.single();
+
+    let tilled_data: Vec<TilledData> = tilled_query.iter()
+        .map(|(transform, crop_field, level_entity)| TilledData {
+            cell: grid_coords(transform.translation, crop_field.grid_size),
+            grid_size: crop_field.grid_size,
+            level: level_entity.0.clone()
+        })
+        .collect();
+
+    let mut crop_data: Vec<CropData> = Vec::new();
+
+    for (crop_transform, crop, level_entity) in crops_query.iter() {
+        crop_data.push(CropData { 
+            translation: crop_transform.translation, 
+            crop: crop.clone(),
+            level: level_entity.0.clone()
+        });
+    }
+
+    let animals: Vec<AnimalData> = animal_query.iter()
+        .map(|(transform, animal, level_entity)| AnimalData {
+            translation: transform.translation,
+            animal: animal.clone(),
+            level: level_entity.0.clone()
+        })
+        .collect();
+
+    let grid_size = current_level.grid_size();
+    let objects: Vec<PlacedObjectData> = object_query.iter()
+        .map(|(transform, object, level_entity, contents)| PlacedObjectData {
+            kind: object.kind,
+            cell: grid_coords(transform.translation, grid_size),
+            grid_size,
+            level: level_entity.0.clone(),
+            contents: contents.cloned()
+        })
+        .collect();
+
+    log::info!("saving {} crops, {} animals and {} objects", crop_data.len(), animals.len(), objects.len());
+
+    let in_current_level = |level: &String| level == current_level.identifier();
+    let thumbnail = render_thumbnail(
+        &current_level.0,
+        &tilled_data.iter().filter(|tilled| in_current_level(&tilled.level)).map(|tilled| tilled.cell).collect::<Vec<_>>(),
+        &crop_data.iter().filter(|crop| in_current_level(&crop.level)).map(|crop| grid_coords(crop.translation, grid_size)).collect::<Vec<_>>(),
+        grid_coords(player_transform.translation, grid_size)
+    );
+
+    let sys_time = OffsetDateTime::now_local().unwrap();
+
+    let formatted_time = format!("{} {} {} at {}:{}", sys_time.month(), sys_time.day(), sys_time.year(), sys_time.hour(), sys_time.minute());
+
+    let encoded_data = save_config.codec.encode(&SaveData {
+        version: SAVE_VERSION,
+        date: formatted_time.clone(),
+        level: current_level.identifier().to_string(),
+        player_data: PlayerData { 
+            translation: player_transform.translation, 
+            player: player.clone(),
+            inventory: inventory.clone(),
+            equipped_tool: equipped_tool.0,
+            energy: *energy,
+            skills: skills.clone(),
+            wallet: Some(*wallet)
+        },
+        crop_data,
+        tilled_data,
+        broken_breakables: broken_breakables.0.clone(),
+        calendar: *calendar,
+        clock: *clock,
+        shipping_bin: shipping_bin_contents.0.clone(),
+        shop_purchases: shop_purchases.0.clone(),
+        market: crop_market.market.clone(),
+        dialogue_flags: dialogue_flags.0.clone(),
+        relationships: relationships.0.clone(),
+        known_recipes: known_recipes.0.clone(),
+        animals,
+        objects,
+        scarecrows: Vec::new(),
+        rng: Some(*game_rng)
+    });
+
+    let save_games_data = read_to_string("saves/save_games.rson").unwrap();
+    let mut save_games_file = File::create("saves/save_games.rson").unwrap();
+    let mut save_games_data = ron::from_str::<SaveGames>(&save_games_data.as_str()).unwrap();
+    
+    // TODO implement an error screen UI that can be used to display an error when the user tries to save more than five games
+    let save_game_path = match save_name.name() {
+        Some(_) => Some(save_name.path().unwrap()),
+        None => {
+            if save_games_data.0.len() < MAX_SAVE_GAMES {
+                let new_name = save_games_data.next_free_name();
+                save_games_data.0.push(SaveGameMetaData { 
+                    date: formatted_time.clone(), 
+                    name: new_name.clone() 
+                });
+                *save_name = SaveName::Load(new_name.clone());
+                Some(format!("saves/{}", new_name))
+            }
+            else {
+                None
+            }
+        }
+    }.expect("Max Save Game Count Reached");
+    log::info!("attempting to open save file {}", save_game_path);
+    let mut save_file = File::create(save_game_path).unwrap();
+    save_file.write_all(&encoded_data).unwrap();
+    // the thumbnail is only used when exporting so the save still counts if it can't be written
+    if let Err(err) = write(save_name.thumbnail_path().unwrap(), thumbnail) {
+        log::warn!("failed to write the thumbnail of {}: {}", save_name.name().unwrap(), err);
+    }
+    
+    for save_game in save_games_data.0.iter_mut() {
+        if save_game.name == save_name.name().unwrap() {
+            save_game.date = formatted_time;
+            break;
+        }
+    }
+    save_games_file.write_all(ron::ser::to_string_pretty(&save_games_data, ron::ser::PrettyConfig::default()).unwrap().as_bytes()).unwrap();
+
+    app_state.overwrite_set(next_state.0.clone()).unwrap();
+    next_state.0 = GameState::default();
+}
+
+fn load_save(
+    mut commands: Commands,
+    mut player_query: Query<(&mut Transform, &mut Player, &mut Inventory, &mut EquippedTool, &mut Energy, &mut Skills, &mut Wallet)>,
+    animal_spawn_query: Query<(&AnimalSpawn, &GlobalTransform)>,
+    mut broken_breakables: ResMut<BrokenBreakables>,
+    mut calendar: ResMut<Calendar>,
+    mut clock: ResMut<Clock>,
+    mut shipping_bin_contents: ResMut<ShippingBinContents>,
+    mut shop_purchases: ResMut<ShopPurchases>,
+    mut crop_market: ResMut<CropMarket>,
+    // grouped since systems can't take more than 16 parameters
+    (mut dialogue_flags, mut relationships, mut known_recipes, mut game_rng): (
+        ResMut<DialogueFlags>,
+        ResMut<Relationships>,
+        ResMut<KnownRecipes>,
+        ResMut<GameRng>
+    ),
+    save_name: Res<SaveName>,
+    (mut game_state, mut next_state, mut main_menu_state, mut load_error): (
+        ResMut<State<GameState>>,
+        ResMut<NextState>,
+        ResMut<State<MainMenuState>>,
+        ResMut<SaveLoadError>
+    ),
+    atlases: Res<Atlases>,
+    mut level_selection: ResMut<LevelSelection>
+) {
+    let (mut player_transform, mut player, mut inventory, mut equipped_tool, mut energy, mut skills, mut wallet) = player_query.single_mut();
+
+    let save_data = match SaveData::read(&save_name) {
+        Ok(save_data) => save_data.migrate(),
+        Err(err) => {
+            // back to the load game menu which shows the error instead of starting a game that is half loaded
+            log::error!("failed to load {}: {}", save_name.name().unwrap_or_default(), err);
+            load_error.0 = Some(format!("Couldn't load save: {}", err));
+            game_state.overwrite_set(GameState::Unload).unwrap();
+            next_state.0 = GameState::MainMenu;
+            main_menu_state.overwrite_set(MainMenuState::LoadingLoadGame).unwrap();
+            return;
+        }
+    };
+
+    *player_transform = Transform::from_xyz(save_data.player_data.translation.x, save_data.player_data.translation.y, save_data.player_data.translation.z + 50.);
+    *player = save_data.player_data.player;
+    *inventory = save_data.player_data.inventory;
+    equipped_tool.0 = save_data.player_data.equipped_tool;
+    *energy = save_data.player_data.energy;
+    *skills = save_data.player_data.skills;
+    if let Some(saved_wallet) = save_data.player_data.wallet {
+        *wallet = saved_wallet;
+    }
+    broken_breakables.0 = save_data.broken_breakables;
+    *calendar = save_data.calendar;
+    *clock = save_data.clock;
+    shipping_bin_contents.0 = save_data.shipping_bin;
+    shop_purchases.0 = save_data.shop_purchases;
+    crop_market.market = save_data.market;
+    dialogue_flags.0 = save_data.dialogue_flags;
+    relationships.0 = save_data.relationships;
+    known_recipes.0 = save_data.known_recipes;
+    *game_rng = save_data.rng.unwrap_or_default();
+
+    log::info!("setting player position and data from save");
+
+    if save_data.level != MAIN_LEVEL {
+        log::info!("moving player to saved level {}", save_data.level);
+        *level_selection = LevelSelection::Identifier(save_data.level.clone());
+    }
+
+    for crop_data in save_data.crop_data {
+        commands.spawn(CropBundle {
+            sprite_sheet_bundle: SpriteSheetBundle {
+                texture_atlas: atlases.handles.get(crop_data.crop.crop_type.atlas_name()).unwrap().clone(),
+                transform: Transform::from_translation(crop_data.translation),
+                ..Default::default()
+            },
+            sensor_bundle: SensorBundle {
+                collider: Collider::cuboid(8., 3.),
+                sensor: Sensor,
+                ..Default::default()
+            },
+            animation_timer: AnimationTimer(Timer::from_seconds(crop_data.crop.crop_type.duration(), bevy::time::TimerMode::Repeating)),
+            rigid_body: RigidBody::KinematicPositionBased,
+            level_entity: LevelEntity(crop_data.level),
+            crop: crop_data.crop,
+            ..Default::default()
+        })
+            .insert(Interactable::new(HARVEST_PROMPT, 0, InteractionHandler::Harvest))
+            .with_children(|parent| {
+                parent.spawn(SmallCropColliderBundle::default());
+            })
+        ;
+    }
+    for tilled_data in save_data.tilled_data {
+        commands.spawn(TilledSoilBundle::new(tilled_data.cell, tilled_data.grid_size, tilled_data.level));
+    }
+    // animals can't be gotten rid of so a save without any is from before they existed and gets the animals a new game starts with
+    if save_data.animals.is_empty() {
+        for (animal_spawn, transform) in animal_spawn_query.iter() {
+            let animal = Animal::new(animal_spawn.kind, animal_spawn.name.clone());
+            commands.spawn(AnimalBundle::new(animal, transform.translation(), MAIN_LEVEL.to_string(), &atlases));
+        }
+    }
+    for animal_data in save_data.animals {
+        commands.spawn(AnimalBundle::new(animal_data.animal, animal_data.translation, animal_data.level, &atlases));
+    }
+    for object_data in save_data.objects {
+        let kind = object_data.kind;
+        let mut object = commands.spawn(PlacedObjectBundle::new(kind, object_data.cell, object_data.grid_size, object_data.level));
+        if let Some(contents) = object_data.contents.or_else(|| kind.contents()) {
+            object.insert(contents);
+        }
+    }
+    game_state.overwrite_set(GameState::LoadingAnimations).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,134 @@
+use std::io::Write;
+
+use bevy::prelude::IVec2;
+use bevy_ecs_ldtk::ldtk::Level;
+use flate2::{write::ZlibEncoder, Compression};
+
+/// how many pixels wide and high every cell of the level is in the thumbnail
+pub const THUMBNAIL_CELL_PIXELS: usize = 2;
+
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+const GRASS_COLOR: [u8; 3] = [106, 190, 48];
+const PATH_COLOR: [u8; 3] = [228, 166, 114];
+const PLANTER_COLOR: [u8; 3] = [143, 86, 59];
+const FENCE_COLOR: [u8; 3] = [102, 57, 49];
+const TILLED_COLOR: [u8; 3] = [92, 64, 43];
+const CROP_COLOR: [u8; 3] = [55, 148, 110];
+const PLAYER_COLOR: [u8; 3] = [217, 87, 99];
+
+/// Draws a small map of `level` as a png, saved next to the save file so exported archives can bundle it
+///
+/// `tilled`, `crops` and `player` are grid cells counted from the bottom of the level like `grid_coords` returns them
+pub fn render_thumbnail(level: &Level, tilled: &[IVec2], crops: &[IVec2], player: IVec2) -> Vec<u8> {
+    let layer_instances = level.layer_instances.as_deref().unwrap_or_default();
+    let (width, height) = layer_instances.first()
+        .map(|layer_instance| (layer_instance.c_wid.max(1) as usize, layer_instance.c_hei.max(1) as usize))
+        .unwrap_or((1, 1));
+
+    let mut cells = vec![GRASS_COLOR; width * height];
+
+    // int grid rows count from the top of the level
+    for layer_instance in layer_instances.iter() {
+        for (i, value) in layer_instance.int_grid_csv.iter().enumerate().take(cells.len()) {
+            let color = match (layer_instance.identifier.as_str(), value) {
+                ("Paths", 1 | 2) => PATH_COLOR,
+                ("Paths", 3) => PLANTER_COLOR,
+                ("Collisions", 1) => FENCE_COLOR,
+                _ => continue
+            };
+            cells[i] = color;
+        }
+    }
+
+    let mut paint = |cell: IVec2, color: [u8; 3]| {
+        let row = height as i32 - 1 - cell.y;
+        if cell.x >= 0 && (cell.x as usize) < width && row >= 0 && (row as usize) < height {
+            cells[row as usize * width + cell.x as usize] = color;
+        }
+    };
+    tilled.iter().for_each(|cell| paint(*cell, TILLED_COLOR));
+    crops.iter().for_each(|cell| paint(*cell, CROP_COLOR));
+    // a single cell is hard to spot so the player is drawn a bit bigger
+    for y in -1..=1 {
+        for x in -1..=1 {
+            paint(player + IVec2::new(x, y), PLAYER_COLOR);
+        }
+    }
+
+    let pixels_width = width * THUMBNAIL_CELL_PIXELS;
+    let mut pixels = Vec::with_capacity(pixels_width * height * THUMBNAIL_CELL_PIXELS * 3);
+    for row in cells.chunks(width) {
+        let line: Vec<u8> = row.iter()
+            .flat_map(|color| color.repeat(THUMBNAIL_CELL_PIXELS))
+            .collect();
+        for _ in 0..THUMBNAIL_CELL_PIXELS {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    encode_png(pixels_width, height * THUMBNAIL_CELL_PIXELS, &pixels)
+}
+
+/// encodes 8 bit rgb `pixels` row by row from the top as an unfiltered png
+pub fn encode_png(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+    for row in pixels.chunks(width * 3) {
+        // every row starts with its filter type which is always none
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&raw).unwrap();
+    let data = encoder.finish().unwrap();
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bit rgb with the default compression and filter methods and no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &data);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+
+    #[test]
+    fn png_has_header_and_rows() {
+        let pixels = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+        let png = encode_png(2, 2, &pixels);
+
+        assert_eq!(&png[..8], PNG_SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..20], &2u32.to_be_bytes());
+        assert_eq!(&png[20..24], &2u32.to_be_bytes());
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+
+        // IHDR takes 25 bytes after the signature, the IDAT length and kind come next
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut raw = Vec::new();
+        ZlibDecoder::new(&png[41..41 + idat_len]).read_to_end(&mut raw).unwrap();
+        assert_eq!(raw, [0, 255, 0, 0, 0, 255, 0, 0, 0, 0, 255, 255, 255, 255]);
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, fs::read_to_string};
+
+use bevy::{prelude::*, log};
+use bevy_ecs_ldtk::{prelude::*, utils::sprite_sheet_bundle_from_entity_info};
+use bevy_rapier2d::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{GameState, ldtk::*, item::{Item, Inventory}, calendar::{Calendar, Season}, mechanics::{interaction::*, perspective::SecondaryPerspectiveBody}};
+
+use self::systems::*;
+
+pub mod systems;
+
+pub const SHOP_CATALOG_PATH: &str = "assets/data/shop.rson";
+pub const SHOP_PROMPT: &str = "Shop";
+
+/// **INFO** plugin for the shop
+///
+/// Interacting with a LDtk `Shop` entity opens the shop screen. What the shop sells and for how much is read from `assets/data/shop.rson`,
+/// every offer can be limited to some seasons, some days of the season and a number of purchases per day. The shop buys anything that has
+/// an `Item::sell_price`, crops at the `CropMarket` price
+///
+/// Tool offers are upgrades, buying one replaces the tool of that kind in the inventory. They are only listed when the player owns the tool
+/// one level below the offer
+///
+/// **NOTE** the ui sends a `BuyEvent` or `SellEvent` and the systems here do the actual transaction
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkShop>("Shop")
+            .insert_resource(ShopCatalog::read())
+            .insert_resource(ShopPurchases::default())
+            .add_event::<BuyEvent>()
+            .add_event::<SellEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(open_shop)
+                .with_system(restock_shop)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Shop)
+                .with_system(buy_items)
+                .with_system(sell_items)
+            )
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShopOffer {
+    pub item: Item,
+    pub price: u32,
+    /// the seasons the offer is in stock, every season if it is empty
+    #[serde(default)]
+    pub seasons: Vec<Season>,
+    /// the days of the season the offer is in stock, every day if it is empty
+    #[serde(default)]
+    pub days: Vec<u32>,
+    /// how many can be bought per day, no limit if it is `None`
+    #[serde(default)]
+    pub daily_limit: Option<u32>
+}
+
+impl ShopOffer {
+    pub fn in_stock(&self, calendar: &Calendar) -> bool {
+        (self.seasons.is_empty() || self.seasons.contains(&calendar.season))
+            && (self.days.is_empty() || self.days.contains(&calendar.day))
+    }
+
+    /// tool upgrades are only available for the next level of the tool in `inventory`, everything else always is
+    pub fn available(&self, inventory: &Inventory) -> bool {
+        match self.item {
+            Item::Tool(tool) => inventory.tool(tool.kind).and_then(|owned| owned.upgraded()) == Some(tool),
+            _ => true
+        }
+    }
+
+    /// how many more can be bought today, `None` if there is no limit
+    pub fn remaining(&self, purchases: &ShopPurchases) -> Option<u32> {
+        self.daily_limit.map(|limit| limit.saturating_sub(purchases.0.get(&self.item).copied().unwrap_or(0)))
+    }
+}
+
+/// everything the shop can sell, read from `assets/data/shop.rson`
+#[derive(Debug, Resource, Default, Serialize, Deserialize)]
+pub struct ShopCatalog {
+    pub offers: Vec<ShopOffer>
+}
+
+impl ShopCatalog {
+    /// the shop is empty if the file is missing or invalid
+    pub fn read() -> Self {
+        match read_to_string(SHOP_CATALOG_PATH) {
+            Ok(catalog) => ron::from_str::<ShopCatalog>(&catalog.as_str()).unwrap_or_else(|err| {
+                log::warn!("invalid shop catalog, the shop will be empty: {}", err);
+                ShopCatalog::default()
+            }),
+            Err(_) => {
+                log::warn!("no shop catalog at {}, the shop will be empty", SHOP_CATALOG_PATH);
+                ShopCatalog::default()
+            }
+        }
+    }
+
+    /// the index and offer of everything in stock on the `calendar` day
+    pub fn stock<'a>(&'a self, calendar: &'a Calendar) -> impl Iterator<Item = (usize, &'a ShopOffer)> + 'a {
+        self.offers.iter()
+            .enumerate()
+            .filter(move |(_, offer)| offer.in_stock(calendar))
+    }
+}
+
+/// how many of each item was bought today, cleared every morning
+#[derive(Debug, Resource, Default, Clone)]
+pub struct ShopPurchases(pub HashMap<Item, u32>);
+
+/// buys one of the offer at the index in the `ShopCatalog`
+#[derive(Debug, Clone, Copy)]
+pub struct BuyEvent(pub usize);
+
+/// sells the whole stack in an inventory slot
+#[derive(Debug, Clone, Copy)]
+pub struct SellEvent(pub usize);
+
+#[derive(Component, Debug, Clone, Default)]
+pub struct Shop;
+
+#[derive(Clone, Bundle)]
+pub struct LdtkShop {
+    #[bundle]
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    pub shop: Shop,
+    pub interactable: Interactable,
+    pub secondary_perspective_body: SecondaryPerspectiveBody,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkShop {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            _: &LayerInstance,
+            tileset: Option<&Handle<Image>>,
+            tileset_definition: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            texture_atlases: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        // entities without a tile in LDtk are left invisible
+        let sprite_sheet_bundle = if tileset.is_some() && tileset_definition.is_some() {
+            sprite_sheet_bundle_from_entity_info(entity_instance, tileset, tileset_definition, texture_atlases, false)
+        }
+        else {
+            SpriteSheetBundle::default()
+        };
+
+        Self {
+            sprite_sheet_bundle,
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(entity_instance.width as f32 / 2., entity_instance.height as f32 / 2.),
+                rigid_body: RigidBody::Fixed,
+                ..Default::default()
+            },
+            shop: Shop,
+            interactable: Interactable::new(SHOP_PROMPT, 1, InteractionHandler::Shop),
+            secondary_perspective_body: SecondaryPerspectiveBody,
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tool::{Tool, ToolKind};
+    use super::*;
+
+    fn offer(item: Item) -> ShopOffer {
+        ShopOffer {
+            item,
+            price: 500,
+            seasons: Vec::new(),
+            days: Vec::new(),
+            daily_limit: None
+        }
+    }
+
+    #[test]
+    fn tool_upgrades_follow_the_owned_tool() {
+        let copper = offer(Item::Tool(Tool { kind: ToolKind::Hoe, level: 1 }));
+        let iron = offer(Item::Tool(Tool { kind: ToolKind::Hoe, level: 2 }));
+        let mut inventory = Inventory::starting();
+        assert!(copper.available(&inventory));
+        assert!(!iron.available(&inventory));
+
+        inventory.upgrade_tool(ToolKind::Hoe);
+        assert!(!copper.available(&inventory));
+        assert!(iron.available(&inventory));
+
+        inventory.upgrade_tool(ToolKind::Hoe);
+        assert!(!copper.available(&inventory));
+        assert!(!iron.available(&inventory));
+    }
+
+    #[test]
+    fn upgrades_need_the_tool() {
+        let inventory = Inventory::default();
+        assert!(!offer(Item::Tool(Tool { kind: ToolKind::Axe, level: 1 })).available(&inventory));
+        assert!(offer(Item::Fertilizer).available(&inventory));
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::{prelude::*, log};
+use crate::{GameState, player::Player, item::Inventory, economy::{Wallet, CropMarket}, calendar::{Calendar, NewDayEvent}};
+
+use super::*;
+
+pub fn open_shop(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut state: ResMut<State<GameState>>
+) {
+    if interaction_events.iter().any(|event| event.handler == InteractionHandler::Shop) {
+        state.overwrite_set(GameState::LoadingShop).unwrap();
+    }
+}
+
+/// daily limits reset every morning
+pub fn restock_shop(
+    mut new_day_events: EventReader<NewDayEvent>,
+    mut purchases: ResMut<ShopPurchases>
+) {
+    if new_day_events.iter().count() > 0 {
+        purchases.0.clear();
+    }
+}
+
+pub fn buy_items(
+    mut buy_events: EventReader<BuyEvent>,
+    mut player_query: Query<(&mut Inventory, &mut Wallet), With<Player>>,
+    catalog: Res<ShopCatalog>,
+    calendar: Res<Calendar>,
+    mut purchases: ResMut<ShopPurchases>
+) {
+    for BuyEvent(index) in buy_events.iter() {
+        let offer = match catalog.offers.get(*index) {
+            Some(offer) if offer.in_stock(&calendar) => offer,
+            _ => continue
+        };
+        if offer.remaining(&purchases) == Some(0) {
+            continue;
+        }
+        let (mut inventory, mut wallet) = player_query.single_mut();
+        if !offer.available(&inventory) {
+            continue;
+        }
+        // upgrades replace the owned tool so they don't need a free slot
+        let upgrade = match offer.item {
+            Item::Tool(tool) => Some(tool.kind),
+            _ => None
+        };
+        if upgrade.is_none() && !inventory.can_add(&offer.item, 1) {
+            log::info!("no room in the inventory for {}", offer.item.name());
+            continue;
+        }
+        if !wallet.spend(offer.price) {
+            log::info!("not enough coins for {}", offer.item.name());
+            continue;
+        }
+        match upgrade {
+            Some(kind) => {
+                inventory.upgrade_tool(kind);
+            },
+            None => {
+                inventory.add(offer.item.clone(), 1);
+            }
+        }
+        *purchases.0.entry(offer.item.clone()).or_insert(0) += 1;
+        log::info!("bought {} for {} coins", offer.item.name(), offer.price);
+    }
+}
+
+/// the shop pays straight away unlike the shipping bin, crops still go through the `CropMarket`
+pub fn sell_items(
+    mut sell_events: EventReader<SellEvent>,
+    mut player_query: Query<(&mut Inventory, &mut Wallet), With<Player>>,
+    mut crop_market: ResMut<CropMarket>,
+    calendar: Res<Calendar>
+) {
+    for SellEvent(slot) in sell_events.iter() {
+        let (mut inventory, mut wallet) = player_query.single_mut();
+        let count = match inventory.get(*slot) {
+            Some(stack) if stack.item.sell_price().is_some() => stack.count,
+            _ => continue
+        };
+        if let Some(sold) = inventory.remove_from_slot(*slot, count) {
+            let earned = crop_market.sell(&sold.item, sold.count, calendar.total_days()).unwrap_or(0);
+            wallet.earn(earned);
+            log::info!("sold {} {} for {} coins", sold.count, sold.item.name(), earned);
+        }
+    }
+}
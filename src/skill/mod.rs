@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use crate::{GameState, item::Quality, tool::ToolKind, crop::CROP_KILL_CHANCE, energy::DEFAULT_MAX_ENERGY};
+
+use self::systems::*;
+
+pub mod systems;
+
+/// the experience needed to reach each level, a skill with no experience is level 0
+pub const SKILL_LEVEL_THRESHOLDS: [u32; 5] = [100, 380, 770, 1300, 2150];
+pub const HARVEST_EXPERIENCE: u32 = 10;
+pub const TOOL_USE_EXPERIENCE: u32 = 2;
+pub const BREAK_EXPERIENCE: u32 = 15;
+/// extra max energy every `Perk::Stamina` gives
+pub const STAMINA_PERK_ENERGY: f32 = 20.;
+/// the chance out of 100 that a ripe crop is at least silver with `Perk::SilverCrops`
+pub const SILVER_CROP_CHANCE: u32 = 30;
+/// the chance out of 100 that a ripe crop is gold with `Perk::GoldCrops`
+pub const GOLD_CROP_CHANCE: u32 = 15;
+
+/// which skill reaching which level unlocks a perk, a perk unlocked by more than one skill stacks
+pub const SKILL_PERKS: [(Skill, u32, Perk); 5] = [
+    (Skill::Farming, 2, Perk::CarefulSteps),
+    (Skill::Farming, 3, Perk::SilverCrops),
+    (Skill::Farming, 5, Perk::GoldCrops),
+    (Skill::Foraging, 2, Perk::Stamina),
+    (Skill::Mining, 2, Perk::Stamina),
+];
+
+/// **INFO** plugin for the players skills
+///
+/// Send an `ExperienceEvent` to give the player experience in a skill, tool uses give experience on their own. Levels are reached at
+/// `SKILL_LEVEL_THRESHOLDS` and unlock the perks in `SKILL_PERKS`
+///
+/// **NOTE** a `LevelUpEvent` is sent every time a skill reaches a new level
+pub struct SkillPlugin;
+
+impl Plugin for SkillPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ExperienceEvent>()
+            .add_event::<LevelUpEvent>()
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(tool_experience.before("gain_experience"))
+                .with_system(gain_experience.label("gain_experience"))
+            )
+            .add_system(apply_energy_perks)
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Skill {
+    Farming,
+    Foraging,
+    Mining
+}
+
+impl Skill {
+    pub const ALL: [Skill; 3] = [
+        Skill::Farming,
+        Skill::Foraging,
+        Skill::Mining,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::Farming => "Farming",
+            Skill::Foraging => "Foraging",
+            Skill::Mining => "Mining"
+        }
+    }
+
+    /// the skill using a tool trains
+    pub fn for_tool(kind: ToolKind) -> Self {
+        match kind {
+            ToolKind::Axe => Skill::Foraging,
+            ToolKind::Pickaxe => Skill::Mining,
+            _ => Skill::Farming
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perk {
+    /// halves the chance of trampling a crop
+    CarefulSteps,
+    SilverCrops,
+    GoldCrops,
+    /// raises the players max energy by `STAMINA_PERK_ENERGY`
+    Stamina
+}
+
+impl Perk {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Perk::CarefulSteps => "Careful Steps",
+            Perk::SilverCrops => "Silver Crops",
+            Perk::GoldCrops => "Gold Crops",
+            Perk::Stamina => "Stamina"
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Skills {
+    pub experience: HashMap<Skill, u32>
+}
+
+impl Skills {
+    pub fn experience(&self, skill: Skill) -> u32 {
+        self.experience.get(&skill).copied().unwrap_or(0)
+    }
+
+    pub fn level(&self, skill: Skill) -> u32 {
+        let experience = self.experience(skill);
+        SKILL_LEVEL_THRESHOLDS.iter().filter(|threshold| experience >= **threshold).count() as u32
+    }
+
+    /// the experience the next level needs, `None` once the skill is maxed out
+    pub fn next_level_experience(&self, skill: Skill) -> Option<u32> {
+        SKILL_LEVEL_THRESHOLDS.get(self.level(skill) as usize).copied()
+    }
+
+    /// returns the new level if the skill levelled up
+    pub fn add_experience(&mut self, skill: Skill, amount: u32) -> Option<u32> {
+        let level = self.level(skill);
+        *self.experience.entry(skill).or_insert(0) += amount;
+        let new_level = self.level(skill);
+        if new_level > level {
+            return Some(new_level);
+        }
+        None
+    }
+
+    /// every unlocked perk, perks unlocked by more than one skill show up more than once
+    pub fn perks(&self) -> impl Iterator<Item = Perk> + '_ {
+        SKILL_PERKS.iter()
+            .filter(|(skill, level, _)| self.level(*skill) >= *level)
+            .map(|(_, _, perk)| *perk)
+    }
+
+    pub fn has_perk(&self, perk: Perk) -> bool {
+        self.perks().any(|unlocked| unlocked == perk)
+    }
+
+    pub fn max_energy(&self) -> f32 {
+        DEFAULT_MAX_ENERGY + self.perks().filter(|perk| *perk == Perk::Stamina).count() as f32 * STAMINA_PERK_ENERGY
+    }
+
+    /// the chance out of 100 that stepping on a crop tramples it
+    pub fn trample_chance(&self) -> i32 {
+        if self.has_perk(Perk::CarefulSteps) {
+            return CROP_KILL_CHANCE / 2;
+        }
+        CROP_KILL_CHANCE
+    }
+
+    pub fn roll_crop_quality(&self, rng: &mut impl Rng) -> Quality {
+        let roll = rng.gen_range(0..100);
+        if self.has_perk(Perk::GoldCrops) && roll < GOLD_CROP_CHANCE {
+            return Quality::Gold;
+        }
+        if self.has_perk(Perk::SilverCrops) && roll < SILVER_CROP_CHANCE {
+            return Quality::Silver;
+        }
+        Quality::Normal
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExperienceEvent {
+    pub skill: Skill,
+    pub amount: u32
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LevelUpEvent {
+    pub skill: Skill,
+    pub level: u32
+}
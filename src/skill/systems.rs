@@ -0,0 +1,47 @@
+use bevy::{prelude::*, log};
+use crate::{player::Player, tool::ToolUsedEvent, energy::Energy};
+
+use super::*;
+
+pub fn gain_experience(
+    mut experience_events: EventReader<ExperienceEvent>,
+    mut player_query: Query<&mut Skills, With<Player>>,
+    mut level_up_events: EventWriter<LevelUpEvent>
+) {
+    for event in experience_events.iter() {
+        let mut skills = player_query.single_mut();
+        if let Some(level) = skills.add_experience(event.skill, event.amount) {
+            log::info!("{} reached level {}", event.skill.name(), level);
+            level_up_events.send(LevelUpEvent {
+                skill: event.skill,
+                level
+            });
+        }
+    }
+}
+
+/// every tool use trains the skill the tool belongs to
+pub fn tool_experience(
+    mut tool_used_events: EventReader<ToolUsedEvent>,
+    mut experience_events: EventWriter<ExperienceEvent>
+) {
+    for event in tool_used_events.iter() {
+        experience_events.send(ExperienceEvent {
+            skill: Skill::for_tool(event.tool.kind),
+            amount: TOOL_USE_EXPERIENCE
+        });
+    }
+}
+
+/// keeps the players max energy in line with their stamina perks, also runs when a save is loaded
+pub fn apply_energy_perks(
+    mut player_query: Query<(&Skills, &mut Energy), (With<Player>, Changed<Skills>)>
+) {
+    for (skills, mut energy) in player_query.iter_mut() {
+        let max_energy = skills.max_energy();
+        if energy.max != max_energy {
+            energy.max = max_energy;
+            energy.current = energy.current.min(max_energy);
+        }
+    }
+}
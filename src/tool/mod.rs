@@ -0,0 +1,264 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::sprite_sheet_bundle_from_entity_info};
+use bevy_rapier2d::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::{GameState, ldtk::*, item::Item, mechanics::perspective::SecondaryPerspectiveBody, level::DEFAULT_GRID_SIZE};
+
+use self::systems::*;
+
+pub mod systems;
+
+/// the highest upgrade level a tool can reach
+pub const TOOL_MAX_LEVEL: u8 = 2;
+/// where the tool sprite sits relative to the player for each direction the player can face
+pub const TOOL_SPRITE_OFFSET_SIDE: Vec3 = Vec3::new(10., -6., 0.1);
+pub const TOOL_SPRITE_OFFSET_UP: Vec3 = Vec3::new(4., 2., -0.1);
+pub const TOOL_SPRITE_OFFSET_DOWN: Vec3 = Vec3::new(-4., -10., 0.1);
+
+pub struct ToolPlugin;
+
+impl Plugin for ToolPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .register_ldtk_entity::<LdtkBreakable>("Breakable")
+            .add_event::<ToolUsedEvent>()
+            .insert_resource(BrokenBreakables::default())
+            .add_system_set(SystemSet::on_enter(GameState::LoadingGame)
+                .with_system(spawn_tool_sprite)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(cycle_equipped_tool)
+                // finishing runs first so a tool use is only finished the frame after its animation started
+                .with_system(finish_tool_use.label("finish_tool_use"))
+                .with_system(start_tool_use.after("finish_tool_use"))
+                .with_system(break_breakables.after("finish_tool_use"))
+            )
+            .add_system(remove_broken_breakables)
+        ;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ToolKind {
+    Hoe,
+    WateringCan,
+    Axe,
+    Pickaxe,
+    Scythe
+}
+
+impl ToolKind {
+    pub const ALL: [ToolKind; 5] = [
+        ToolKind::Hoe,
+        ToolKind::WateringCan,
+        ToolKind::Axe,
+        ToolKind::Pickaxe,
+        ToolKind::Scythe,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolKind::Hoe => "Hoe",
+            ToolKind::WateringCan => "Watering Can",
+            ToolKind::Axe => "Axe",
+            ToolKind::Pickaxe => "Pickaxe",
+            ToolKind::Scythe => "Scythe"
+        }
+    }
+
+    /// the blocking player animation played while using the tool
+    pub fn animation_name(&self) -> &'static str {
+        match self {
+            ToolKind::Hoe => "player_hoeing",
+            ToolKind::WateringCan => "player_watering",
+            ToolKind::Axe => "player_chopping",
+            ToolKind::Pickaxe => "player_mining",
+            ToolKind::Scythe => "player_scything"
+        }
+    }
+
+    /// the animation of the tool itself from `hoe_and_axe.png`, not every tool has one yet
+    pub fn sprite_animation_name(&self) -> Option<&'static str> {
+        match self {
+            ToolKind::Hoe => Some("hoe_swing"),
+            ToolKind::Axe | ToolKind::Pickaxe => Some("axe_swing"),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Tool {
+    pub kind: ToolKind,
+    /// every level widens the area the tool affects
+    pub level: u8
+}
+
+impl Tool {
+    pub fn new(kind: ToolKind) -> Self {
+        Self {
+            kind,
+            level: 0
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self.level {
+            0 => self.kind.name().to_string(),
+            1 => format!("Copper {}", self.kind.name()),
+            _ => format!("Iron {}", self.kind.name())
+        }
+    }
+
+    pub fn upgraded(&self) -> Option<Self> {
+        if self.level >= TOOL_MAX_LEVEL {
+            return None;
+        }
+        Some(Self {
+            kind: self.kind,
+            level: self.level + 1
+        })
+    }
+
+    /// the grid cells the tool affects when used on `target` while facing `facing`
+    ///
+    /// level 0 is just the target, level 1 is a row of three across the facing direction and level 2 is a 3x3 square in front of the player
+    pub fn area(&self, target: IVec2, facing: IVec2) -> Vec<IVec2> {
+        let across = IVec2::new(facing.y.abs(), facing.x.abs());
+        let (width, depth) = match self.level {
+            0 => (0, 1),
+            1 => (1, 1),
+            _ => (1, 3)
+        };
+        let mut cells = Vec::new();
+        for forward in 0..depth {
+            for side in -width..=width {
+                cells.push(target + facing * forward + across * side);
+            }
+        }
+        cells
+    }
+
+    /// how many hits a use of the tool deals to a `Breakable`
+    pub fn power(&self) -> u32 {
+        self.level as u32 + 1
+    }
+}
+
+/// the inventory slot of the tool the player uses with the use tool action
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct EquippedTool(pub Option<usize>);
+
+/// a tool that was swung but whose animation hasn't finished yet, the tool only affects the world once it has
+#[derive(Component, Debug, Clone, Default)]
+pub struct PendingToolUse(pub Option<ToolUsedEvent>);
+
+/// sent once a tool animation finishes, every system that cares about a tool handles the cells it affects
+#[derive(Debug, Clone)]
+pub struct ToolUsedEvent {
+    pub tool: Tool,
+    pub cells: Vec<IVec2>,
+    pub grid_size: i32
+}
+
+/// the tool drawn in the players hands
+#[derive(Component)]
+pub struct ToolSprite;
+
+/// Trees, rocks and anything else placed in LDtk that an axe or pickaxe can break
+///
+/// the LDtk entity has a `tool` enum field (Axe or Pickaxe), an int `hits` field and a `drop` enum field (Wood or Stone)
+#[derive(Component, Debug, Clone)]
+pub struct Breakable {
+    pub tool: ToolKind,
+    pub hits: u32,
+    pub drop: Option<Item>,
+    pub iid: String,
+    pub grid_size: i32
+}
+
+impl Default for Breakable {
+    fn default() -> Self {
+        Self {
+            tool: ToolKind::Axe,
+            hits: 3,
+            drop: None,
+            iid: String::new(),
+            grid_size: DEFAULT_GRID_SIZE
+        }
+    }
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct LdtkBreakable {
+    #[bundle]
+    pub sprite_sheet_bundle: SpriteSheetBundle,
+    #[bundle]
+    pub collider_bundle: ColliderBundle,
+    pub breakable: Breakable,
+    pub secondary_perspective_body: SecondaryPerspectiveBody,
+    entity_instance: EntityInstance,
+    pub ldtk: Ldtk
+}
+
+impl LdtkEntity for LdtkBreakable {
+    fn bundle_entity(
+            entity_instance: &EntityInstance,
+            layer_instance: &LayerInstance,
+            tileset: Option<&Handle<Image>>,
+            tileset_definition: Option<&TilesetDefinition>,
+            _: &AssetServer,
+            texture_atlases: &mut Assets<TextureAtlas>,
+        ) -> Self {
+        let mut breakable = Breakable {
+            iid: entity_instance.iid.clone(),
+            grid_size: layer_instance.grid_size,
+            ..Default::default()
+        };
+
+        for field in entity_instance.field_instances.iter() {
+            match (field.identifier.as_str(), &field.value) {
+                ("tool", FieldValue::Enum(Some(tool))) => {
+                    breakable.tool = match tool.as_str() {
+                        "Pickaxe" => ToolKind::Pickaxe,
+                        _ => ToolKind::Axe
+                    };
+                },
+                ("hits", FieldValue::Int(Some(hits))) => breakable.hits = (*hits).max(1) as u32,
+                ("drop", FieldValue::Enum(Some(drop))) => {
+                    breakable.drop = match drop.as_str() {
+                        "Wood" => Some(Item::Wood),
+                        "Stone" => Some(Item::Stone),
+                        _ => None
+                    };
+                },
+                _ => {}
+            }
+        }
+
+        // entities without a tile in LDtk are left invisible
+        let sprite_sheet_bundle = if tileset.is_some() && tileset_definition.is_some() {
+            sprite_sheet_bundle_from_entity_info(entity_instance, tileset, tileset_definition, texture_atlases, false)
+        }
+        else {
+            SpriteSheetBundle::default()
+        };
+
+        Self {
+            sprite_sheet_bundle,
+            collider_bundle: ColliderBundle {
+                collider: Collider::cuboid(entity_instance.width as f32 / 2., entity_instance.height as f32 / 4.),
+                rigid_body: RigidBody::Fixed,
+                ..Default::default()
+            },
+            breakable,
+            secondary_perspective_body: SecondaryPerspectiveBody,
+            entity_instance: entity_instance.clone(),
+            ldtk: Ldtk
+        }
+    }
+}
+
+/// the iids of every breakable that has been broken so it doesn't come back when its level is spawned again
+#[derive(Debug, Resource, Default)]
+pub struct BrokenBreakables(pub Vec<String>);
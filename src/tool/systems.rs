@@ -0,0 +1,214 @@
+use bevy::{prelude::*, log};
+use crate::{bevy_animations::*, player::{Player, PLAYER_FOOT_OFFSET}, item::*, input::*, crop::grid_coords, load_atlases::Atlases, animations::{TOOL_SPRITE_FRAMES, TOOL_SPRITE_TIMINGS}, mechanics::interaction::Facing, level::{CurrentLevel, LevelTransition}, energy::Energy, skill::*, placement::Placement};
+
+use super::*;
+
+pub fn spawn_tool_sprite(
+    mut commands: Commands,
+    player_query: Query<Entity, With<Player>>,
+    atlases: Res<Atlases>,
+    mut animations: ResMut<Animations>
+) {
+    let player_entity = player_query.single();
+    let tool_handle = atlases.handles.get("hoe_and_axe").unwrap();
+
+    let mut tool_sprite_entity = None;
+    commands.entity(player_entity).with_children(|children| {
+        tool_sprite_entity = Some(children.spawn(SpriteSheetBundle {
+            texture_atlas: tool_handle.clone(),
+            visibility: Visibility::INVISIBLE,
+            ..Default::default()
+        })
+            .insert(AnimationDirection::default())
+            .insert(ToolSprite)
+            .id()
+        );
+    });
+    let tool_sprite_entity = tool_sprite_entity.unwrap();
+
+    // the first row of the sheet is the hoe and the second row is the axe
+    for (row, name) in [(0, "hoe_swing"), (1, "axe_swing")] {
+        animations.insert_animation(
+            tool_sprite_entity,
+            AnimationType::LinearTimed(
+                LinearTimedAnimation::new(
+                    TOOL_SPRITE_FRAMES.iter().map(|frame| frame + row * TOOL_SPRITE_FRAMES.len()).collect(),
+                    Vec::from(TOOL_SPRITE_TIMINGS),
+                    tool_handle.clone(),
+                    false
+                ),
+                name
+            )
+        );
+    }
+}
+
+/// moves the equipped tool to the next tool in the inventory
+pub fn cycle_equipped_tool(
+    action_input: Res<ActionInput>,
+    mut player_query: Query<(&Inventory, &mut EquippedTool), With<Player>>
+) {
+    if !action_input.just_pressed(Action::NextTool) {
+        return;
+    }
+    let (inventory, mut equipped_tool) = player_query.single_mut();
+    let start = equipped_tool.0.map(|index| index + 1).unwrap_or(0);
+    let slots = inventory.slots.len();
+
+    equipped_tool.0 = (0..slots)
+        .map(|offset| (start + offset) % slots)
+        .find(|index| matches!(inventory.get(*index), Some(ItemStack { item: Item::Tool(_), .. })));
+
+    if let Some(ItemStack { item, .. }) = equipped_tool.0.and_then(|index| inventory.get(index)) {
+        log::info!("equipped {}", item.name());
+    }
+}
+
+/// the equipped tool, falling back to the first tool in the inventory if the equipped slot doesn't hold one anymore
+fn equipped(inventory: &Inventory, equipped_tool: &mut EquippedTool) -> Option<Tool> {
+    if let Some(ItemStack { item: Item::Tool(tool), .. }) = equipped_tool.0.and_then(|index| inventory.get(index)) {
+        return Some(*tool);
+    }
+    equipped_tool.0 = None;
+    for (index, slot) in inventory.slots.iter().enumerate() {
+        if let Some(ItemStack { item: Item::Tool(tool), .. }) = slot {
+            equipped_tool.0 = Some(index);
+            return Some(*tool);
+        }
+    }
+    None
+}
+
+/// the grid direction closest to the way the player is facing
+pub fn facing_cell(facing: Vec2) -> IVec2 {
+    if facing.x.abs() >= facing.y.abs() {
+        IVec2::new(facing.x.signum() as i32, 0)
+    }
+    else {
+        IVec2::new(0, facing.y.signum() as i32)
+    }
+}
+
+pub fn start_tool_use(
+    action_input: Res<ActionInput>,
+    mut player_query: Query<(Entity, &Player, &Transform, &Facing, &Inventory, &Energy, &mut EquippedTool, &mut PendingToolUse)>,
+    mut tool_sprite_query: Query<(Entity, &mut Transform, &mut TextureAtlasSprite, &mut Visibility), (With<ToolSprite>, Without<Player>)>,
+    animations: Res<Animations>,
+    mut animation_event: EventWriter<AnimationEvent>,
+    current_level: Res<CurrentLevel>,
+    transition: Res<LevelTransition>,
+    placement: Res<Placement>
+) {
+    // the tool action places objects in placement mode
+    if !action_input.just_pressed(Action::UseTool) || !transition.is_idle() || placement.0.is_some() {
+        return;
+    }
+    let (player_entity, player, player_transform, facing, inventory, energy, mut equipped_tool, mut pending) = player_query.single_mut();
+
+    if player.harvesting || pending.0.is_some() || animations.in_blocking_animation(player_entity) != Some(false) {
+        return;
+    }
+    let tool = match equipped(inventory, &mut equipped_tool) {
+        Some(tool) => tool,
+        None => return
+    };
+    if energy.is_exhausted() {
+        log::info!("too tired to use the {}", tool.name());
+        return;
+    }
+
+    let grid_size = current_level.grid_size();
+    let facing = facing_cell(facing.0);
+    let feet = player_transform.translation + PLAYER_FOOT_OFFSET;
+    let target = grid_coords(feet, grid_size) + facing;
+
+    pending.0 = Some(ToolUsedEvent {
+        tool,
+        cells: tool.area(target, facing),
+        grid_size
+    });
+    animation_event.send(AnimationEvent(tool.kind.animation_name(), player_entity));
+
+    if let Some(name) = tool.kind.sprite_animation_name() {
+        if let Ok((tool_sprite_entity, mut transform, mut sprite, mut visibility)) = tool_sprite_query.get_single_mut() {
+            transform.translation = match facing {
+                IVec2 { x: 1, .. } => TOOL_SPRITE_OFFSET_SIDE,
+                IVec2 { x: -1, .. } => TOOL_SPRITE_OFFSET_SIDE * Vec3::new(-1., 1., 1.),
+                IVec2 { y: 1, .. } => TOOL_SPRITE_OFFSET_UP,
+                _ => TOOL_SPRITE_OFFSET_DOWN
+            };
+            // the sheet is drawn facing right
+            sprite.flip_x = facing.x < 0;
+            *visibility = Visibility::VISIBLE;
+            animation_event.send(AnimationEvent(name, tool_sprite_entity));
+        }
+    }
+}
+
+/// applies a pending tool use once the players tool animation is over
+pub fn finish_tool_use(
+    mut player_query: Query<(Entity, &mut PendingToolUse), With<Player>>,
+    mut tool_sprite_query: Query<&mut Visibility, With<ToolSprite>>,
+    animations: Res<Animations>,
+    mut animation_event: EventWriter<AnimationEvent>,
+    mut tool_used_events: EventWriter<ToolUsedEvent>
+) {
+    let (player_entity, mut pending) = player_query.single_mut();
+    if pending.0.is_none() || animations.in_blocking_animation(player_entity) != Some(false) {
+        return;
+    }
+    let tool_use = pending.0.take().unwrap();
+    log::info!("used {} on {} cells", tool_use.tool.name(), tool_use.cells.len());
+    tool_used_events.send(tool_use);
+
+    if let Ok(mut visibility) = tool_sprite_query.get_single_mut() {
+        *visibility = Visibility::INVISIBLE;
+    }
+    animation_event.send(AnimationEvent("player_walking", player_entity));
+}
+
+pub fn break_breakables(
+    mut commands: Commands,
+    mut tool_used_events: EventReader<ToolUsedEvent>,
+    mut breakable_query: Query<(Entity, &mut Breakable, &GlobalTransform)>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut broken: ResMut<BrokenBreakables>,
+    mut experience_events: EventWriter<ExperienceEvent>
+) {
+    for event in tool_used_events.iter() {
+        for (entity, mut breakable, transform) in breakable_query.iter_mut() {
+            if breakable.tool != event.tool.kind || !event.cells.contains(&grid_coords(transform.translation(), breakable.grid_size)) {
+                continue;
+            }
+            breakable.hits = breakable.hits.saturating_sub(event.tool.power());
+            if breakable.hits > 0 {
+                continue;
+            }
+            if let Some(drop) = breakable.drop.clone() {
+                let mut inventory = player_query.single_mut();
+                if inventory.add(drop.clone(), 1) > 0 {
+                    log::warn!("no room in the inventory for {}", drop.name());
+                }
+            }
+            experience_events.send(ExperienceEvent {
+                skill: Skill::for_tool(breakable.tool),
+                amount: BREAK_EXPERIENCE
+            });
+            broken.0.push(breakable.iid.clone());
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// breakables are spawned again with their level so the ones that were already broken have to be removed
+pub fn remove_broken_breakables(
+    mut commands: Commands,
+    breakable_query: Query<(Entity, &Breakable), Added<Breakable>>,
+    broken: Res<BrokenBreakables>
+) {
+    for (entity, breakable) in breakable_query.iter() {
+        if broken.0.contains(&breakable.iid) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
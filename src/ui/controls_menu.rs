@@ -0,0 +1,235 @@
+use bevy::{prelude::*, log};
+use crate::input::*;
+
+use super::{*, colors::*};
+
+pub const CONTROLS_ROW_WIDTH: f32 = 600.;
+pub const CONTROLS_ROW_HEIGHT: f32 = 45.;
+
+#[derive(Component)]
+pub struct ControlsMenu;
+
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub enum ControlsButton {
+    Rebind(Action),
+    RunMode,
+    ClickToMove,
+    Reset,
+    Back
+}
+
+/// the text showing what an action is currently bound to
+#[derive(Component)]
+pub struct BindingText(pub Action);
+
+#[derive(Component)]
+pub struct RunModeText;
+
+#[derive(Component)]
+pub struct ClickToMoveText;
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled { "On" } else { "Off" }
+}
+
+/// the action waiting on the next key or gamepad button press
+#[derive(Debug, Resource, Default)]
+pub struct Rebinding(pub Option<Action>);
+
+pub fn controls_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    input_settings: Res<InputSettings>,
+    mut state: ResMut<State<GameState>>
+) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 25.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    let row_button = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(CONTROLS_ROW_WIDTH), Val::Px(CONTROLS_ROW_HEIGHT)),
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::horizontal(Val::Px(15.)),
+            margin: UiRect::vertical(Val::Px(5.)),
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ControlsMenu)
+        .with_children(|parent| {
+            for action in Action::ALL {
+                parent
+                    .spawn(row_button.clone())
+                    .insert(ControlsButton::Rebind(action))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(action.name(), text_style.clone()));
+                        parent
+                            .spawn(TextBundle::from_section(input_settings.binding(action).describe(), text_style.clone()))
+                            .insert(BindingText(action))
+                        ;
+                    })
+                ;
+            }
+            parent
+                .spawn(row_button.clone())
+                .insert(ControlsButton::RunMode)
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Run Mode", text_style.clone()));
+                    parent
+                        .spawn(TextBundle::from_section(format!("{:?}", input_settings.run_mode), text_style.clone()))
+                        .insert(RunModeText)
+                    ;
+                })
+            ;
+            parent
+                .spawn(row_button.clone())
+                .insert(ControlsButton::ClickToMove)
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Click To Move", text_style.clone()));
+                    parent
+                        .spawn(TextBundle::from_section(on_off(input_settings.click_to_move), text_style.clone()))
+                        .insert(ClickToMoveText)
+                    ;
+                })
+            ;
+            parent
+                .spawn(row_button.clone())
+                .insert(ControlsButton::Reset)
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Reset To Defaults", text_style.clone()));
+                })
+            ;
+            parent
+                .spawn(row_button.clone())
+                .insert(ControlsButton::Back)
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Back", text_style.clone()));
+                })
+            ;
+        })
+    ;
+    state.overwrite_set(GameState::Controls).unwrap();
+}
+
+pub fn controls_button_system(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ControlsButton),
+        (Changed<Interaction>, With<Button>)
+    >,
+    controls_menu_query: Query<Entity, With<ControlsMenu>>,
+    mut input_settings: ResMut<InputSettings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut state: ResMut<State<GameState>>
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                match *button {
+                    ControlsButton::Rebind(action) => {
+                        rebinding.0 = Some(action);
+                    },
+                    ControlsButton::RunMode => {
+                        input_settings.run_mode = match input_settings.run_mode {
+                            RunMode::Hold => RunMode::Toggle,
+                            RunMode::Toggle => RunMode::Hold
+                        };
+                        input_settings.write();
+                    },
+                    ControlsButton::ClickToMove => {
+                        input_settings.click_to_move = !input_settings.click_to_move;
+                        input_settings.write();
+                    },
+                    ControlsButton::Reset => {
+                        *input_settings = InputSettings::default();
+                        input_settings.write();
+                    },
+                    ControlsButton::Back => {
+                        rebinding.0 = None;
+                        commands.entity(controls_menu_query.single()).despawn_recursive();
+                        state.overwrite_set(GameState::LoadingPause).unwrap();
+                    }
+                }
+            },
+            Interaction::Hovered => {
+                *color = NORMAL_BUTTON_HOVER.into();
+            },
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+/// binds the next key or gamepad button that is pressed to the action being rebound, replacing its old binding of that kind
+pub fn capture_rebinding(
+    mut rebinding: ResMut<Rebinding>,
+    mut input_settings: ResMut<InputSettings>,
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>
+) {
+    let action = match rebinding.0 {
+        Some(action) => action,
+        None => return
+    };
+
+    if let Some(key) = keys.get_just_pressed().next() {
+        log::info!("bound {:?} to {}", key, action.name());
+        input_settings.bindings.entry(action).or_default().keys = vec![*key];
+    }
+    else if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        log::info!("bound {:?} to {}", button.button_type, action.name());
+        input_settings.bindings.entry(action).or_default().gamepad_buttons = vec![button.button_type];
+    }
+    else {
+        return;
+    }
+
+    input_settings.write();
+    rebinding.0 = None;
+}
+
+pub fn update_binding_texts(
+    input_settings: Res<InputSettings>,
+    rebinding: Res<Rebinding>,
+    mut binding_text_query: Query<(&mut Text, &BindingText), (Without<RunModeText>, Without<ClickToMoveText>)>,
+    mut run_mode_text_query: Query<&mut Text, (With<RunModeText>, Without<ClickToMoveText>)>,
+    mut click_to_move_text_query: Query<&mut Text, (With<ClickToMoveText>, Without<RunModeText>)>
+) {
+    if !input_settings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    for (mut text, binding_text) in binding_text_query.iter_mut() {
+        text.sections[0].value = if rebinding.0 == Some(binding_text.0) {
+            "Press a key or button...".to_string()
+        }
+        else {
+            input_settings.binding(binding_text.0).describe()
+        };
+    }
+    for mut text in run_mode_text_query.iter_mut() {
+        text.sections[0].value = format!("{:?}", input_settings.run_mode);
+    }
+    for mut text in click_to_move_text_query.iter_mut() {
+        text.sections[0].value = on_off(input_settings.click_to_move).to_string();
+    }
+}
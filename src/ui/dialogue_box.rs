@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use crate::dialogue::{ActiveDialogue, DialogueLibrary};
+
+use super::*;
+
+pub const DIALOGUE_BOX_WIDTH: f32 = 720.;
+pub const DIALOGUE_BOX_HEIGHT: f32 = 180.;
+pub const DIALOGUE_BOX_COLOR: Color = Color::rgba(0.1, 0.07, 0.05, 0.9);
+pub const SELECTED_CHOICE_COLOR: Color = Color::rgb(1., 0.85, 0.4);
+pub const CHOICE_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+#[derive(Component)]
+pub struct DialogueBox;
+
+#[derive(Component)]
+pub struct DialogueSpeakerText;
+
+#[derive(Component)]
+pub struct DialogueLineText;
+
+/// the column the choices are spawned in
+#[derive(Component)]
+pub struct DialogueChoiceList;
+
+fn text_style(asset_server: &AssetServer, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size,
+        color
+    }
+}
+
+pub fn dialogue_box_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut state: ResMut<State<GameState>>
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                align_items: AlignItems::FlexEnd,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(DialogueBox)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(DIALOGUE_BOX_WIDTH), Val::Px(DIALOGUE_BOX_HEIGHT)),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(15.)),
+                        margin: UiRect::bottom(Val::Px(30.)),
+                        ..default()
+                    },
+                    background_color: DIALOGUE_BOX_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("", text_style(&asset_server, 22., SELECTED_CHOICE_COLOR)))
+                        .insert(DialogueSpeakerText)
+                    ;
+                    parent.spawn(TextBundle::from_section("", text_style(&asset_server, 20., CHOICE_COLOR)).with_style(Style {
+                        max_size: Size::new(Val::Px(DIALOGUE_BOX_WIDTH - 30.), Val::Undefined),
+                        margin: UiRect::vertical(Val::Px(8.)),
+                        ..default()
+                    }))
+                        .insert(DialogueLineText)
+                    ;
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                        .insert(DialogueChoiceList)
+                    ;
+                })
+            ;
+        })
+    ;
+    state.overwrite_set(GameState::Dialogue).unwrap();
+}
+
+/// shows whatever the typewriter has got to and the choices once the last line is done
+pub fn refresh_dialogue_box(
+    mut commands: Commands,
+    active: Res<ActiveDialogue>,
+    library: Res<DialogueLibrary>,
+    new_box_query: Query<(), Added<DialogueBox>>,
+    mut speaker_text_query: Query<&mut Text, (With<DialogueSpeakerText>, Without<DialogueLineText>)>,
+    mut line_text_query: Query<&mut Text, (With<DialogueLineText>, Without<DialogueSpeakerText>)>,
+    choice_list_query: Query<Entity, With<DialogueChoiceList>>,
+    asset_server: Res<AssetServer>,
+    // the choices that are currently spawned and which one was selected
+    mut shown_choices: Local<Option<(Vec<usize>, usize)>>
+) {
+    if !active.is_changed() && new_box_query.is_empty() {
+        return;
+    }
+    if let Ok(mut text) = speaker_text_query.get_single_mut() {
+        if text.sections[0].value != active.speaker {
+            text.sections[0].value = active.speaker.clone();
+        }
+    }
+    if let Ok(mut text) = line_text_query.get_single_mut() {
+        let revealed = active.revealed_text(&library);
+        if text.sections[0].value != revealed {
+            text.sections[0].value = revealed;
+        }
+    }
+
+    let choice_list = match choice_list_query.get_single() {
+        Ok(choice_list) => choice_list,
+        Err(_) => return
+    };
+    let choices = if active.showing_choices(&library) {
+        Some((active.choices.clone(), active.selected))
+    }
+    else {
+        None
+    };
+    if choices == *shown_choices && new_box_query.is_empty() {
+        return;
+    }
+
+    commands.entity(choice_list).despawn_descendants();
+    if let (Some((indices, selected)), Some(node)) = (choices.as_ref(), active.current_node(&library)) {
+        commands.entity(choice_list).with_children(|parent| {
+            for (position, index) in indices.iter().enumerate() {
+                let (prefix, color) = if position == *selected { ("> ", SELECTED_CHOICE_COLOR) } else { ("  ", CHOICE_COLOR) };
+                let text = format!("{}{}", prefix, node.choices[*index].text);
+                parent.spawn(TextBundle::from_section(text, text_style(&asset_server, 20., color)));
+            }
+        });
+    }
+    *shown_choices = choices;
+}
+
+pub fn close_dialogue_box(
+    mut commands: Commands,
+    dialogue_box_query: Query<Entity, With<DialogueBox>>
+) {
+    for entity in dialogue_box_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,13 +1,21 @@
 use bevy::prelude::*;
-use crate::IconAssets;
+use crate::{IconAssets, input::*, item::*, crop::CropType, player::Player, tool::EquippedTool, energy::{Energy, EatEvent}, skill::*, economy::{Wallet, ShipEvent, CropMarket, market::MARKET_CROPS}, calendar::{Calendar, Clock}, npc::{GiftEvent, Relationships, friendship::{FriendshipConfig, KnownRecipes}}, placement::{Placing, StartPlacementEvent}, chest::{SlotOwner, TransferEvent}};
 
 use super::{*, colors::*};
 
 pub const INVENTORY_COLS: usize = 18;
-pub const INVENTORY_ROWS: usize = 9;
-pub const INVENTORY_Y_SIZE: f32 = 350.;
+pub const INVENTORY_ROWS: usize = PLAYER_INVENTORY_SIZE / INVENTORY_COLS;
+pub const INVENTORY_Y_SIZE: f32 = INVENTORY_ROWS as f32 * (ICON_SIZE + 4.);
+pub const CHEST_ROWS: usize = CHEST_INVENTORY_SIZE / INVENTORY_COLS;
+pub const CHEST_Y_SIZE: f32 = CHEST_ROWS as f32 * (ICON_SIZE + 4.);
 pub const INVENTORY_X_SIZE: f32 = 600.;
 pub const ICON_SIZE: f32 = 32.;
+pub const INVENTORY_TAB_X_SIZE: f32 = 70.;
+pub const INVENTORY_TAB_Y_SIZE: f32 = 22.;
+pub const ENERGY_BAR_X_SIZE: f32 = 150.;
+pub const ENERGY_BAR_Y_SIZE: f32 = 14.;
+pub const ENERGY_BAR_COLOR: Color = Color::rgb(0.3, 0.8, 0.2);
+pub const EXHAUSTED_ENERGY_BAR_COLOR: Color = Color::rgb(0.8, 0.2, 0.2);
 
 #[derive(Component)]
 pub enum GameButton {
@@ -16,6 +24,151 @@ pub enum GameButton {
 #[derive(Component)]
 pub struct InventoryUi;
 
+/// the slots of an open chest, shown above the players inventory
+#[derive(Component)]
+pub struct ChestUi;
+
+/// the background of a single inventory slot
+#[derive(Component)]
+pub struct InventorySlot(pub usize, pub SlotOwner);
+
+#[derive(Component)]
+pub struct InventorySlotIcon(pub usize, pub SlotOwner);
+
+/// the stack size, or the name of items that don't have an icon yet
+#[derive(Component)]
+pub struct InventorySlotText(pub usize, pub SlotOwner);
+
+/// the pages of the inventory, only one is visible at a time
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryPage {
+    Items,
+    Skills,
+    Prices,
+    Relationships
+}
+
+impl InventoryPage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InventoryPage::Items => "Items",
+            InventoryPage::Skills => "Skills",
+            InventoryPage::Prices => "Prices",
+            InventoryPage::Relationships => "Friends"
+        }
+    }
+}
+
+/// the button above the inventory that switches to a page
+#[derive(Component)]
+pub struct InventoryTab(pub InventoryPage);
+
+#[derive(Component)]
+pub struct SkillText(pub Skill);
+
+/// a crops line on the price board
+#[derive(Component)]
+pub struct PriceText(pub CropType);
+
+/// the relationships page, its lines are respawned whenever a relationship changes since villagers are only added once the player meets them
+#[derive(Component)]
+pub struct RelationshipList;
+
+/// explains what clicking a slot does in the current `InventoryMode`
+#[derive(Component)]
+pub struct InventoryModeText;
+
+#[derive(Component)]
+pub struct WalletText;
+
+#[derive(Component)]
+pub struct CalendarText;
+
+/// the part of the energy bar that shrinks as the player gets tired
+#[derive(Component)]
+pub struct EnergyBarFill;
+
+/// not every item has an icon yet
+fn item_icon(item: &Item, icons: &IconAssets) -> Option<Handle<Image>> {
+    let crop_type = match item {
+        Item::Crop(crop_type, _) | Item::Seeds(crop_type) => crop_type,
+        _ => return None
+    };
+    match crop_type {
+        CropType::Potato => Some(icons.potato.clone()),
+        CropType::Carrot => Some(icons.carrot.clone()),
+        CropType::Corn => Some(icons.corn.clone()),
+        CropType::Cabbage => Some(icons.cabbage.clone()),
+        CropType::Dead => None
+    }
+}
+
+/// short label for the slot text of items without an icon i.e `Wa` for the watering can
+fn item_label(item: &Item) -> String {
+    match item {
+        Item::Tool(tool) => {
+            let name = tool.kind.name();
+            format!("{}{}", name.chars().take(2).collect::<String>(), if tool.level > 0 { tool.level.to_string() } else { String::new() })
+        },
+        Item::Seeds(_) => "S".to_string(),
+        Item::Egg(_) => "Eg".to_string(),
+        Item::Milk(_) => "Mi".to_string(),
+        _ => item.name().chars().take(2).collect()
+    }
+}
+
+/// keeps the inventory slots in sync with the players `Inventory` and the `Inventory` of the open chest
+pub fn refresh_inventory_ui(
+    player_query: Query<(&Inventory, ChangeTrackers<Inventory>, &EquippedTool, ChangeTrackers<EquippedTool>), With<Player>>,
+    chest_query: Query<(&Inventory, ChangeTrackers<Inventory>), Without<Player>>,
+    inventory_mode: Res<InventoryMode>,
+    new_slot_query: Query<(), Added<InventorySlot>>,
+    mut slot_query: Query<(&InventorySlot, &mut BackgroundColor)>,
+    mut icon_query: Query<(&InventorySlotIcon, &mut UiImage, &mut Visibility)>,
+    mut text_query: Query<(&InventorySlotText, &mut Text)>,
+    icons: Res<IconAssets>
+) {
+    let (inventory, inventory_tracker, equipped_tool, equipped_tracker) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return
+    };
+    let chest = match *inventory_mode {
+        InventoryMode::Chest(entity) => chest_query.get(entity).ok(),
+        _ => None
+    };
+    let chest_changed = inventory_mode.is_changed() || chest.as_ref().map_or(false, |(_, chest_tracker)| chest_tracker.is_changed());
+    // the slots are spawned after the player so they also need filling in once they are added
+    if !inventory_tracker.is_changed() && !equipped_tracker.is_changed() && !chest_changed && new_slot_query.is_empty() {
+        return;
+    }
+    let chest_inventory = chest.map(|(chest_inventory, _)| chest_inventory);
+    let slot_stack = |index: usize, owner: SlotOwner| match owner {
+        SlotOwner::Player => inventory.get(index),
+        SlotOwner::Chest => chest_inventory.and_then(|chest_inventory| chest_inventory.get(index))
+    };
+
+    for (slot, mut color) in slot_query.iter_mut() {
+        let equipped = slot.1 == SlotOwner::Player && equipped_tool.0 == Some(slot.0);
+        *color = if equipped { PRESSED_BUTTON.into() } else { NORMAL_BUTTON.into() };
+    }
+    for (slot_icon, mut image, mut visibility) in icon_query.iter_mut() {
+        match slot_stack(slot_icon.0, slot_icon.1).and_then(|stack| item_icon(&stack.item, &icons)) {
+            Some(icon) => {
+                *image = UiImage(icon);
+                *visibility = Visibility::VISIBLE;
+            },
+            None => *visibility = Visibility::INVISIBLE
+        }
+    }
+    for (slot_text, mut text) in text_query.iter_mut() {
+        text.sections[0].value = match slot_stack(slot_text.0, slot_text.1) {
+            Some(stack) if item_icon(&stack.item, &icons).is_none() => item_label(&stack.item),
+            Some(stack) if stack.count > 1 => stack.count.to_string(),
+            _ => String::new()
+        };
+    }
+}
+
 pub fn game_ui_interact_system(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor, &GameButton),
@@ -47,24 +200,287 @@ pub fn game_ui_interact_system(
     }
 }
 
-pub fn game_menu_setup(
+/// clicking a slot in the open inventory eats, ships, gifts or moves whatever is in it depending on the `InventoryMode`
+pub fn click_inventory_slots(
+    interaction_query: Query<(&Interaction, &InventorySlot), Changed<Interaction>>,
+    inventory_mode: Res<InventoryMode>,
+    action_input: Res<ActionInput>,
+    player_query: Query<&Inventory, With<Player>>,
+    mut eat_events: EventWriter<EatEvent>,
+    mut ship_events: EventWriter<ShipEvent>,
+    mut gift_events: EventWriter<GiftEvent>,
+    mut placement_events: EventWriter<StartPlacementEvent>,
+    mut transfer_events: EventWriter<TransferEvent>
+) {
+    for (interaction, slot) in interaction_query.iter() {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        if let InventoryMode::Chest(_) = *inventory_mode {
+            transfer_events.send(TransferEvent {
+                owner: slot.1,
+                slot: slot.0,
+                all: action_input.pressed(Action::QuickTransfer)
+            });
+            continue;
+        }
+        // the chest slots are hidden outside of chest mode
+        if slot.1 != SlotOwner::Player {
+            continue;
+        }
+        match *inventory_mode {
+            InventoryMode::Use => {
+                let placeable = player_query.get_single().ok()
+                    .and_then(|inventory| inventory.get(slot.0))
+                    .map_or(false, |stack| Placing::from_item(&stack.item).is_some());
+                if placeable {
+                    placement_events.send(StartPlacementEvent(slot.0));
+                }
+                else {
+                    eat_events.send(EatEvent(slot.0));
+                }
+            },
+            InventoryMode::Ship => ship_events.send(ShipEvent(slot.0)),
+            InventoryMode::Gift(_) => gift_events.send(GiftEvent(slot.0)),
+            InventoryMode::Chest(_) => {}
+        }
+    }
+}
+
+/// the inventory can also be opened by other systems i.e the shipping bin so it is shown whenever the state is entered
+pub fn show_inventory(
+    mut inventory_ui: Query<&mut Visibility, With<InventoryUi>>,
+    mut chest_ui: Query<&mut Visibility, (With<ChestUi>, Without<InventoryUi>)>,
+    mut mode_text_query: Query<&mut Text, With<InventoryModeText>>,
+    inventory_mode: Res<InventoryMode>
+) {
+    if let Ok(mut visibility) = inventory_ui.get_single_mut() {
+        *visibility = Visibility::VISIBLE;
+    }
+    if let Ok(mut visibility) = chest_ui.get_single_mut() {
+        *visibility = if matches!(*inventory_mode, InventoryMode::Chest(_)) { Visibility::VISIBLE } else { Visibility::INVISIBLE };
+    }
+    if let Ok(mut text) = mode_text_query.get_single_mut() {
+        text.sections[0].value = inventory_mode.hint().to_string();
+    }
+}
+
+pub fn hide_inventory(
+    mut inventory_ui: Query<&mut Visibility, With<InventoryUi>>,
+    mut chest_ui: Query<&mut Visibility, (With<ChestUi>, Without<InventoryUi>)>,
+    mut inventory_mode: ResMut<InventoryMode>
+) {
+    if let Ok(mut visibility) = inventory_ui.get_single_mut() {
+        *visibility = Visibility::INVISIBLE;
+    }
+    if let Ok(mut visibility) = chest_ui.get_single_mut() {
+        *visibility = Visibility::INVISIBLE;
+    }
+    *inventory_mode = InventoryMode::Use;
+}
+
+/// keeps the coins and date on the HUD up to date
+pub fn update_hud_text(
+    player_query: Query<(&Wallet, ChangeTrackers<Wallet>), With<Player>>,
+    new_text_query: Query<(), Or<(Added<WalletText>, Added<CalendarText>)>>,
+    calendar: Res<Calendar>,
+    clock: Res<Clock>,
+    mut wallet_text_query: Query<&mut Text, (With<WalletText>, Without<CalendarText>)>,
+    mut calendar_text_query: Query<&mut Text, (With<CalendarText>, Without<WalletText>)>
+) {
+    // the HUD is spawned after the player so it also needs filling in once it is added
+    let refresh = !new_text_query.is_empty();
+    if let (Ok((wallet, wallet_tracker)), Ok(mut text)) = (player_query.get_single(), wallet_text_query.get_single_mut()) {
+        if refresh || wallet_tracker.is_changed() {
+            text.sections[0].value = format!("{}g", wallet.coins);
+        }
+    }
+    if refresh || calendar.is_changed() || clock.is_changed() {
+        if let Ok(mut text) = calendar_text_query.get_single_mut() {
+            // the clock changes every frame but the text only every ten in-game minutes
+            let date = format!("{}  {}", calendar.describe(), clock.describe());
+            if text.sections[0].value != date {
+                text.sections[0].value = date;
+            }
+        }
+    }
+}
+
+pub fn switch_inventory_page(
+    mut tab_query: Query<(&Interaction, &InventoryTab, &mut BackgroundColor), Changed<Interaction>>,
+    mut page_query: Query<(&InventoryPage, &mut Visibility)>
+) {
+    for (interaction, tab, mut color) in tab_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                for (page, mut visibility) in page_query.iter_mut() {
+                    *visibility = if *page == tab.0 { Visibility::VISIBLE } else { Visibility::INVISIBLE };
+                }
+                *color = PRESSED_BUTTON.into();
+            }
+            Interaction::Hovered => {
+                *color = NORMAL_BUTTON_HOVER.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+/// keeps the skills page in sync with the players `Skills`
+pub fn refresh_skills_ui(
+    player_query: Query<(&Skills, ChangeTrackers<Skills>), With<Player>>,
+    new_text_query: Query<(), Added<SkillText>>,
+    mut text_query: Query<(&SkillText, &mut Text)>
+) {
+    let (skills, skills_tracker) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return
+    };
+    if !skills_tracker.is_changed() && new_text_query.is_empty() {
+        return;
+    }
+    for (skill_text, mut text) in text_query.iter_mut() {
+        let skill = skill_text.0;
+        let progress = match skills.next_level_experience(skill) {
+            Some(next) => format!("{}/{} xp", skills.experience(skill), next),
+            None => "max".to_string()
+        };
+        let perks: Vec<&str> = SKILL_PERKS.iter()
+            .filter(|(perk_skill, level, _)| *perk_skill == skill && skills.level(skill) >= *level)
+            .map(|(_, _, perk)| perk.name())
+            .collect();
+        text.sections[0].value = format!("{}  Lv {}  ({})  {}", skill.name(), skills.level(skill), progress, perks.join(", "));
+    }
+}
+
+/// the price board shows what a normal quality crop sells for today and its prices over the last days
+pub fn refresh_price_board(
+    crop_market: Res<CropMarket>,
+    calendar: Res<Calendar>,
+    new_text_query: Query<(), Added<PriceText>>,
+    mut text_query: Query<(&PriceText, &mut Text)>
+) {
+    if !crop_market.is_changed() && !calendar.is_changed() && new_text_query.is_empty() {
+        return;
+    }
+    let day = calendar.total_days();
+    for (price_text, mut text) in text_query.iter_mut() {
+        let crop_type = &price_text.0;
+        let demand = if crop_market.market.spikes.contains(crop_type) { "  high demand!" } else { "" };
+        let history: Vec<String> = crop_market.market.history.get(crop_type)
+            .map(|history| history.iter().map(|point| point.price.to_string()).collect())
+            .unwrap_or_default();
+        text.sections[0].value = format!(
+            "{:?}  {}g{}  last days: {}",
+            crop_type,
+            crop_market.market.price(crop_type, day, &crop_market.config),
+            demand,
+            history.join(" ")
+        );
+    }
+}
+
+/// lists every villager the player has met with their hearts and the recipes they have shared
+pub fn refresh_relationships_ui(
     mut commands: Commands,
-    mut state: ResMut<State<GameState>>,
-    icons: Res<IconAssets>
+    relationships: Res<Relationships>,
+    known_recipes: Res<KnownRecipes>,
+    config: Res<FriendshipConfig>,
+    new_list_query: Query<(), Added<RelationshipList>>,
+    list_query: Query<Entity, With<RelationshipList>>,
+    asset_server: Res<AssetServer>
 ) {
-    let main_inventory_node = NodeBundle {
-        style: Style {
-            size: Size::new(Val::Px(INVENTORY_X_SIZE), Val::Px(INVENTORY_Y_SIZE)),
-            flex_wrap: FlexWrap::Wrap,
-            position_type: PositionType::Absolute,
-            position: UiRect::new(Val::Percent(27.), Val::Auto, Val::Percent(30.), Val::Auto),
-            ..Default::default()
-        },
-        visibility: Visibility::INVISIBLE,
-        background_color: Color::hex("CC6600").unwrap().into(),
+    let list = match list_query.get_single() {
+        Ok(list) => list,
+        Err(_) => return
+    };
+    if !relationships.is_changed() && !known_recipes.is_changed() && new_list_query.is_empty() {
+        return;
+    }
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 14.,
+        color: Color::WHITE
+    };
+    let line_style = Style {
+        size: Size::new(Val::Percent(100.), Val::Px(ICON_SIZE / 2.)),
+        margin: UiRect::new(Val::Px(4.), Val::Px(0.), Val::Px(1.), Val::Px(0.)),
         ..Default::default()
     };
 
+    let mut names: Vec<&String> = relationships.0.keys().collect();
+    names.sort();
+    let mut recipes: Vec<&str> = known_recipes.0.iter().map(|recipe| recipe.as_str()).collect();
+    recipes.sort();
+
+    commands.entity(list).despawn_descendants();
+    commands.entity(list).with_children(|parent| {
+        for name in names {
+            let relationship = &relationships.0[name];
+            let max_hearts = config.max_points / config.points_per_heart.max(1);
+            let mut today = Vec::new();
+            if relationship.talked_today {
+                today.push("talked today");
+            }
+            if relationship.gifted_today {
+                today.push("gifted today");
+            }
+            parent.spawn(TextBundle::from_section(
+                format!("{}  {}/{} hearts ({} points)  {}", name, relationship.hearts(&config), max_hearts, relationship.points, today.join(", ")),
+                text_style.clone()
+            ).with_style(line_style.clone()));
+        }
+        let recipes = if recipes.is_empty() { "none yet".to_string() } else { recipes.join(", ") };
+        parent.spawn(TextBundle::from_section(format!("Recipes: {}", recipes), text_style.clone()).with_style(line_style.clone()));
+    });
+}
+
+pub fn update_energy_bar(
+    player_query: Query<&Energy, With<Player>>,
+    mut energy_bar_query: Query<(&mut Style, &mut BackgroundColor), With<EnergyBarFill>>
+) {
+    let (energy, (mut style, mut color)) = match (player_query.get_single(), energy_bar_query.get_single_mut()) {
+        (Ok(energy), Ok(energy_bar)) => (energy, energy_bar),
+        _ => return
+    };
+    let width = Val::Percent(energy.fraction() * 100.);
+    if style.size.width != width {
+        style.size.width = width;
+    }
+    let fill_color = if energy.is_exhausted() { EXHAUSTED_ENERGY_BAR_COLOR } else { ENERGY_BAR_COLOR };
+    if color.0 != fill_color {
+        color.0 = fill_color;
+    }
+}
+
+/// opens and closes the inventory with the inventory action, the pause action closes it as well
+pub fn toggle_inventory(
+    mut action_input: ResMut<ActionInput>,
+    mut inventory_ui: Query<&mut Visibility, With<InventoryUi>>,
+    mut state: ResMut<State<GameState>>
+) {
+    let mut inventory_ui = match inventory_ui.get_single_mut() {
+        Ok(inventory_ui) => inventory_ui,
+        Err(_) => return
+    };
+    if state.current() == &GameState::Game && action_input.just_pressed(Action::Inventory) {
+        state.overwrite_set(GameState::Inventory).unwrap();
+        *inventory_ui = Visibility::VISIBLE;
+        action_input.consume(Action::Inventory);
+    }
+    else if state.current() == &GameState::Inventory
+        && (action_input.just_pressed(Action::Inventory) || action_input.just_pressed(Action::Pause)) {
+        state.overwrite_set(GameState::Game).unwrap();
+        *inventory_ui = Visibility::INVISIBLE;
+        action_input.consume(Action::Inventory);
+        action_input.consume(Action::Pause);
+    }
+}
+
+/// spawns `rows` rows of slots showing the inventory of the `owner`
+fn spawn_inventory_slots(parent: &mut ChildBuilder, owner: SlotOwner, rows: usize, text_style: &TextStyle) {
     let inventory_col_node = NodeBundle {
         style: Style {
             size: Size::new(Val::Percent(100.), Val::Px(34.)),
@@ -84,6 +500,57 @@ pub fn game_menu_setup(
         ..Default::default()
     };
 
+    for row in 0..rows {
+        let mut col_node = parent.spawn(inventory_col_node.clone());
+        for col in 0..INVENTORY_COLS {
+            let index = row * INVENTORY_COLS + col;
+            col_node.with_children(|parent| {
+                parent.spawn(inventory_node.clone())
+                .insert(InventorySlot(index, owner))
+                .insert(Interaction::default())
+                .with_children(|parent| {
+                    parent.spawn(ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                            ..Default::default()
+                        },
+                        visibility: Visibility::INVISIBLE,
+                        ..Default::default()
+                    })
+                        .insert(InventorySlotIcon(index, owner))
+                    ;
+                    parent.spawn(TextBundle::from_section("", text_style.clone()).with_style(Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect::new(Val::Px(2.), Val::Auto, Val::Auto, Val::Px(1.)),
+                        ..Default::default()
+                    }))
+                        .insert(InventorySlotText(index, owner))
+                    ;
+                });
+            });
+        }
+    }
+}
+
+pub fn game_menu_setup(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    icons: Res<IconAssets>,
+    asset_server: Res<AssetServer>
+) {
+    let main_inventory_node = NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(INVENTORY_X_SIZE), Val::Px(INVENTORY_Y_SIZE)),
+            flex_wrap: FlexWrap::Wrap,
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Percent(27.), Val::Auto, Val::Percent(30.), Val::Auto),
+            ..Default::default()
+        },
+        visibility: Visibility::INVISIBLE,
+        background_color: Color::hex("CC6600").unwrap().into(),
+        ..Default::default()
+    };
+
     let inventory_button_node = NodeBundle {
         style: Style {
             size: Size::new(Val::Px(ICON_SIZE), Val::Px(ICON_SIZE)),
@@ -118,24 +585,185 @@ pub fn game_menu_setup(
         })
     ;
 
+    // spawn the energy bar
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size::new(Val::Px(ENERGY_BAR_X_SIZE), Val::Px(ENERGY_BAR_Y_SIZE)),
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Percent(2.), Val::Auto, Val::Auto, Val::Percent(2.)),
+            padding: UiRect::all(Val::Px(2.)),
+            ..Default::default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..Default::default()
+    })
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    ..Default::default()
+                },
+                background_color: ENERGY_BAR_COLOR.into(),
+                ..Default::default()
+            })
+                .insert(EnergyBarFill)
+            ;
+        })
+    ;
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 14.,
+        color: Color::WHITE
+    };
+
+    // spawn the coins and date above the energy bar
+    commands.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            position: UiRect::new(Val::Percent(2.), Val::Auto, Val::Auto, Val::Percent(2.)),
+            margin: UiRect::bottom(Val::Px(ENERGY_BAR_Y_SIZE + 4.)),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("", text_style.clone()))
+                .insert(WalletText)
+            ;
+            parent.spawn(TextBundle::from_section("", text_style.clone()))
+                .insert(CalendarText)
+            ;
+        })
+    ;
+
+    let inventory_page_node = NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+            flex_wrap: FlexWrap::Wrap,
+            position_type: PositionType::Absolute,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let inventory_tab_node = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(INVENTORY_TAB_X_SIZE), Val::Px(INVENTORY_TAB_Y_SIZE)),
+            margin: UiRect::right(Val::Px(2.)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..Default::default()
+    };
+
     // spawn inventory
     commands.spawn(main_inventory_node)
         .insert(InventoryUi)
         .with_children(|parent| {
-            for _ in 0..INVENTORY_ROWS {
-                let mut col_node = parent.spawn(inventory_col_node.clone());
-                for _ in 0..INVENTORY_COLS {
-                    col_node.with_children(|parent| {
-                        parent.spawn(inventory_node.clone())
-                        .with_children(|parent| {
-                            parent.spawn(ImageBundle {
-                                image: UiImage(icons.corn.clone()),
+            // the tabs sit right above the inventory
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect::new(Val::Px(0.), Val::Auto, Val::Px(-INVENTORY_TAB_Y_SIZE - 2.), Val::Auto),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+                .with_children(|parent| {
+                    for page in [InventoryPage::Items, InventoryPage::Skills, InventoryPage::Prices, InventoryPage::Relationships] {
+                        parent.spawn(inventory_tab_node.clone())
+                            .insert(InventoryTab(page))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(page.name(), text_style.clone()));
+                            })
+                        ;
+                    }
+                })
+            ;
+
+            // the open chest sits above the tabs
+            parent.spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(INVENTORY_X_SIZE), Val::Px(CHEST_Y_SIZE)),
+                    flex_wrap: FlexWrap::Wrap,
+                    position_type: PositionType::Absolute,
+                    position: UiRect::new(Val::Px(0.), Val::Auto, Val::Px(-INVENTORY_TAB_Y_SIZE - CHEST_Y_SIZE - 6.), Val::Auto),
+                    ..Default::default()
+                },
+                visibility: Visibility::INVISIBLE,
+                background_color: Color::hex("CC6600").unwrap().into(),
+                ..Default::default()
+            })
+                .insert(ChestUi)
+                .with_children(|parent| spawn_inventory_slots(parent, SlotOwner::Chest, CHEST_ROWS, &text_style))
+            ;
+
+            parent.spawn(inventory_page_node.clone())
+                .insert(InventoryPage::Items)
+                .with_children(|parent| spawn_inventory_slots(parent, SlotOwner::Player, INVENTORY_ROWS, &text_style))
+            ;
+
+            parent.spawn(NodeBundle {
+                visibility: Visibility::INVISIBLE,
+                ..inventory_page_node.clone()
+            })
+                .insert(InventoryPage::Skills)
+                .with_children(|parent| {
+                    for skill in Skill::ALL {
+                        parent.spawn(TextBundle::from_section("", text_style.clone())
+                            .with_style(Style {
+                                size: Size::new(Val::Percent(100.), Val::Px(ICON_SIZE / 2.)),
+                                margin: UiRect::new(Val::Px(4.), Val::Px(0.), Val::Px(2.), Val::Px(0.)),
                                 ..Default::default()
-                            });
-                        });
-                    });
-                }
-            }
+                            })
+                        )
+                            .insert(SkillText(skill))
+                        ;
+                    }
+                })
+            ;
+
+            parent.spawn(NodeBundle {
+                visibility: Visibility::INVISIBLE,
+                ..inventory_page_node.clone()
+            })
+                .insert(InventoryPage::Prices)
+                .with_children(|parent| {
+                    for crop_type in MARKET_CROPS {
+                        parent.spawn(TextBundle::from_section("", text_style.clone())
+                            .with_style(Style {
+                                size: Size::new(Val::Percent(100.), Val::Px(ICON_SIZE / 2.)),
+                                margin: UiRect::new(Val::Px(4.), Val::Px(0.), Val::Px(1.), Val::Px(0.)),
+                                ..Default::default()
+                            })
+                        )
+                            .insert(PriceText(crop_type))
+                        ;
+                    }
+                })
+            ;
+
+            parent.spawn(NodeBundle {
+                visibility: Visibility::INVISIBLE,
+                ..inventory_page_node.clone()
+            })
+                .insert(InventoryPage::Relationships)
+                .insert(RelationshipList)
+            ;
+
+            parent.spawn(TextBundle::from_section("", text_style.clone())
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect::new(Val::Px(0.), Val::Auto, Val::Px(INVENTORY_Y_SIZE + 2.), Val::Auto),
+                    ..Default::default()
+                })
+            )
+                .insert(InventoryModeText)
+            ;
         })
     ;
     state.overwrite_set(GameState::LoadingGame).unwrap();
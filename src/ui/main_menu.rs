@@ -1,7 +1,7 @@
 use std::{fs::{File, read_to_string}, io::BufReader};
 
-use bevy::{prelude::*, app::AppExit, ecs::schedule::ShouldRun};
-use crate::{NextState, save::{SaveGames, SaveName}};
+use bevy::{prelude::*, app::AppExit, ecs::schedule::ShouldRun, log};
+use crate::{NextState, input::*, save::{SaveGames, SaveName, SaveConfig, SaveLoadError, archive::{export_save, import_save}}};
 
 use super::{*, colors::*};
 
@@ -9,6 +9,7 @@ pub const MAIN_MENU_BUTTON_WIDTH: f32 = 250.;
 pub const MAIN_MENU_BUTTON_HEIGHT: f32 = 65.;
 pub const LOAD_MENU_BUTTON_WIDTH: f32 = 350.;
 pub const LOAD_MENU_BUTTON_HEIGHT: f32 = 65.;
+pub const EXPORT_BUTTON_WIDTH: f32 = 120.;
 
 #[derive(Debug, Component, PartialEq, Eq)]
 pub enum MainMenuButton {
@@ -20,6 +21,9 @@ pub enum MainMenuButton {
 #[derive(Debug, Component, PartialEq, Eq)]
 pub struct LoadGameButton(pub String);
 
+#[derive(Debug, Component, PartialEq, Eq)]
+pub struct ExportSaveButton(pub String);
+
 #[derive(Component)]
 pub struct MainMenu;
 
@@ -159,6 +163,7 @@ pub fn main_menu_setup(
 pub fn load_game_menu_setup(
     mut commands: Commands,
     mut main_menu_state: ResMut<State<MainMenuState>>,
+    mut load_error: ResMut<SaveLoadError>,
     asset_server: Res<AssetServer>
 ) {
     let save_games = read_to_string("saves/save_games.rson").expect("Save Games File Not Found");
@@ -192,20 +197,58 @@ pub fn load_game_menu_setup(
         }
     )
         .with_children(|parent| {
+            if let Some(error) = load_error.0.take() {
+                parent.spawn(TextBundle::from_section(
+                    error,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 25.,
+                        color: Color::RED
+                    }
+                ));
+            }
             for save_game in save_games.0.iter() {
-            parent.spawn(main_menu_button.clone())
-                .insert(LoadGameButton(save_game.name.clone()))
-                .with_children(|parent| {
-                        parent.spawn(
-                            TextBundle::from_section(
-                                save_game.date.as_str(),
-                                TextStyle {
-                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                                    font_size: 40.,
-                                    color: Color::WHITE
-                                }
-                            )
-                        );
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                    .with_children(|parent| {
+                        parent.spawn(main_menu_button.clone())
+                            .insert(LoadGameButton(save_game.name.clone()))
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        save_game.date.as_str(),
+                                        TextStyle {
+                                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                            font_size: 40.,
+                                            color: Color::WHITE
+                                        }
+                                    )
+                                );
+                            })
+                        ;
+                        let mut export_button = main_menu_button.clone();
+                        export_button.style.size = Size::new(Val::Px(EXPORT_BUTTON_WIDTH), Val::Px(LOAD_MENU_BUTTON_HEIGHT));
+                        export_button.style.margin = UiRect::left(Val::Px(10.));
+                        parent.spawn(export_button)
+                            .insert(ExportSaveButton(save_game.name.clone()))
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        "Export",
+                                        TextStyle {
+                                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                            font_size: 30.,
+                                            color: Color::WHITE
+                                        }
+                                    )
+                                );
+                            })
+                        ;
                     })
                 ;
             }
@@ -225,6 +268,14 @@ pub fn load_game_menu_setup(
                     })
                 ;
             }
+            parent.spawn(TextBundle::from_section(
+                "Drop a .rfsave file on the window to import it",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 25.,
+                    color: Color::GRAY
+                }
+            ));
     });
     main_menu_state.overwrite_set(MainMenuState::LoadGame).unwrap();
 }
@@ -238,9 +289,9 @@ pub fn load_game_button_system(
     mut next_state: ResMut<NextState>,
     mut main_menu_state: ResMut<State<MainMenuState>>,
     mut save_name: ResMut<SaveName>,
-    inputs: Res<Input<KeyCode>>
+    action_input: Res<ActionInput>
 ) {
-    if inputs.pressed(KeyCode::Escape) {
+    if action_input.just_pressed(Action::Pause) {
         state.overwrite_set(GameState::Unload).unwrap();
         next_state.0 = GameState::LoadingMainMenu;
         main_menu_state.overwrite_set(MainMenuState::NotActive).unwrap();
@@ -264,4 +315,70 @@ pub fn load_game_button_system(
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn export_save_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ExportSaveButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut state: ResMut<State<GameState>>,
+    mut next_state: ResMut<NextState>,
+    mut main_menu_state: ResMut<State<MainMenuState>>,
+    mut load_error: ResMut<SaveLoadError>
+) {
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                match export_save(&button.0) {
+                    Ok(_) => *color = Color::GREEN.into(),
+                    Err(err) => {
+                        log::error!("failed to export {}: {}", button.0, err);
+                        *color = Color::RED.into();
+                        // rebuild the load game menu so it shows the error
+                        load_error.0 = Some(format!("Couldn't export save: {}", err));
+                        state.overwrite_set(GameState::Unload).unwrap();
+                        next_state.0 = GameState::MainMenu;
+                        main_menu_state.overwrite_set(MainMenuState::LoadingLoadGame).unwrap();
+                    }
+                }
+            },
+            Interaction::Hovered => {
+                *color = NORMAL_BUTTON_HOVER.into();
+            },
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+/// imports any save archive that gets dropped onto the window while the load game menu is open
+pub fn import_dropped_save_system(
+    mut drop_events: EventReader<FileDragAndDrop>,
+    mut state: ResMut<State<GameState>>,
+    mut next_state: ResMut<NextState>,
+    mut main_menu_state: ResMut<State<MainMenuState>>,
+    mut load_error: ResMut<SaveLoadError>,
+    save_config: Res<SaveConfig>
+) {
+    let mut dropped = false;
+
+    for event in drop_events.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            dropped = true;
+            if let Err(err) = import_save(path_buf, save_config.codec) {
+                log::error!("failed to import {}: {}", path_buf.display(), err);
+                let file_name = path_buf.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                load_error.0 = Some(format!("Couldn't import {}: {}", file_name, err));
+            }
+        }
+    }
+
+    // rebuild the load game menu so the new slot or the error shows up
+    if dropped {
+        state.overwrite_set(GameState::Unload).unwrap();
+        next_state.0 = GameState::MainMenu;
+        main_menu_state.overwrite_set(MainMenuState::LoadingLoadGame).unwrap();
+    }
+}
@@ -1,13 +1,17 @@
 use bevy::{prelude::*, winit::WinitSettings};
 
-use crate::{GameState, NextState};
+use crate::{GameState, NextState, item::InventoryMode};
 
-use self::{main_menu::*, pause_menu::*, game_menu::*};
+use self::{main_menu::*, pause_menu::*, game_menu::*, controls_menu::*, shop_menu::*, dialogue_box::*, notification::*};
 
 mod main_menu;
 mod pause_menu;
 mod game_menu;
+mod controls_menu;
+mod shop_menu;
+mod dialogue_box;
 mod colors;
+pub mod notification;
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default)]
 pub enum MainMenuState {
@@ -33,6 +37,8 @@ impl Plugin for UIPlugin {
             )
             .add_system_set(SystemSet::on_update(MainMenuState::LoadGame)
                 .with_system(load_game_button_system)
+                .with_system(export_save_button_system)
+                .with_system(import_dropped_save_system)
             )
             .add_system_set(SystemSet::on_enter(MainMenuState::LoadingLoadGame)
                 .with_system(load_game_menu_setup)
@@ -42,6 +48,30 @@ impl Plugin for UIPlugin {
             )
             .add_system_set(SystemSet::on_update(GameState::Game)
                 .with_system(game_ui_interact_system)
+                .with_system(toggle_inventory)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Inventory)
+                .with_system(toggle_inventory)
+                .with_system(click_inventory_slots)
+                .with_system(switch_inventory_page)
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Inventory)
+                .with_system(show_inventory)
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Inventory)
+                .with_system(hide_inventory)
+            )
+            .insert_resource(InventoryMode::default())
+            .add_system(refresh_inventory_ui)
+            .add_system(update_hud_text)
+            .add_system(refresh_skills_ui)
+            .add_system(refresh_price_board)
+            .add_system(refresh_relationships_ui)
+            .add_system(update_energy_bar)
+            .add_event::<NotificationEvent>()
+            .add_system(show_notifications)
+            .add_system_set(SystemSet::on_update(GameState::Game)
+                .with_system(hide_notifications)
             )
             .add_system_set(SystemSet::on_enter(GameState::Unload)
                 .with_system(unload)
@@ -56,6 +86,31 @@ impl Plugin for UIPlugin {
                 .with_system(handle_pause_menu_input)
                 .with_system(check_pause_input)
             )
+            .insert_resource(Rebinding::default())
+            .add_system_set(SystemSet::on_enter(GameState::LoadingControls)
+                .with_system(controls_menu_setup)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Controls)
+                .with_system(controls_button_system)
+                .with_system(capture_rebinding)
+                .with_system(update_binding_texts)
+            )
+            .add_system_set(SystemSet::on_enter(GameState::LoadingShop)
+                .with_system(shop_menu_setup)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Shop)
+                .with_system(shop_button_system)
+                .with_system(refresh_shop_lists)
+            )
+            .add_system_set(SystemSet::on_enter(GameState::LoadingDialogue)
+                .with_system(dialogue_box_setup)
+            )
+            .add_system_set(SystemSet::on_update(GameState::Dialogue)
+                .with_system(refresh_dialogue_box)
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Dialogue)
+                .with_system(close_dialogue_box)
+            )
         ;
     }
 }
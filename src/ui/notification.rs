@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use super::*;
+
+/// how long a notification stays on screen
+pub const NOTIFICATION_SECONDS: f32 = 4.;
+pub const NOTIFICATION_COLOR: Color = Color::rgba(0.1, 0.07, 0.05, 0.85);
+pub const NOTIFICATION_TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+
+/// shows a short message at the top of the screen, i.e what happened overnight
+#[derive(Debug, Clone)]
+pub struct NotificationEvent(pub String);
+
+/// despawned once the timer finishes
+#[derive(Component)]
+pub struct Notification(pub Timer);
+
+/// a new notification replaces the one on screen
+pub fn show_notifications(
+    mut commands: Commands,
+    mut notification_events: EventReader<NotificationEvent>,
+    notification_query: Query<Entity, With<Notification>>,
+    asset_server: Res<AssetServer>
+) {
+    let message = match notification_events.iter().last() {
+        Some(NotificationEvent(message)) => message,
+        None => return
+    };
+    for entity in notification_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                position: UiRect::top(Val::Px(40.)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Notification(Timer::from_seconds(NOTIFICATION_SECONDS, TimerMode::Once)))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(10.)),
+                        ..default()
+                    },
+                    background_color: NOTIFICATION_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(message.clone(), TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 22.,
+                        color: NOTIFICATION_TEXT_COLOR
+                    }));
+                })
+            ;
+        })
+    ;
+}
+
+pub fn hide_notifications(
+    mut commands: Commands,
+    mut notification_query: Query<(Entity, &mut Notification)>,
+    time: Res<Time>
+) {
+    for (entity, mut notification) in notification_query.iter_mut() {
+        if notification.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
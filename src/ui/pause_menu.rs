@@ -1,5 +1,5 @@
 use bevy::{prelude::*, app::AppExit};
-use crate::NextState;
+use crate::{NextState, input::*};
 
 use super::{*, colors::*};
 
@@ -8,20 +8,20 @@ use super::{*, colors::*};
 pub struct PauseMenu;
 
 pub fn check_pause_input(
-    mut inputs: ResMut<Input<KeyCode>>,
+    mut action_input: ResMut<ActionInput>,
     mut state: ResMut<State<GameState>>,
     mut commands: Commands,
     pause_menu_query: Query<Entity, With<PauseMenu>>
 ) {
-    if inputs.pressed(KeyCode::Escape) {
+    if action_input.just_pressed(Action::Pause) {
         if state.current() == &GameState::Game {
             state.overwrite_set(GameState::LoadingPause).unwrap();
-            inputs.reset_all();
+            action_input.consume(Action::Pause);
         }
         else if state.current() == &GameState::Pause {
             state.overwrite_set(GameState::Game).unwrap();
             commands.entity(pause_menu_query.single()).despawn_recursive();
-            inputs.reset_all();
+            action_input.consume(Action::Pause);
         }
     }
 }
@@ -111,6 +111,32 @@ pub fn setup_ui(
                     })
                     .insert(PauseMenuItem::Save)
                 ;
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(400.0), Val::Px(65.0)),
+                            // horizontally center child text
+                            justify_content: JustifyContent::Center,
+                            // vertically center child text
+                            align_items: AlignItems::Center,
+                            margin: UiRect::vertical(Val::Px(25.)),
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Controls",
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 40.0,
+                                color: Color::rgb(0.9, 0.9, 0.9),
+                            },
+                        ));
+                    })
+                    .insert(PauseMenuItem::Controls)
+                ;
                 parent
                     .spawn(ButtonBundle {
                         style: Style {
@@ -172,6 +198,7 @@ pub fn setup_ui(
 pub enum PauseMenuItem {
     Resume,
     Save,
+    Controls,
     ExitToMain,
     Exit,
 }
@@ -213,6 +240,11 @@ pub fn handle_pause_menu_input(
                         app_state.overwrite_set(GameState::Saving).unwrap();
                         next_state.0 = GameState::Game;
                         commands.entity(pause_menu).despawn_recursive();
+                    },
+                    // Handle Controls
+                    PauseMenuItem::Controls => {
+                        app_state.overwrite_set(GameState::LoadingControls).unwrap();
+                        commands.entity(pause_menu).despawn_recursive();
                     }
                 }
             },
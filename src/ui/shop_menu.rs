@@ -0,0 +1,251 @@
+use bevy::prelude::*;
+use crate::{input::*, item::Inventory, player::Player, economy::{Wallet, CropMarket}, calendar::Calendar, shop::*};
+
+use super::{*, colors::*};
+
+pub const SHOP_COLUMN_WIDTH: f32 = 360.;
+pub const SHOP_ROW_HEIGHT: f32 = 30.;
+
+#[derive(Component)]
+pub struct ShopMenu;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShopButton {
+    /// the index of the offer in the `ShopCatalog`
+    Buy(usize),
+    /// the inventory slot to sell
+    Sell(usize),
+    Leave
+}
+
+/// the column the buy buttons are spawned in
+#[derive(Component)]
+pub struct ShopBuyList;
+
+/// the column the sell buttons are spawned in
+#[derive(Component)]
+pub struct ShopSellList;
+
+#[derive(Component)]
+pub struct ShopCoinsText;
+
+fn text_style(asset_server: &AssetServer) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.,
+        color: Color::rgb(0.9, 0.9, 0.9)
+    }
+}
+
+fn row_button() -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(SHOP_COLUMN_WIDTH), Val::Px(SHOP_ROW_HEIGHT)),
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            padding: UiRect::horizontal(Val::Px(10.)),
+            margin: UiRect::vertical(Val::Px(2.)),
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    }
+}
+
+pub fn shop_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut state: ResMut<State<GameState>>
+) {
+    let text_style = text_style(&asset_server);
+
+    let column = NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            margin: UiRect::all(Val::Px(10.)),
+            ..default()
+        },
+        background_color: Color::hex("CC6600").unwrap().into(),
+        ..default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ShopMenu)
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::FlexStart,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(column.clone())
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Buy", text_style.clone()));
+                            parent.spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                                .insert(ShopBuyList)
+                            ;
+                        })
+                    ;
+                    parent.spawn(column.clone())
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Sell", text_style.clone()));
+                            parent.spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                                .insert(ShopSellList)
+                            ;
+                        })
+                    ;
+                })
+            ;
+            parent.spawn(TextBundle::from_section("", text_style.clone()))
+                .insert(ShopCoinsText)
+            ;
+            parent
+                .spawn(row_button())
+                .insert(ShopButton::Leave)
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section("Leave", text_style.clone()));
+                })
+            ;
+        })
+    ;
+    state.overwrite_set(GameState::Shop).unwrap();
+}
+
+/// respawns the buy and sell buttons whenever what can be bought or sold changes
+pub fn refresh_shop_lists(
+    mut commands: Commands,
+    player_query: Query<(&Inventory, ChangeTrackers<Inventory>, &Wallet, ChangeTrackers<Wallet>), With<Player>>,
+    new_menu_query: Query<(), Added<ShopMenu>>,
+    buy_list_query: Query<Entity, With<ShopBuyList>>,
+    sell_list_query: Query<Entity, With<ShopSellList>>,
+    mut coins_text_query: Query<&mut Text, With<ShopCoinsText>>,
+    catalog: Res<ShopCatalog>,
+    purchases: Res<ShopPurchases>,
+    crop_market: Res<CropMarket>,
+    calendar: Res<Calendar>,
+    asset_server: Res<AssetServer>
+) {
+    let (inventory, inventory_tracker, wallet, wallet_tracker) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return
+    };
+    let (buy_list, sell_list) = match (buy_list_query.get_single(), sell_list_query.get_single()) {
+        (Ok(buy_list), Ok(sell_list)) => (buy_list, sell_list),
+        _ => return
+    };
+    if new_menu_query.is_empty() && !inventory_tracker.is_changed() && !wallet_tracker.is_changed() && !purchases.is_changed() && !crop_market.is_changed() {
+        return;
+    }
+    let text_style = text_style(&asset_server);
+
+    if let Ok(mut text) = coins_text_query.get_single_mut() {
+        text.sections[0].value = format!("{}g", wallet.coins);
+    }
+
+    commands.entity(buy_list).despawn_descendants();
+    commands.entity(buy_list).with_children(|parent| {
+        for (index, offer) in catalog.stock(&calendar).filter(|(_, offer)| offer.available(inventory)) {
+            let remaining = offer.remaining(&purchases);
+            let price = match remaining {
+                Some(0) => "sold out".to_string(),
+                Some(remaining) => format!("{}g ({} left)", offer.price, remaining),
+                None => format!("{}g", offer.price)
+            };
+            parent
+                .spawn(row_button())
+                .insert(ShopButton::Buy(index))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(offer.item.name(), text_style.clone()));
+                    parent.spawn(TextBundle::from_section(price, text_style.clone()));
+                })
+            ;
+        }
+    });
+
+    commands.entity(sell_list).despawn_descendants();
+    commands.entity(sell_list).with_children(|parent| {
+        for (slot, stack) in inventory.slots.iter().enumerate() {
+            let (stack, price) = match stack {
+                Some(stack) => match crop_market.sell_price(&stack.item, calendar.total_days()) {
+                    Some(price) => (stack, price),
+                    None => continue
+                },
+                None => continue
+            };
+            parent
+                .spawn(row_button())
+                .insert(ShopButton::Sell(slot))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(format!("{} x{}", stack.item.name(), stack.count), text_style.clone()));
+                    parent.spawn(TextBundle::from_section(format!("{}g each", price), text_style.clone()));
+                })
+            ;
+        }
+    });
+}
+
+pub fn shop_button_system(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ShopButton),
+        (Changed<Interaction>, With<Button>)
+    >,
+    shop_menu_query: Query<Entity, With<ShopMenu>>,
+    mut action_input: ResMut<ActionInput>,
+    mut buy_events: EventWriter<BuyEvent>,
+    mut sell_events: EventWriter<SellEvent>,
+    mut state: ResMut<State<GameState>>
+) {
+    let mut leave = action_input.just_pressed(Action::Pause);
+    for (interaction, mut color, button) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                match *button {
+                    ShopButton::Buy(index) => buy_events.send(BuyEvent(index)),
+                    ShopButton::Sell(slot) => sell_events.send(SellEvent(slot)),
+                    ShopButton::Leave => leave = true
+                }
+            },
+            Interaction::Hovered => {
+                *color = NORMAL_BUTTON_HOVER.into();
+            },
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+    if leave {
+        // so leaving with the pause action doesn't open the pause menu straight away
+        action_input.consume(Action::Pause);
+        commands.entity(shop_menu_query.single()).despawn_recursive();
+        state.overwrite_set(GameState::Game).unwrap();
+    }
+}